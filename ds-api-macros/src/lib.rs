@@ -120,6 +120,17 @@ fn is_option(ty: &Type) -> bool {
     false
 }
 
+/// A `CancellationToken` parameter receives the agent's token instead of a
+/// JSON argument.
+fn is_cancellation_token(ty: &Type) -> bool {
+    if let Type::Path(tp) = ty
+        && let Some(seg) = tp.path.segments.last()
+    {
+        return seg.ident == "CancellationToken";
+    }
+    false
+}
+
 struct ToolMethod {
     tool_name: String,
     description: String,
@@ -132,15 +143,16 @@ struct ParamInfo {
     ty: Type,
     desc: String,
     optional: bool,
+    cancel: bool,
 }
 
 #[proc_macro_attribute]
 pub fn tool(attr: TokenStream, item: TokenStream) -> TokenStream {
     // 先尝试解析为独立 async fn
-    if let Ok(item_fn) = syn::parse::<ItemFn>(item.clone()) {
-        if item_fn.sig.asyncness.is_some() {
-            return tool_from_fn(attr, item_fn);
-        }
+    if let Ok(item_fn) = syn::parse::<ItemFn>(item.clone())
+        && item_fn.sig.asyncness.is_some()
+    {
+        return tool_from_fn(attr, item_fn);
     }
     // 否则走 impl 块路径
     tool_from_impl(attr, item)
@@ -176,11 +188,13 @@ fn tool_from_fn(attr: TokenStream, item_fn: ItemFn) -> TokenStream {
             let ty = (*pt.ty).clone();
             let desc = param_docs.get(&name).cloned().unwrap_or_default();
             let optional = is_option(&ty);
+            let cancel = is_cancellation_token(&ty);
             params.push(ParamInfo {
                 name,
                 ty,
                 desc,
                 optional,
+                cancel,
             });
         }
     }
@@ -195,7 +209,7 @@ fn tool_from_fn(attr: TokenStream, item_fn: ItemFn) -> TokenStream {
     let raw_tools_body = {
         let tool_name = &method.tool_name;
         let description = &method.description;
-        let prop_inserts = method.params.iter().map(|p| {
+        let prop_inserts = method.params.iter().filter(|p| !p.cancel).map(|p| {
             let pname = &p.name;
             let pdesc = &p.desc;
             let schema = type_to_json_schema(&p.ty);
@@ -208,7 +222,7 @@ fn tool_from_fn(attr: TokenStream, item_fn: ItemFn) -> TokenStream {
         let required: Vec<&str> = method
             .params
             .iter()
-            .filter(|p| !p.optional)
+            .filter(|p| !p.optional && !p.cancel)
             .map(|p| p.name.as_str())
             .collect();
         quote! {{
@@ -238,6 +252,9 @@ fn tool_from_fn(attr: TokenStream, item_fn: ItemFn) -> TokenStream {
             let pname = syn::Ident::new(&p.name, Span::call_site());
             let pname_str = &p.name;
             let ty = &p.ty;
+            if p.cancel {
+                return quote! { let #pname: #ty = __cancel.clone(); };
+            }
             quote! {
                 let #pname: #ty = match serde_json::from_value(
                     args.get(#pname_str).cloned().unwrap_or(serde_json::Value::Null)
@@ -272,6 +289,15 @@ fn tool_from_fn(attr: TokenStream, item_fn: ItemFn) -> TokenStream {
             }
 
             async fn call(&self, name: &str, args: serde_json::Value) -> serde_json::Value {
+                self.call_with_cancel(name, args, ds_api::CancellationToken::new()).await
+            }
+
+            async fn call_with_cancel(
+                &self,
+                name: &str,
+                args: serde_json::Value,
+                __cancel: ds_api::CancellationToken,
+            ) -> serde_json::Value {
                 match name {
                     #call_arm
                     _ => serde_json::json!({"error": format!("unknown tool: {}", name)}),
@@ -320,11 +346,13 @@ fn tool_from_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
                     let ty = (*pt.ty).clone();
                     let desc = param_docs.get(&name).cloned().unwrap_or_default();
                     let optional = is_option(&ty);
+                    let cancel = is_cancellation_token(&ty);
                     params.push(ParamInfo {
                         name,
                         ty,
                         desc,
                         optional,
+                        cancel,
                     });
                 }
             }
@@ -340,7 +368,7 @@ fn tool_from_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
     let raw_tools_body = tool_methods.iter().map(|m| {
         let tool_name = &m.tool_name;
         let description = &m.description;
-        let prop_inserts = m.params.iter().map(|p| {
+        let prop_inserts = m.params.iter().filter(|p| !p.cancel).map(|p| {
             let pname = &p.name;
             let pdesc = &p.desc;
             let schema = type_to_json_schema(&p.ty);
@@ -353,7 +381,7 @@ fn tool_from_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
         let required: Vec<&str> = m
             .params
             .iter()
-            .filter(|p| !p.optional)
+            .filter(|p| !p.optional && !p.cancel)
             .map(|p| p.name.as_str())
             .collect();
         quote! {{
@@ -383,6 +411,9 @@ fn tool_from_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
             let pname = syn::Ident::new(&p.name, Span::call_site());
             let pname_str = &p.name;
             let ty = &p.ty;
+            if p.cancel {
+                return quote! { let #pname: #ty = __cancel.clone(); };
+            }
             quote! {
                 let #pname: #ty = match serde_json::from_value(
                     args.get(#pname_str).cloned().unwrap_or(serde_json::Value::Null)
@@ -416,6 +447,15 @@ fn tool_from_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
             }
 
             async fn call(&self, name: &str, args: serde_json::Value) -> serde_json::Value {
+                self.call_with_cancel(name, args, ds_api::CancellationToken::new()).await
+            }
+
+            async fn call_with_cancel(
+                &self,
+                name: &str,
+                args: serde_json::Value,
+                __cancel: ds_api::CancellationToken,
+            ) -> serde_json::Value {
                 match name {
                     #(#call_arms)*
                     _ => serde_json::json!({"error": format!("unknown tool: {}", name)}),
@@ -56,6 +56,7 @@ async fn main() {
                 else { println!("Tool call {}({})", c.name, c.delta) }
             }
             Ok(AgentEvent::ToolResult(r)) => println!("-> {}", r.result),
+            Ok(_) => {}
         }
     }
 
@@ -89,6 +90,7 @@ async fn main() {
                 else { print!("{}", c.delta) }
             }
             Ok(AgentEvent::ToolResult(r)) => println!("[result] {}", r.result),
+            Ok(_) => {}
         }
     }
     println!(); // final newline after streamed text
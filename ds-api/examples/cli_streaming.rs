@@ -1,4 +1,5 @@
-use ds_api::{AgentEvent, DeepseekAgent, tool};
+use ds_api::raw::FinishReason;
+use ds_api::{AgentEvent, ApiClient, DeepseekAgent, RetryPolicy, tool};
 use futures::StreamExt;
 use serde_json::json;
 use std::io::{self, Write};
@@ -49,7 +50,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Create the agent once and recover it after each `.chat(...)` via `AgentStream::into_agent`.
     // `chat(self, ...)` consumes the agent, but `AgentStream::into_agent` can return it back.
-    let mut agent = DeepseekAgent::new(&token)
+    // Retry transient failures (429 / 5xx) so one overloaded response does not end the session.
+    let client = ApiClient::new(&token).with_retry(RetryPolicy::default());
+    let mut agent = DeepseekAgent::from_client(client, "deepseek-chat")
         .with_streaming()
         .add_tool(ShellTool)
         .with_system_prompt(
@@ -102,6 +105,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 Ok(AgentEvent::ToolResult(r)) => {
                     println!("\n[tool result] {} -> {}", r.name, r.result);
                 }
+                Ok(AgentEvent::Retry(r)) => {
+                    eprintln!(
+                        "\n[retry {}/{} in {:?}] {}",
+                        r.attempt, r.max_attempts, r.delay, r.error
+                    );
+                }
+                Ok(AgentEvent::TurnFinished {
+                    finish_reason: Some(FinishReason::Length),
+                }) => {
+                    eprintln!("\n[reply truncated: max_tokens reached]");
+                }
+                Ok(AgentEvent::MaxStepsReached { max_steps }) => {
+                    eprintln!("\n[stopped calling tools after {max_steps} steps]");
+                }
+                Ok(AgentEvent::BudgetExceeded(limit)) => {
+                    eprintln!("\n[stopped: {limit}]");
+                }
+                // Usage, other turn ends, and events for approval policies
+                // and checkpoints, which are not set here.
+                Ok(_) => {}
             }
         }
 
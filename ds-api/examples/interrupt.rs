@@ -97,7 +97,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("\n(injected message will be picked up before the next API turn)\n");
             }
 
-            Ok(_) => todo!(),
+            Ok(_) => {}
         }
     }
 
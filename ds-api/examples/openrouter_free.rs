@@ -69,6 +69,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 Ok(AgentEvent::ToolResult(r)) => {
                     println!("\n[tool result: {} -> {}]", r.name, r.result);
                 }
+                Ok(_) => {}
             }
         }
 
@@ -41,6 +41,7 @@ async fn main() {
             Ok(AgentEvent::ToolResult(res)) => {
                 println!("[tool result] {} -> {}", res.name, res.result);
             }
+            Ok(_) => {}
         }
     }
 }
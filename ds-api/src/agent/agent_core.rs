@@ -1,8 +1,16 @@
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 
-use crate::api::ApiClient;
-use crate::conversation::{Conversation, LlmSummarizer, Summarizer};
+use crate::agent::approval::{ApprovalDecision, ApprovalHandle, ApprovalPolicy, PendingToolCall};
+use crate::agent::budget::{Budget, BudgetLimit, RunSpend};
+use crate::agent::checkpoint::Checkpoint;
+use crate::agent::options::{RequestOptions, TurnContext, TurnHook};
+use crate::api::{ApiClient, ChatBackend, CostTracker, RetryEvent};
+use crate::conversation::{Conversation, ConversationStore, LlmSummarizer, Summarizer};
+use crate::raw::request::content_part::ContentPart;
 use crate::raw::request::message::{Message, Role};
+use crate::raw::{FinishReason, Usage};
 use crate::tool_trait::Tool;
 use serde_json::Value;
 use tokio::sync::mpsc;
@@ -47,8 +55,28 @@ pub struct ToolCallResult {
 ///   the full argument JSON.  Execution begins after all chunks for a turn are
 ///   delivered.
 /// - `ToolResult(ToolCallResult)` — a tool has finished executing.  One event is
-///   emitted per call, in the same order as the corresponding `ToolCall` events.
+///   emitted per call, in the same order as the corresponding `ToolCall` events
+///   unless [`with_parallel_tools`][DeepseekAgent::with_parallel_tools] is set,
+///   in which case results arrive as the tools finish.
+/// - `Retry(RetryEvent)` — an API request failed transiently and is about to be
+///   retried according to the client's [`RetryPolicy`][crate::api::RetryPolicy].
+/// - `Usage(Usage)` and `TurnFinished { finish_reason }` — emitted in that order
+///   once per API turn, after the turn's `Token` and `ToolCall` events and
+///   before any of its tools run.
+/// - `BudgetExceeded(BudgetLimit)` — the run hit a limit of the agent's
+///   [`Budget`]; always the last event of the run.
+/// - `MaxStepsReached { max_steps }` — the tool loop hit the agent's
+///   [`max_steps`][DeepseekAgent::with_max_steps] and the run ended with a
+///   final turn without tools; always the last event of the run.
+/// - `ApprovalRequired { call }` — a tool call needs a human decision before
+///   the turn's tools run; see [`with_approval`][DeepseekAgent::with_approval].
+/// - `Checkpoint(Box<Checkpoint>)` — the run reached a safe point; only with
+///   [`with_checkpoints`][DeepseekAgent::with_checkpoints].
+///
+/// The enum is `#[non_exhaustive]`: new events may be added in minor
+/// releases, so matches need a wildcard arm.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum AgentEvent {
     Token(String),
     /// Emitted when the model produces reasoning/thinking content (e.g. deepseek-reasoner).
@@ -56,6 +84,40 @@ pub enum AgentEvent {
     ReasoningToken(String),
     ToolCall(ToolCallChunk),
     ToolResult(ToolCallResult),
+    /// Emitted before the client retries a failed API request (including the
+    /// summarizer's calls).  Only produced when the client has a retry policy.
+    Retry(RetryEvent),
+    /// Token usage of the turn that just finished, including
+    /// `prompt_cache_hit_tokens` and, for reasoning models,
+    /// [`reasoning_tokens`][Usage::reasoning_tokens].  Skipped when the
+    /// backend did not report usage.
+    Usage(Usage),
+    /// The model finished a turn.  `finish_reason` is
+    /// [`FinishReason::Length`] when the reply was cut off by `max_tokens`, and
+    /// `None` only when a stream ended without reporting a reason.
+    TurnFinished {
+        finish_reason: Option<FinishReason>,
+    },
+    /// The run was stopped by the agent's [`Budget`].  Tool calls of the last
+    /// turn that were not executed have already been reported as
+    /// `ToolResult`s carrying an error.
+    BudgetExceeded(BudgetLimit),
+    /// The model was still calling tools after `max_steps` turns, so the run
+    /// ended with one more turn sent with `tool_choice: none`.  Emitted after
+    /// that turn's events.
+    MaxStepsReached {
+        max_steps: u32,
+    },
+    /// A tool call selected by the agent's [`ApprovalPolicy`] is waiting for
+    /// a decision.  The run stays paused until it gets one through the
+    /// agent's [`ApprovalHandle`].
+    ApprovalRequired {
+        call: PendingToolCall,
+    },
+    /// The run's state after a turn's reply reached history or after a tool
+    /// batch, for [`resume`][DeepseekAgent::resume].  Emitted after the
+    /// turn's `TurnFinished`, or after the batch's last `ToolResult`.
+    Checkpoint(Box<Checkpoint>),
 }
 
 /// An agent that combines a [`Conversation`] with a set of callable tools.
@@ -99,10 +161,52 @@ pub struct DeepseekAgent {
     pub(crate) tool_inject_rx: mpsc::UnboundedReceiver<ToolInjection>,
     /// Optional map of extra top-level JSON fields to merge into the API request body.
     pub(crate) extra_body: Option<serde_json::Map<String, serde_json::Value>>,
+    /// Sampling and tool-choice settings applied to every API turn.
+    pub(crate) options: RequestOptions,
+    /// Optional per-turn override of `options`.
+    pub(crate) turn_hook: Option<Arc<TurnHook>>,
+    /// 1-based index of the current API turn within the running `chat()`;
+    /// `0` before the first turn.
+    pub(crate) turn: u32,
+    /// Limits for each `chat()` run.
+    pub(crate) budget: Option<Budget>,
+    /// What the running `chat()` has spent; reset with `turn`.
+    pub(crate) spend: RunSpend,
+    /// Tool-loop turns allowed per `chat()` before the final tool-free turn.
+    pub(crate) max_steps: Option<u32>,
+    /// Tool calls of one turn that may run at the same time; `1` runs them
+    /// one after another.
+    pub(crate) tool_concurrency: usize,
+    /// Tool calls that wait for a human decision before they run.
+    pub(crate) approval: Option<ApprovalPolicy>,
+    /// Always-on channel for approval decisions, see [`ApprovalHandle`].
+    pub(crate) approval_tx: mpsc::UnboundedSender<(String, ApprovalDecision)>,
+    pub(crate) approval_rx: mpsc::UnboundedReceiver<(String, ApprovalDecision)>,
+    /// How long a tool call may run before it is abandoned.
+    pub(crate) tool_timeouts: ToolTimeouts,
+    /// Emit [`AgentEvent::Checkpoint`] at the run's safe points.
+    pub(crate) checkpoints: bool,
+    /// A system message sent with every request but kept out of history:
+    /// the schema of a running `chat_typed`.
+    pub(crate) instruction: Option<String>,
+}
+
+/// Default and per-tool deadlines for tool calls.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ToolTimeouts {
+    pub(crate) default: Option<Duration>,
+    pub(crate) per_tool: HashMap<String, Duration>,
+}
+
+impl ToolTimeouts {
+    /// The deadline for calls of the tool named `name`, if any.
+    pub(crate) fn for_tool(&self, name: &str) -> Option<Duration> {
+        self.per_tool.get(name).copied().or(self.default)
+    }
 }
 
 /// A runtime tool-injection command sent through the channel created by
-/// [`DeepseekAgent::with_tool_inject_channel`].
+/// [`DeepseekAgent::tool_inject_sender`].
 pub enum ToolInjection {
     /// Add a new tool to the running agent.
     Add(Box<dyn Tool>),
@@ -111,13 +215,14 @@ pub enum ToolInjection {
 }
 
 impl DeepseekAgent {
-    fn from_parts(client: ApiClient, model: impl Into<String>) -> Self {
+    fn from_parts(backend: Arc<dyn ChatBackend>, model: impl Into<String>) -> Self {
         let model = model.into();
-        let summarizer = LlmSummarizer::new(client.clone()).with_model(model.clone());
+        let summarizer = LlmSummarizer::from_shared(backend.clone()).with_model(model.clone());
         let (interrupt_tx, interrupt_rx) = mpsc::unbounded_channel();
         let (tool_inject_tx, tool_inject_rx) = mpsc::unbounded_channel();
+        let (approval_tx, approval_rx) = mpsc::unbounded_channel();
         Self {
-            conversation: Conversation::new(client).with_summarizer(summarizer),
+            conversation: Conversation::from_shared(backend).with_summarizer(summarizer),
             tools: vec![],
             tool_index: HashMap::new(),
             streaming: false,
@@ -127,12 +232,44 @@ impl DeepseekAgent {
             tool_inject_tx,
             tool_inject_rx,
             extra_body: None,
+            options: RequestOptions::default(),
+            turn_hook: None,
+            turn: 0,
+            budget: None,
+            spend: RunSpend::new(),
+            max_steps: None,
+            tool_concurrency: 1,
+            approval: None,
+            approval_tx,
+            approval_rx,
+            tool_timeouts: ToolTimeouts::default(),
+            checkpoints: false,
+            instruction: None,
         }
     }
 
     /// Create a new agent targeting the DeepSeek API with `deepseek-chat`.
     pub fn new(token: impl Into<String>) -> Self {
-        Self::from_parts(ApiClient::new(token), "deepseek-chat")
+        Self::from_parts(Arc::new(ApiClient::new(token)), "deepseek-chat")
+    }
+
+    /// Create an agent from a pre-configured [`ApiClient`] or any other
+    /// [`ChatBackend`].
+    ///
+    /// Use this when the client needs settings beyond a token and base URL,
+    /// such as a [`RetryPolicy`][crate::api::RetryPolicy], or to run the agent
+    /// against a [`MockBackend`][crate::api::MockBackend] in tests.  The
+    /// backend is shared with the default [`LlmSummarizer`], so its settings
+    /// apply to summarization calls as well.
+    ///
+    /// ```no_run
+    /// use ds_api::{ApiClient, DeepseekAgent, RetryPolicy};
+    ///
+    /// let client = ApiClient::new("sk-...").with_retry(RetryPolicy::default());
+    /// let agent = DeepseekAgent::from_client(client, "deepseek-chat");
+    /// ```
+    pub fn from_client(backend: impl ChatBackend + 'static, model: impl Into<String>) -> Self {
+        Self::from_parts(Arc::new(backend), model)
     }
 
     /// Create an agent targeting an OpenAI-compatible provider.
@@ -154,7 +291,7 @@ impl DeepseekAgent {
         model: impl Into<String>,
     ) -> Self {
         let client = ApiClient::new(token).with_base_url(base_url);
-        Self::from_parts(client, model)
+        Self::from_parts(Arc::new(client), model)
     }
 
     /// Register a tool (builder-style, supports chaining).
@@ -177,6 +314,52 @@ impl DeepseekAgent {
         crate::agent::stream::AgentStream::new(self)
     }
 
+    /// Push a multimodal user message (text and images) and return an
+    /// [`AgentStream`][crate::agent::AgentStream] that drives the agent loop.
+    ///
+    /// Images need a vision model on an OpenAI-compatible provider, e.g. via
+    /// [`custom`][Self::custom]; the DeepSeek API accepts text only.
+    ///
+    /// ```no_run
+    /// use ds_api::DeepseekAgent;
+    /// use ds_api::raw::ContentPart;
+    ///
+    /// let agent = DeepseekAgent::custom("sk-or-...", "https://openrouter.ai/api/v1", "openai/gpt-4o");
+    /// let stream = agent.chat_parts(vec![
+    ///     ContentPart::text("What does this chart show?"),
+    ///     ContentPart::image_url("https://example.com/chart.png"),
+    /// ]);
+    /// ```
+    pub fn chat_parts(mut self, parts: Vec<ContentPart>) -> crate::agent::stream::AgentStream {
+        self.conversation.push_user_parts(parts);
+        crate::agent::stream::AgentStream::new(self)
+    }
+
+    /// Push a user message and an assistant prefix, then run the agent loop;
+    /// the model's reply continues from `prefix` (beta chat prefix
+    /// completion, routed to the beta endpoint by [`ApiClient`]).
+    ///
+    /// `Token` events carry only the continuation.  In history, the prefix
+    /// and the continuation are stitched into a single assistant message.
+    ///
+    /// ```no_run
+    /// use ds_api::{DeepseekAgent, RequestOptions};
+    ///
+    /// // Force a bare code block: open it in the prefix, stop at the fence.
+    /// let agent = DeepseekAgent::new("sk-...")
+    ///     .with_options(RequestOptions::new().stop(["```"]));
+    /// let stream = agent.chat_with_prefix("Write quicksort in Rust.", "```rust\n");
+    /// ```
+    pub fn chat_with_prefix(
+        mut self,
+        user_message: &str,
+        prefix: &str,
+    ) -> crate::agent::stream::AgentStream {
+        self.conversation.push_user_input(user_message);
+        self.conversation.push_assistant_prefix(prefix);
+        crate::agent::stream::AgentStream::new(self)
+    }
+
     /// Start an agent turn from the current history **without** pushing a new
     /// user message first.
     ///
@@ -187,6 +370,60 @@ impl DeepseekAgent {
         crate::agent::stream::AgentStream::new(self)
     }
 
+    /// Continue the run saved in `checkpoint` on `backend`, with `tools` (a
+    /// [`ToolBundle`][crate::ToolBundle] for several).
+    ///
+    /// Shorthand for building an agent with [`from_client`][Self::from_client],
+    /// [`add_tool`][Self::add_tool] and
+    /// [`with_checkpoints`][Self::with_checkpoints] and calling
+    /// [`resume_from`][Self::resume_from] on it; the run keeps emitting
+    /// checkpoints.  The checkpoint does not record the endpoint, so pass a
+    /// backend for the provider the run was started on.  Use `resume_from`
+    /// to bring a summarizer, hooks or an approval policy.
+    ///
+    /// ```no_run
+    /// use ds_api::{ApiClient, Checkpoint, DeepseekAgent, ToolBundle};
+    ///
+    /// # fn load() -> String { String::new() }
+    /// let checkpoint: Checkpoint = serde_json::from_str(&load()).unwrap();
+    /// let stream = DeepseekAgent::resume(ApiClient::new("sk-..."), checkpoint, ToolBundle::new());
+    /// ```
+    pub fn resume(
+        backend: impl ChatBackend + 'static,
+        checkpoint: Checkpoint,
+        tools: impl Tool + 'static,
+    ) -> crate::agent::stream::AgentStream {
+        Self::from_client(backend, checkpoint.model())
+            .add_tool(tools)
+            .with_checkpoints()
+            .resume_from(checkpoint)
+    }
+
+    /// Continue the run saved in `checkpoint` on this agent and return its
+    /// [`AgentStream`][crate::agent::AgentStream].
+    ///
+    /// Call it on an agent built the way the checkpointed one was: the
+    /// checkpoint restores the history, the run's turn count and spend, and
+    /// the plain-data settings (model, streaming, extra body, request
+    /// options, budget, max steps, tool concurrency and timeouts), while
+    /// tools, the backend and its endpoint and key, the summarizer, the turn
+    /// hook, the approval policy and the cost tracker are this agent's.
+    /// Tool calls the model made before the checkpoint run first; a finished
+    /// run yields no events.  The resumed run emits checkpoints if this agent
+    /// has [`with_checkpoints`][Self::with_checkpoints] set.
+    ///
+    /// ```no_run
+    /// use ds_api::{Checkpoint, DeepseekAgent};
+    ///
+    /// # fn load() -> String { String::new() }
+    /// let checkpoint: Checkpoint = serde_json::from_str(&load()).unwrap();
+    /// let stream = DeepseekAgent::new("sk-...").resume_from(checkpoint);
+    /// ```
+    pub fn resume_from(mut self, checkpoint: Checkpoint) -> crate::agent::stream::AgentStream {
+        let (pending, finished) = checkpoint.restore(&mut self);
+        crate::agent::stream::AgentStream::resume(self, pending, finished)
+    }
+
     /// Enable SSE streaming for each API turn (builder-style).
     pub fn with_streaming(mut self) -> Self {
         self.streaming = true;
@@ -223,6 +460,181 @@ impl DeepseekAgent {
         self
     }
 
+    /// Set the [`RequestOptions`] applied to every API turn (builder-style).
+    ///
+    /// Replaces any options set earlier.  The summarizer's calls are not
+    /// affected.
+    ///
+    /// ```no_run
+    /// use ds_api::{DeepseekAgent, RequestOptions};
+    ///
+    /// let agent = DeepseekAgent::new("sk-...")
+    ///     .with_options(RequestOptions::new().temperature(0.0).max_tokens(2048));
+    /// ```
+    pub fn with_options(mut self, options: RequestOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Register a hook that adjusts the [`RequestOptions`] for each API turn
+    /// (builder-style).
+    ///
+    /// The hook receives a [`TurnContext`] and a copy of the agent-wide
+    /// options, and returns the options to use for that turn only.  It runs
+    /// after summarization, right before the request is sent.
+    ///
+    /// ```no_run
+    /// use ds_api::DeepseekAgent;
+    ///
+    /// // Force a search on the first turn, then let the model decide.
+    /// let agent = DeepseekAgent::new("sk-...").with_turn_hook(|ctx, opts| {
+    ///     if ctx.turn == 1 {
+    ///         opts.tool_choice_function("search")
+    ///     } else {
+    ///         opts
+    ///     }
+    /// });
+    /// ```
+    pub fn with_turn_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&TurnContext<'_>, RequestOptions) -> RequestOptions + Send + Sync + 'static,
+    {
+        self.turn_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// The agent-wide [`RequestOptions`].
+    pub fn options(&self) -> &RequestOptions {
+        &self.options
+    }
+
+    /// Price every API turn of this agent, and its summarizer's calls, with
+    /// `tracker` (builder-style).
+    ///
+    /// The tracker is attached to the agent's [`Conversation`]; streamed
+    /// turns report usage too.  See [`CostTracker`] for aggregating per
+    /// client instead.
+    pub fn with_cost_tracker(mut self, tracker: CostTracker) -> Self {
+        self.conversation = self.conversation.with_cost_tracker(tracker);
+        self
+    }
+
+    /// The tracker attached with [`with_cost_tracker`][Self::with_cost_tracker], if any.
+    pub fn cost_tracker(&self) -> Option<&CostTracker> {
+        self.conversation.cost_tracker()
+    }
+
+    /// Cap the tokens, cost, time or turns of each [`chat`][Self::chat] run
+    /// (builder-style).
+    ///
+    /// A run that reaches a limit ends with [`AgentEvent::BudgetExceeded`].
+    /// See [`Budget`] for when the limits are checked.
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Limit the tool loop of each [`chat`][Self::chat] run to `n` turns
+    /// (builder-style).
+    ///
+    /// If the model still calls tools on turn `n`, those tools run and one
+    /// more turn is sent with `tool_choice: none`, so the model has to give a
+    /// final answer.  The run then ends with
+    /// [`AgentEvent::MaxStepsReached`].
+    pub fn with_max_steps(mut self, n: u32) -> Self {
+        self.max_steps = Some(n);
+        self
+    }
+
+    /// Run up to `limit` of a turn's tool calls at the same time
+    /// (builder-style).
+    ///
+    /// Tools run one after another by default.  With a limit above 1, each
+    /// [`AgentEvent::ToolResult`] is emitted as soon as its tool finishes, so
+    /// results can arrive out of call order; the tool messages are still
+    /// written to history in call order once the whole batch is done.  An
+    /// interrupt aborts every tool of the batch that has not finished.
+    /// A `limit` of 0 is treated as 1.
+    pub fn with_parallel_tools(mut self, limit: usize) -> Self {
+        self.tool_concurrency = limit.max(1);
+        self
+    }
+
+    /// Abandon any tool call that runs longer than `timeout` (builder-style).
+    ///
+    /// A call that times out is answered with
+    /// `{"error": "timed out", "timeout_ms": ...}` and its cancellation token
+    /// is cancelled (see [`Tool::call_with_cancel`]).  Overridden per tool by
+    /// [`with_tool_timeout_for`][Self::with_tool_timeout_for].
+    pub fn with_tool_timeout(mut self, timeout: Duration) -> Self {
+        self.tool_timeouts.default = Some(timeout);
+        self
+    }
+
+    /// Set the timeout for calls of the tool named `name` (builder-style).
+    pub fn with_tool_timeout_for(mut self, name: impl Into<String>, timeout: Duration) -> Self {
+        self.tool_timeouts.per_tool.insert(name.into(), timeout);
+        self
+    }
+
+    /// Emit [`AgentEvent::Checkpoint`] at each safe point of a run
+    /// (builder-style): once a turn's reply is in history, and after each
+    /// tool batch.  Store the checkpoints to [`resume`][Self::resume] a run
+    /// that was stopped, e.g. in another process.
+    pub fn with_checkpoints(mut self) -> Self {
+        self.checkpoints = true;
+        self
+    }
+
+    /// Pause before running the tool calls that `policy` selects until a
+    /// human decides on them (builder-style).
+    ///
+    /// Each such call is announced with [`AgentEvent::ApprovalRequired`] once
+    /// the turn's tool calls are known.  No tool of the turn runs until every
+    /// announced call has been approved, rejected or edited through
+    /// [`approval_handle`][Self::approval_handle].  Rejected calls are
+    /// answered with an error tool result carrying the reason, so the model
+    /// can adapt.
+    pub fn with_approval(mut self, policy: ApprovalPolicy) -> Self {
+        self.approval = Some(policy);
+        self
+    }
+
+    /// The calls among `calls` that need approval under the agent's policy.
+    pub(crate) fn calls_needing_approval(
+        &self,
+        calls: &[crate::raw::request::message::ToolCall],
+    ) -> Vec<PendingToolCall> {
+        let Some(policy) = &self.approval else {
+            return Vec::new();
+        };
+        calls
+            .iter()
+            .map(|tc| PendingToolCall {
+                id: tc.id.clone(),
+                name: tc.function.name.clone(),
+                args: tc.function.arguments.clone(),
+            })
+            .filter(|call| policy.requires(call))
+            .collect()
+    }
+
+    /// `Some(max_steps)` if the current turn is the final, tool-free one.
+    pub(crate) fn steps_exhausted(&self) -> Option<u32> {
+        self.max_steps.filter(|&n| self.turn > n)
+    }
+
+    /// Record one turn's usage in the cost tracker and the run's spend.
+    pub(crate) fn record_usage(&mut self, model: &str, usage: &Usage) {
+        self.conversation.record_usage(model, usage);
+        self.spend.record(self.budget.as_ref(), model, usage);
+    }
+
+    /// The budget limit the running `chat()` has reached, if any.
+    pub(crate) fn budget_exceeded(&self) -> Option<BudgetLimit> {
+        self.budget.as_ref()?.check(&self.spend, self.turn)
+    }
+
     /// Prepend a permanent system prompt to the conversation history (builder-style).
     ///
     /// System messages added this way are never removed by the built-in summarizers.
@@ -241,8 +653,9 @@ impl DeepseekAgent {
 
     /// Seed the agent with an existing message history (builder-style).
     ///
-    /// Used to restore a conversation from persistent storage (e.g. SQLite)
-    /// after a process restart.  The messages are set directly on the
+    /// Used to restore a conversation saved elsewhere after a process
+    /// restart; with a [store][Self::with_store], use
+    /// [`load_history`][Self::load_history] instead.  The messages are set directly on the
     /// underlying `Conversation` and will be included in the next API call.
     ///
     /// # Example
@@ -264,6 +677,42 @@ impl DeepseekAgent {
         self
     }
 
+    /// Persist the conversation to `store` under `session` (builder-style).
+    ///
+    /// History is written before every API turn and once more when a run
+    /// ends; summarization and other rewrites replace the stored session
+    /// atomically.  A failed write is logged and retried on the next one.
+    /// The first write replaces the session unless
+    /// [`load_history`][Self::load_history] runs first.  See
+    /// [`Conversation::with_store`].
+    ///
+    /// ```no_run
+    /// use ds_api::DeepseekAgent;
+    /// use ds_api::conversation::JsonlStore;
+    ///
+    /// # #[tokio::main] async fn main() -> ds_api::error::Result<()> {
+    /// let mut agent = DeepseekAgent::new("sk-...")
+    ///     .with_store(JsonlStore::new("./sessions"), "user-42");
+    /// // Continue where the last process left off.
+    /// agent.load_history().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_store(
+        mut self,
+        store: impl ConversationStore + 'static,
+        session: impl Into<String>,
+    ) -> Self {
+        self.conversation = self.conversation.with_store(store, session);
+        self
+    }
+
+    /// Replace the history with the messages stored for the agent's session.
+    /// Does nothing without a [store][Self::with_store].
+    pub async fn load_history(&mut self) -> crate::error::Result<()> {
+        self.conversation.load_history().await
+    }
+
     /// Append a user message with an optional display name to the conversation
     /// history.
     ///
@@ -284,7 +733,7 @@ impl DeepseekAgent {
         use crate::raw::request::message::{Message, Role};
         let mut msg = Message::new(Role::User, text);
         msg.name = name.map(|n| n.to_string());
-        self.conversation.history_mut().push(msg);
+        self.conversation.push(msg);
     }
 
     /// Read-only view of the current conversation history.
@@ -317,6 +766,17 @@ impl DeepseekAgent {
         self.interrupt_tx.clone()
     }
 
+    /// A handle for deciding on calls announced by
+    /// [`AgentEvent::ApprovalRequired`].
+    ///
+    /// Take it before calling [`chat`][Self::chat]; it stays valid for every
+    /// run of this agent.
+    pub fn approval_handle(&self) -> ApprovalHandle {
+        ApprovalHandle {
+            tx: self.approval_tx.clone(),
+        }
+    }
+
     /// Clone the sender half of the tool-injection channel.
     ///
     /// Send [`ToolInjection::Add`] or [`ToolInjection::Remove`] at any time;
@@ -331,9 +791,7 @@ impl DeepseekAgent {
     /// Called by the state machine at every `Idle` transition.
     pub(crate) fn drain_interrupts(&mut self) {
         while let Ok(msg) = self.interrupt_rx.try_recv() {
-            self.conversation
-                .history_mut()
-                .push(Message::new(Role::User, &msg));
+            self.conversation.push(Message::new(Role::User, &msg));
         }
     }
 
@@ -0,0 +1,154 @@
+//! Human-in-the-loop approval of tool calls.
+//!
+//! An [`ApprovalPolicy`] set with
+//! [`with_approval`][crate::agent::DeepseekAgent::with_approval] names the
+//! tool calls that need a human decision before they run.  When a turn asks
+//! for one, the stream emits
+//! [`AgentEvent::ApprovalRequired`][crate::agent::AgentEvent::ApprovalRequired]
+//! for it and pauses before executing the turn's tools.  It resumes once every
+//! such call has been approved, rejected or edited through the agent's
+//! [`ApprovalHandle`].
+
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+/// A complete tool call waiting for a decision, from
+/// [`AgentEvent::ApprovalRequired`][crate::agent::AgentEvent::ApprovalRequired].
+#[derive(Debug, Clone)]
+pub struct PendingToolCall {
+    pub id: String,
+    pub name: String,
+    /// The arguments as the model sent them, as a JSON string.
+    pub args: String,
+}
+
+type ApprovalPredicate = dyn Fn(&PendingToolCall) -> bool + Send + Sync;
+
+/// Which tool calls need approval before they run.
+///
+/// A call needs approval if its tool is named with [`require`][Self::require]
+/// or if any [`require_if`][Self::require_if] predicate returns `true` for it.
+///
+/// # Example
+///
+/// ```no_run
+/// use ds_api::{ApprovalPolicy, DeepseekAgent};
+///
+/// let agent = DeepseekAgent::new("sk-...").with_approval(
+///     ApprovalPolicy::new()
+///         .require("deploy")
+///         .require_if(|call| call.name == "sql" && !call.args.contains("SELECT")),
+/// );
+/// ```
+#[derive(Clone, Default)]
+pub struct ApprovalPolicy {
+    tools: HashSet<String>,
+    predicates: Vec<Arc<ApprovalPredicate>>,
+}
+
+impl fmt::Debug for ApprovalPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApprovalPolicy")
+            .field("tools", &self.tools)
+            .field("predicates", &self.predicates.len())
+            .finish()
+    }
+}
+
+impl ApprovalPolicy {
+    /// A policy that lets every call run.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: every call of the tool named `name` needs approval.
+    pub fn require(mut self, name: impl Into<String>) -> Self {
+        self.tools.insert(name.into());
+        self
+    }
+
+    /// Builder: calls for which `predicate` returns `true` need approval.
+    pub fn require_if<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&PendingToolCall) -> bool + Send + Sync + 'static,
+    {
+        self.predicates.push(Arc::new(predicate));
+        self
+    }
+
+    /// Whether `call` needs approval.
+    pub(crate) fn requires(&self, call: &PendingToolCall) -> bool {
+        self.tools.contains(&call.name) || self.predicates.iter().any(|p| p(call))
+    }
+}
+
+/// A decision on one pending call.
+#[derive(Debug, Clone)]
+pub(crate) enum ApprovalDecision {
+    Approve,
+    Reject(String),
+    Edit(Value),
+}
+
+/// Sends decisions on pending tool calls to a running agent.
+///
+/// Obtain one with
+/// [`DeepseekAgent::approval_handle`][crate::agent::DeepseekAgent::approval_handle]
+/// before calling `chat`; clones send to the same agent.  Decisions name the
+/// call by [`PendingToolCall::id`] and may be sent in any order.  Decisions
+/// for calls that are not waiting are ignored.
+#[derive(Debug, Clone)]
+pub struct ApprovalHandle {
+    pub(crate) tx: mpsc::UnboundedSender<(String, ApprovalDecision)>,
+}
+
+impl ApprovalHandle {
+    /// Run the call as the model sent it.
+    pub fn approve(&self, id: impl Into<String>) {
+        self.send(id.into(), ApprovalDecision::Approve);
+    }
+
+    /// Do not run the call.  The model sees a tool result carrying `reason`.
+    pub fn reject(&self, id: impl Into<String>, reason: impl Into<String>) {
+        self.send(id.into(), ApprovalDecision::Reject(reason.into()));
+    }
+
+    /// Run the call with `args` instead of the model's arguments.  The call
+    /// is rewritten in history too, so the model sees what actually ran.
+    pub fn edit(&self, id: impl Into<String>, args: Value) {
+        self.send(id.into(), ApprovalDecision::Edit(args));
+    }
+
+    fn send(&self, id: String, decision: ApprovalDecision) {
+        // The agent owns the receiver; once it is dropped nothing is waiting.
+        let _ = self.tx.send((id, decision));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, args: &str) -> PendingToolCall {
+        PendingToolCall {
+            id: "call_1".into(),
+            name: name.into(),
+            args: args.into(),
+        }
+    }
+
+    #[test]
+    fn policy_matches_by_name_or_predicate() {
+        let policy = ApprovalPolicy::new()
+            .require("deploy")
+            .require_if(|c| c.args.contains("DROP"));
+        assert!(policy.requires(&call("deploy", "{}")));
+        assert!(policy.requires(&call("sql", r#"{"q":"DROP TABLE t"}"#)));
+        assert!(!policy.requires(&call("sql", r#"{"q":"SELECT 1"}"#)));
+        assert!(!ApprovalPolicy::new().requires(&call("deploy", "{}")));
+    }
+}
@@ -0,0 +1,199 @@
+//! Per-run budgets for [`DeepseekAgent`][crate::agent::DeepseekAgent].
+//!
+//! A [`Budget`] set with
+//! [`with_budget`][crate::agent::DeepseekAgent::with_budget] caps what a
+//! single [`chat`][crate::agent::DeepseekAgent::chat] run may spend.  The
+//! limits are checked at the run's safe points: before each API turn, and
+//! after a turn's tool calls are known but before they execute.  A run that
+//! is over budget ends with
+//! [`AgentEvent::BudgetExceeded`][crate::agent::AgentEvent::BudgetExceeded];
+//! tool calls it did not execute get an error result, so the history stays
+//! valid for the next request.
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::api::PriceTable;
+use crate::raw::Usage;
+
+/// Limits for one agent run.  Unset limits are not enforced.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use ds_api::{Budget, DeepseekAgent};
+///
+/// let agent = DeepseekAgent::new("sk-...").with_budget(
+///     Budget::new()
+///         .max_turns(8)
+///         .max_tokens(200_000)
+///         .max_cost(0.05)
+///         .max_duration(Duration::from_secs(120)),
+/// );
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Budget {
+    pub(crate) max_tokens: Option<u64>,
+    pub(crate) max_cost: Option<f64>,
+    pub(crate) max_duration: Option<Duration>,
+    pub(crate) max_turns: Option<u32>,
+    pub(crate) prices: PriceTable,
+}
+
+impl Budget {
+    /// No limits, default DeepSeek prices.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: cap the total tokens (prompt and completion) of all turns.
+    pub fn max_tokens(mut self, n: u64) -> Self {
+        self.max_tokens = Some(n);
+        self
+    }
+
+    /// Builder: cap the total cost of all turns, priced with
+    /// [`prices`][Self::prices].  Turns on models missing from the table
+    /// cost nothing.
+    pub fn max_cost(mut self, amount: f64) -> Self {
+        self.max_cost = Some(amount);
+        self
+    }
+
+    /// Builder: cap the wall-clock time of the run.
+    ///
+    /// The clock is checked at safe points only, so a long turn or tool call
+    /// can overrun it.
+    pub fn max_duration(mut self, d: Duration) -> Self {
+        self.max_duration = Some(d);
+        self
+    }
+
+    /// Builder: cap the number of API turns.
+    pub fn max_turns(mut self, n: u32) -> Self {
+        self.max_turns = Some(n);
+        self
+    }
+
+    /// Builder: the prices used for [`max_cost`][Self::max_cost].
+    pub fn prices(mut self, prices: PriceTable) -> Self {
+        self.prices = prices;
+        self
+    }
+
+    /// The first limit the run has reached, if any, given that `turns` API
+    /// turns have been made.
+    pub(crate) fn check(&self, spend: &RunSpend, turns: u32) -> Option<BudgetLimit> {
+        if let Some(limit) = self.max_turns
+            && turns >= limit
+        {
+            return Some(BudgetLimit::Turns { limit });
+        }
+        if let Some(limit) = self.max_tokens
+            && spend.tokens >= limit
+        {
+            return Some(BudgetLimit::Tokens {
+                limit,
+                used: spend.tokens,
+            });
+        }
+        if let Some(limit) = self.max_cost
+            && spend.cost >= limit
+        {
+            return Some(BudgetLimit::Cost {
+                limit,
+                spent: spend.cost,
+            });
+        }
+        if let Some(limit) = self.max_duration {
+            let elapsed = spend.started.elapsed();
+            if elapsed >= limit {
+                return Some(BudgetLimit::Duration { limit, elapsed });
+            }
+        }
+        None
+    }
+}
+
+/// The limit that ended a run, from
+/// [`AgentEvent::BudgetExceeded`][crate::agent::AgentEvent::BudgetExceeded].
+#[derive(Debug, Clone, PartialEq)]
+pub enum BudgetLimit {
+    Turns { limit: u32 },
+    Tokens { limit: u64, used: u64 },
+    Cost { limit: f64, spent: f64 },
+    Duration { limit: Duration, elapsed: Duration },
+}
+
+impl fmt::Display for BudgetLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetLimit::Turns { limit } => write!(f, "turn budget of {limit} reached"),
+            BudgetLimit::Tokens { limit, used } => {
+                write!(f, "token budget exceeded ({used} of {limit})")
+            }
+            BudgetLimit::Cost { limit, spent } => {
+                write!(f, "cost budget exceeded ({spent:.4} of {limit:.4})")
+            }
+            BudgetLimit::Duration { limit, elapsed } => {
+                write!(f, "time budget exceeded ({elapsed:?} of {limit:?})")
+            }
+        }
+    }
+}
+
+/// What the current run has spent so far.
+#[derive(Debug)]
+pub(crate) struct RunSpend {
+    pub(crate) tokens: u64,
+    pub(crate) cost: f64,
+    pub(crate) started: Instant,
+}
+
+impl RunSpend {
+    pub(crate) fn new() -> Self {
+        Self {
+            tokens: 0,
+            cost: 0.0,
+            started: Instant::now(),
+        }
+    }
+
+    /// Add one turn's usage, priced with the budget's table.
+    pub(crate) fn record(&mut self, budget: Option<&Budget>, model: &str, usage: &Usage) {
+        self.tokens += u64::from(usage.total_tokens);
+        if let Some(price) = budget.and_then(|b| b.prices.get(model)) {
+            self.cost += price.cost(usage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_reports_the_first_limit_reached() {
+        let mut spend = RunSpend::new();
+        let budget = Budget::new().max_turns(3).max_tokens(100);
+        assert_eq!(budget.check(&spend, 2), None);
+
+        spend.tokens = 120;
+        assert_eq!(
+            budget.check(&spend, 2),
+            Some(BudgetLimit::Tokens {
+                limit: 100,
+                used: 120
+            })
+        );
+        assert_eq!(
+            budget.check(&spend, 3),
+            Some(BudgetLimit::Turns { limit: 3 })
+        );
+        assert_eq!(Budget::new().check(&spend, 1000), None);
+    }
+}
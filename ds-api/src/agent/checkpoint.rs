@@ -0,0 +1,202 @@
+//! Checkpoints of an in-progress agent run.
+//!
+//! With [`with_checkpoints`][crate::agent::DeepseekAgent::with_checkpoints]
+//! set, an [`AgentStream`][crate::agent::AgentStream] emits
+//! [`AgentEvent::Checkpoint`][crate::agent::AgentEvent::Checkpoint] at the
+//! run's safe points: once a turn's reply is in history, and after each tool
+//! batch.  A run stopped by its budget or step limit ends with a finished
+//! checkpoint, so resuming it runs none of the calls it skipped.  A
+//! [`Checkpoint`] is plain data that serializes with serde, so it
+//! can be stored and the run picked up again in another process with
+//! [`DeepseekAgent::resume`][crate::agent::DeepseekAgent::resume] or
+//! [`resume_from`][crate::agent::DeepseekAgent::resume_from].  Neither reads
+//! the endpoint or key from the checkpoint: the caller supplies the backend.
+//!
+//! A checkpoint holds the history, the tool calls still to run, the run's
+//! step counters and spend, and the agent settings that are plain data.
+//! Tools, the backend, the summarizer, hooks, the approval policy and cost
+//! trackers cannot be serialized; they come from the agent `resume_from` is
+//! called on.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::agent::agent_core::{DeepseekAgent, ToolTimeouts};
+use crate::agent::budget::{Budget, RunSpend};
+use crate::agent::options::RequestOptions;
+use crate::raw::request::message::{Message, ToolCall};
+
+/// The state of an agent run at a safe point, from
+/// [`AgentEvent::Checkpoint`][crate::agent::AgentEvent::Checkpoint].
+///
+/// # Example
+///
+/// ```no_run
+/// use ds_api::{AgentEvent, Checkpoint, DeepseekAgent};
+/// use futures::StreamExt;
+///
+/// # #[tokio::main] async fn main() {
+/// # let saved: Option<String> = None;
+/// let agent = DeepseekAgent::new("sk-...").with_checkpoints();
+/// let mut stream = match saved {
+///     // Pick up where the last process stopped.
+///     Some(json) => {
+///         let checkpoint: Checkpoint = serde_json::from_str(&json).unwrap();
+///         agent.resume_from(checkpoint)
+///     }
+///     None => agent.chat("Research the topic and write a report"),
+/// };
+/// while let Some(event) = stream.next().await {
+///     if let AgentEvent::Checkpoint(checkpoint) = event.unwrap() {
+///         let json = serde_json::to_string(&checkpoint).unwrap();
+///         // Store `json` somewhere durable.
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    history: Vec<Message>,
+    /// Tool calls of the last assistant message that have no result yet.
+    pending_tool_calls: Vec<ToolCall>,
+    /// The model gave its final answer; nothing is left to do.
+    finished: bool,
+    turn: u32,
+    tokens: u64,
+    cost: f64,
+    elapsed: Duration,
+    config: AgentConfig,
+}
+
+/// The agent settings a checkpoint carries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AgentConfig {
+    model: String,
+    streaming: bool,
+    extra_body: Option<serde_json::Map<String, serde_json::Value>>,
+    options: RequestOptions,
+    budget: Option<Budget>,
+    max_steps: Option<u32>,
+    tool_concurrency: usize,
+    tool_timeout: Option<Duration>,
+    tool_timeouts: HashMap<String, Duration>,
+}
+
+impl Checkpoint {
+    /// Capture `agent` with `pending` tool calls still to run.
+    pub(crate) fn capture(agent: &DeepseekAgent, pending: &[ToolCall], finished: bool) -> Self {
+        Self {
+            history: agent.history().to_vec(),
+            pending_tool_calls: pending.to_vec(),
+            finished,
+            turn: agent.turn,
+            tokens: agent.spend.tokens,
+            cost: agent.spend.cost,
+            elapsed: agent.spend.started.elapsed(),
+            config: AgentConfig {
+                model: agent.model.clone(),
+                streaming: agent.streaming,
+                extra_body: agent.extra_body.clone(),
+                options: agent.options.clone(),
+                budget: agent.budget.clone(),
+                max_steps: agent.max_steps,
+                tool_concurrency: agent.tool_concurrency,
+                tool_timeout: agent.tool_timeouts.default,
+                tool_timeouts: agent.tool_timeouts.per_tool.clone(),
+            },
+        }
+    }
+
+    /// Move the checkpoint's state into `agent`, returning the tool calls
+    /// still to run and whether the run had finished.
+    pub(crate) fn restore(self, agent: &mut DeepseekAgent) -> (Vec<ToolCall>, bool) {
+        let config = self.config;
+        agent.model = config.model;
+        agent.streaming = config.streaming;
+        agent.extra_body = config.extra_body;
+        agent.options = config.options;
+        agent.budget = config.budget;
+        agent.max_steps = config.max_steps;
+        agent.tool_concurrency = config.tool_concurrency;
+        agent.tool_timeouts = ToolTimeouts {
+            default: config.tool_timeout,
+            per_tool: config.tool_timeouts,
+        };
+        agent.conversation.replace_history(self.history);
+        agent.turn = self.turn;
+        agent.spend = RunSpend {
+            tokens: self.tokens,
+            cost: self.cost,
+            started: Instant::now()
+                .checked_sub(self.elapsed)
+                .unwrap_or_else(Instant::now),
+        };
+        (self.pending_tool_calls, self.finished)
+    }
+
+    /// The conversation history at the checkpoint.
+    pub fn history(&self) -> &[Message] {
+        &self.history
+    }
+
+    /// Tool calls the model made that have not run yet.  Resuming runs them
+    /// first.
+    pub fn pending_tool_calls(&self) -> &[ToolCall] {
+        &self.pending_tool_calls
+    }
+
+    /// The model the run was using.
+    pub fn model(&self) -> &str {
+        &self.config.model
+    }
+
+    /// The API turns made so far in the run.
+    pub fn turn(&self) -> u32 {
+        self.turn
+    }
+
+    /// Whether the run had ended with the model's final answer.  Resuming a
+    /// finished run yields no events.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::MockBackend;
+    use crate::raw::request::message::Role;
+
+    #[test]
+    fn restore_round_trips_through_json() {
+        let agent = DeepseekAgent::from_client(MockBackend::new(), "deepseek-reasoner")
+            .with_streaming()
+            .with_options(RequestOptions::new().temperature(0.3).tool_choice_none())
+            .with_budget(Budget::new().max_turns(4))
+            .with_max_steps(3)
+            .with_parallel_tools(2)
+            .with_tool_timeout_for("fetch", Duration::from_secs(5))
+            .with_history(vec![Message::new(Role::User, "hi")]);
+        let json = serde_json::to_string(&Checkpoint::capture(&agent, &[], false)).unwrap();
+
+        let checkpoint: Checkpoint = serde_json::from_str(&json).unwrap();
+        let mut resumed = DeepseekAgent::from_client(MockBackend::new(), "deepseek-chat");
+        let (pending, finished) = checkpoint.restore(&mut resumed);
+        assert!(pending.is_empty() && !finished);
+        assert_eq!(resumed.model, "deepseek-reasoner");
+        assert!(resumed.streaming);
+        assert_eq!(resumed.options.temperature, Some(0.3));
+        assert_eq!(resumed.budget.as_ref().unwrap().max_turns, Some(4));
+        assert_eq!(resumed.max_steps, Some(3));
+        assert_eq!(resumed.tool_concurrency, 2);
+        assert_eq!(
+            resumed.tool_timeouts.for_tool("fetch"),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(resumed.history().len(), 1);
+    }
+}
@@ -5,25 +5,34 @@
 //!
 //! | Function | Responsibility |
 //! |---|---|
-//! | [`build_request`] | Assemble an [`ApiRequest`] from current history + tools. |
-//! | [`run_summarize`] | Invoke `maybe_summarize` and hand the agent back. |
+//! | [`build_request`] | Assemble an [`ApiRequest`] from history, tools and options. |
+//! | [`run_summarize`] | Invoke `maybe_summarize`, persist history, and hand the agent back. |
+//! | [`run_persist`] | Persist history when a run ends and hand the agent back. |
 //! | [`fetch_response`] | Non-streaming API call; returns content + raw tool calls. |
 //! | [`connect_stream`] | Open an SSE stream and hand back the `BoxStream`. |
-//! | [`execute_tools`] | Dispatch all pending tool calls and collect results. |
+//! | [`apply_approvals`] | Apply human decisions to tool calls before they run. |
+//! | [`execute_tools`] | Dispatch all pending tool calls and record their results. |
 //!
 //! The streaming state machine in [`stream`][super::stream] is the only consumer of
 //! this module; nothing in here knows about [`Poll`] or [`Context`].  That separation
-//! makes it straightforward to add retry logic, timeouts, or parallel tool execution
-//! in the future without touching the state machine.
+//! makes it straightforward to add retry logic, timeouts, or other execution
+//! strategies in the future without touching the state machine.
 
-use futures::stream::BoxStream;
+use std::collections::HashMap;
+
+use futures::StreamExt;
 use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
 use crate::agent::agent_core::{DeepseekAgent, ToolCallResult};
-use crate::api::ApiRequest;
+use crate::agent::approval::ApprovalDecision;
+use crate::agent::options::TurnContext;
+use crate::api::typed::insert_system;
+use crate::api::{ApiRequest, ChunkStream};
 use crate::error::ApiError;
-use crate::raw::ChatCompletionChunk;
 use crate::raw::request::message::{FunctionCall, Message, Role, ToolCall, ToolType};
+use crate::raw::{FinishReason, Usage};
 
 // ── Internal result types ─────────────────────────────────────────────────────
 
@@ -35,12 +44,16 @@ pub(crate) struct FetchResult {
     pub(crate) reasoning_content: Option<String>,
     /// Raw tool-call objects requested by the model.
     pub(crate) raw_tool_calls: Vec<ToolCall>,
+    /// How the turn ended and what it cost.
+    pub(crate) turn_end: TurnEnd,
 }
 
-/// Outcome of a completed tool-execution pass.
-pub(crate) struct ToolsResult {
-    /// One [`ToolCallResult`] per dispatched tool call, in call order.
-    pub(crate) results: Vec<ToolCallResult>,
+/// Token usage and finish reason of one API turn, reported as
+/// `AgentEvent::Usage` and `AgentEvent::TurnFinished`.
+#[derive(Default)]
+pub(crate) struct TurnEnd {
+    pub(crate) usage: Option<Usage>,
+    pub(crate) finish_reason: Option<FinishReason>,
 }
 
 // ── Streaming accumulator ─────────────────────────────────────────────────────
@@ -57,7 +70,7 @@ pub(crate) struct PartialToolCall {
 /// Boxed by the caller so it fits neatly in one state-machine variant without
 /// blowing up the size of every other variant.
 pub(crate) struct StreamingData {
-    pub(crate) stream: BoxStream<'static, Result<ChatCompletionChunk, ApiError>>,
+    pub(crate) stream: ChunkStream,
     pub(crate) agent: DeepseekAgent,
     /// Accumulated text content across all deltas for the current turn.
     pub(crate) content_buf: String,
@@ -65,6 +78,8 @@ pub(crate) struct StreamingData {
     pub(crate) reasoning_buf: String,
     /// Per-index partial tool-call buffers; sparse — may contain `None` gaps.
     pub(crate) tool_call_bufs: Vec<Option<PartialToolCall>>,
+    /// Usage and finish reason, taken from whichever chunks carry them.
+    pub(crate) turn_end: TurnEnd,
 }
 
 /// An incremental streaming event produced by [`apply_chunk_delta`].
@@ -88,29 +103,32 @@ pub(crate) enum ChunkEvent {
 
 /// Future produced by [`fetch_response`].
 pub(crate) type FetchFuture = std::pin::Pin<
-    Box<dyn std::future::Future<Output = (Result<FetchResult, ApiError>, DeepseekAgent)> + Send>,
+    Box<
+        dyn std::future::Future<Output = (Option<Result<FetchResult, ApiError>>, DeepseekAgent)>
+            + Send,
+    >,
 >;
 
 /// Future produced by [`connect_stream`].
 pub(crate) type ConnectFuture = std::pin::Pin<
     Box<
-        dyn std::future::Future<
-                Output = (
-                    Result<BoxStream<'static, Result<ChatCompletionChunk, ApiError>>, ApiError>,
-                    DeepseekAgent,
-                ),
-            > + Send,
+        dyn std::future::Future<Output = (Option<Result<ChunkStream, ApiError>>, DeepseekAgent)>
+            + Send,
     >,
 >;
 
 /// Future produced by [`execute_tools`].
 pub(crate) type ExecFuture =
-    std::pin::Pin<Box<dyn std::future::Future<Output = (ToolsResult, DeepseekAgent)> + Send>>;
+    std::pin::Pin<Box<dyn std::future::Future<Output = DeepseekAgent> + Send>>;
 
 /// Future produced by [`run_summarize`].
 pub(crate) type SummarizeFuture =
     std::pin::Pin<Box<dyn std::future::Future<Output = DeepseekAgent> + Send>>;
 
+/// Future produced by [`run_persist`].
+pub(crate) type PersistFuture =
+    std::pin::Pin<Box<dyn std::future::Future<Output = DeepseekAgent> + Send>>;
+
 // ── Public helpers ────────────────────────────────────────────────────────────
 
 // ── Business-logic functions ──────────────────────────────────────────────────
@@ -166,6 +184,11 @@ pub(crate) fn build_request(agent: &DeepseekAgent) -> ApiRequest {
         })
         .collect();
 
+    let mut messages = messages;
+    if let Some(instruction) = &agent.instruction {
+        insert_system(&mut messages, instruction);
+    }
+
     let mut req = ApiRequest::builder()
         .with_model(agent.model.clone())
         .messages(messages);
@@ -178,6 +201,22 @@ pub(crate) fn build_request(agent: &DeepseekAgent) -> ApiRequest {
         req = req.tool_choice_auto();
     }
 
+    // Agent-wide options first, then whatever the turn hook changes for this
+    // turn; an explicit tool choice replaces the `auto` default above.
+    let mut options = agent.options.clone();
+    if let Some(hook) = &agent.turn_hook {
+        let ctx = TurnContext {
+            turn: agent.turn,
+            history,
+        };
+        options = hook(&ctx, options);
+    }
+    // Out of tool-loop steps: the model must answer without tools.
+    if !agent.tools.is_empty() && agent.steps_exhausted().is_some() {
+        options = options.tool_choice_none();
+    }
+    req = options.apply(req);
+
     // Merge any extra_body fields stored on the agent into the ApiRequest.
     // Clone because `agent` is borrowed immutably here.
     if let Some(ref map) = agent.extra_body {
@@ -187,12 +226,20 @@ pub(crate) fn build_request(agent: &DeepseekAgent) -> ApiRequest {
     req
 }
 
-/// Run `maybe_summarize` on the agent's conversation and return the agent.
+/// Run `maybe_summarize` on the agent's conversation, write the history to
+/// its store (if any), and return the agent.
 ///
 /// Ownership of the agent is taken so the future can be stored in the state
 /// machine without lifetime complications.
 pub(crate) async fn run_summarize(mut agent: DeepseekAgent) -> DeepseekAgent {
     agent.conversation.maybe_summarize().await;
+    agent.conversation.persist_or_warn().await;
+    agent
+}
+
+/// Write the messages of the run's last turn to the conversation's store.
+pub(crate) async fn run_persist(mut agent: DeepseekAgent) -> DeepseekAgent {
+    agent.conversation.persist_or_warn().await;
     agent
 }
 
@@ -202,28 +249,43 @@ pub(crate) async fn run_summarize(mut agent: DeepseekAgent) -> DeepseekAgent {
 /// history before returning.  On failure, the agent is returned alongside the
 /// error so the state machine can store it safely.
 ///
-/// Returns `(Result<FetchResult, ApiError>, DeepseekAgent)` so ownership is
-/// always transferred back to the caller regardless of outcome.
+/// Returns `(Option<Result<FetchResult, ApiError>>, DeepseekAgent)` so
+/// ownership is always transferred back to the caller regardless of outcome.
+/// The result is `None` if `cancel` fired before the response arrived; history
+/// is untouched in that case.
 pub(crate) async fn fetch_response(
     mut agent: DeepseekAgent,
-) -> (Result<FetchResult, ApiError>, DeepseekAgent) {
+    cancel: CancellationToken,
+) -> (Option<Result<FetchResult, ApiError>>, DeepseekAgent) {
     let req = build_request(&agent);
 
-    let resp = match agent.conversation.client.send(req).await {
-        Ok(r) => r,
-        Err(e) => return (Err(e), agent),
+    let sent = tokio::select! {
+        biased;
+        _ = cancel.cancelled() => None,
+        res = agent.conversation.backend.send(req) => Some(res),
+    };
+    let resp = match sent {
+        Some(Ok(r)) => r,
+        Some(Err(e)) => return (Some(Err(e)), agent),
+        None => return (None, agent),
     };
 
+    agent.record_usage(resp.model.as_str(), &resp.usage);
+    let usage = resp.usage;
     let choice = match resp.choices.into_iter().next() {
         Some(c) => c,
         None => {
             return (
-                Err(ApiError::Other("empty response: no choices".into())),
+                Some(Err(ApiError::Other("empty response: no choices".into()))),
                 agent,
             );
         }
     };
 
+    let turn_end = TurnEnd {
+        usage: Some(usage),
+        finish_reason: Some(choice.finish_reason),
+    };
     let assistant_msg = choice.message;
     let content = assistant_msg.content.clone();
     let reasoning_content = assistant_msg.reasoning_content.clone();
@@ -231,14 +293,15 @@ pub(crate) async fn fetch_response(
     // Keep reasoning_content in history so it can be sent back within the same
     // Turn (required by deepseek-reasoner when tool calls are involved).
     // It will be stripped at the start of the next Turn in drain_interrupts.
-    agent.conversation.history_mut().push(assistant_msg);
+    agent.conversation.push_assistant_reply(assistant_msg);
 
     (
-        Ok(FetchResult {
+        Some(Ok(FetchResult {
             content,
             reasoning_content,
             raw_tool_calls,
-        }),
+            turn_end,
+        })),
         agent,
     )
 }
@@ -249,90 +312,147 @@ pub(crate) async fn fetch_response(
 /// returned alongside the stream so the state machine can transition into
 /// [`StreamingChunks`][super::stream::AgentStreamState::StreamingChunks].
 ///
-/// Returns `(Result<BoxStream<…>, ApiError>, DeepseekAgent)` for the same
-/// ownership-transfer reason as [`fetch_response`].
+/// Returns `(Option<Result<ChunkStream, ApiError>>, DeepseekAgent)` for the
+/// same ownership-transfer reason as [`fetch_response`], with `None` likewise
+/// meaning `cancel` fired first.
 pub(crate) async fn connect_stream(
     agent: DeepseekAgent,
-) -> (
-    Result<BoxStream<'static, Result<ChatCompletionChunk, ApiError>>, ApiError>,
-    DeepseekAgent,
-) {
-    let req = build_request(&agent);
-    match agent.conversation.client.clone().into_stream(req).await {
-        Ok(stream) => (Ok(stream), agent),
-        Err(e) => (Err(e), agent),
-    }
+    cancel: CancellationToken,
+) -> (Option<Result<ChunkStream, ApiError>>, DeepseekAgent) {
+    // Ask for the trailing usage chunk so streamed turns report usage too.
+    let req = build_request(&agent).include_usage(true);
+    let connected = tokio::select! {
+        biased;
+        _ = cancel.cancelled() => None,
+        res = agent.conversation.backend.send_stream(req) => Some(res),
+    };
+    (connected, agent)
 }
 
-/// Execute all pending tool calls sequentially and collect results.
+/// Execute all pending tool calls and record their results.
 ///
-/// For each [`ToolCall`]:
-/// 1. The corresponding tool implementation is looked up by name.
-/// 2. The tool is called with the parsed argument [`Value`].
-/// 3. A `Role::Tool` message is appended to the conversation history so the
-///    model can see the result on the next turn.
-/// 4. A [`ToolCallResult`] is pushed to the results list.
+/// Calls whose entry in `settled` is already `Some` (rejected by a human) are
+/// not run; their result is reported first.  Of the rest, up to
+/// `agent.tool_concurrency` calls run at the same time (one by default, i.e.
+/// sequentially).  As each call finishes, its [`ToolCallResult`] is sent on
+/// `results_tx` so the state machine can report it right away.  Once the
+/// whole batch is done, one `Role::Tool` message per call is appended to the
+/// conversation history, in call order, so the model can see the results on
+/// the next turn.
 ///
 /// Unknown tool names produce an error-shaped JSON result rather than panicking,
-/// so a misconfigured agent degrades gracefully.
+/// so a misconfigured agent degrades gracefully.  A call that outlives its
+/// timeout is answered with a `"timed out"` error.  An interrupt that arrives
+/// while the batch runs aborts it: every call that has not finished yet is
+/// answered with an `"aborted by interrupt"` error.  Cancelling the run's
+/// `cancel` token aborts it the same way, with a `"cancelled"` error.
 ///
-/// Returns `(ToolsResult, DeepseekAgent)` — the agent is returned so the state
-/// machine can reclaim ownership after the future resolves.
+/// Every call gets a child of one batch-wide [`CancellationToken`], itself a
+/// child of `cancel`.  A call's token is cancelled when it times out; the
+/// batch token is cancelled on an interrupt or when this future is dropped
+/// with calls still running.
+///
+/// Returns the agent so the state machine can reclaim ownership after the
+/// future resolves.
 pub(crate) async fn execute_tools(
     mut agent: DeepseekAgent,
     raw_tool_calls: Vec<ToolCall>,
-) -> (ToolsResult, DeepseekAgent) {
-    let mut results = Vec::with_capacity(raw_tool_calls.len());
+    settled: Vec<Option<Value>>,
+    results_tx: mpsc::UnboundedSender<ToolCallResult>,
+    cancel: CancellationToken,
+) -> DeepseekAgent {
+    let mut outputs = settled;
+    for (tc, output) in raw_tool_calls.iter().zip(&outputs) {
+        if let Some(result) = output {
+            let _ = results_tx.send(tool_call_result(tc, result));
+        }
+    }
     // Buffer any interrupts that arrive during tool execution so they are
     // appended to history only after the tool-execution pass completes.
     // This prevents interrupt messages from being injected mid-request and
     // causing mismatches between tool_call messages and their corresponding
     // tool results.
     let mut buffered_interrupts: Vec<String> = Vec::new();
+    let batch_cancel = cancel.child_token();
+    let mut abort_error = "aborted by interrupt";
+    let cancel_on_drop = batch_cancel.clone().drop_guard();
 
-    for tc in raw_tool_calls {
-        let args: Value = serde_json::from_str(&tc.function.arguments).unwrap_or(Value::Null);
-
-        // Prepare a result value and an abort flag.
-        let mut result = serde_json::json!({ "error": "unknown tool" });
-        let mut aborted = false;
-
-        if let Some(&idx) = agent.tool_index.get(&tc.function.name) {
+    {
+        let tools = &agent.tools;
+        let tool_index = &agent.tool_index;
+        let timeouts = &agent.tool_timeouts;
+        let batch_cancel = &batch_cancel;
+        let calls = &raw_tool_calls;
+        let to_run: Vec<usize> = (0..calls.len()).filter(|&i| outputs[i].is_none()).collect();
+        let mut running = futures::stream::iter(to_run)
+            .map(|i| async move {
+                let tc = &calls[i];
+                let result = match tool_index.get(&tc.function.name) {
+                    Some(&idx) => {
+                        let args: Value =
+                            serde_json::from_str(&tc.function.arguments).unwrap_or(Value::Null);
+                        let cancel = batch_cancel.child_token();
+                        let call =
+                            tools[idx].call_with_cancel(&tc.function.name, args, cancel.clone());
+                        match timeouts.for_tool(&tc.function.name) {
+                            Some(limit) => match tokio::time::timeout(limit, call).await {
+                                Ok(result) => result,
+                                Err(_) => {
+                                    cancel.cancel();
+                                    serde_json::json!({
+                                        "error": "timed out",
+                                        "timeout_ms": limit.as_millis() as u64,
+                                    })
+                                }
+                            },
+                            None => call.await,
+                        }
+                    }
+                    None => serde_json::json!({
+                        "error": format!("unknown tool: {}", tc.function.name)
+                    }),
+                };
+                (i, result)
+            })
+            .buffer_unordered(agent.tool_concurrency);
+
+        loop {
             tokio::select! {
-                res = agent.tools[idx].call(&tc.function.name, args.clone()) => {
-                    result = res;
-                }
+                // A tool that has already finished keeps its result.
+                biased;
+                next = running.next() => match next {
+                    Some((i, result)) => {
+                        let _ = results_tx.send(tool_call_result(&raw_tool_calls[i], &result));
+                        outputs[i] = Some(result);
+                    }
+                    None => break,
+                },
                 maybe_msg = agent.interrupt_rx.recv() => {
                     if let Some(msg) = maybe_msg {
                         buffered_interrupts.push(msg);
-                        while let Ok(more) = agent.interrupt_rx.try_recv() {
-                            buffered_interrupts.push(more);
-                        }
                     }
-                    result = serde_json::json!({ "error": "aborted by interrupt" });
-                    aborted = true;
+                    batch_cancel.cancel();
+                    break;
+                }
+                _ = cancel.cancelled() => {
+                    abort_error = "cancelled";
+                    break;
                 }
             }
-        } else {
-            result = serde_json::json!({ "error": format!("unknown tool: {}", tc.function.name) });
         }
+    }
+    // Tools that finished may keep their token for work they left running.
+    cancel_on_drop.disarm();
 
-        agent.conversation.history_mut().push(Message {
-            role: Role::Tool,
-            content: Some(result.to_string()),
-            tool_call_id: Some(tc.id.clone()),
-            ..Default::default()
-        });
-
-        results.push(ToolCallResult {
-            id: tc.id,
-            name: tc.function.name,
-            args: tc.function.arguments,
-            result,
-        });
-
-        if aborted {
-            break;
+    for (tc, output) in raw_tool_calls.into_iter().zip(outputs) {
+        match output {
+            Some(result) => {
+                push_tool_result(&mut agent, tc, result);
+            }
+            None => {
+                let result = serde_json::json!({ "error": abort_error });
+                let _ = results_tx.send(push_tool_result(&mut agent, tc, result));
+            }
         }
     }
 
@@ -344,10 +464,90 @@ pub(crate) async fn execute_tools(
 
     // Append buffered interrupts to the conversation history in order.
     for msg in buffered_interrupts {
-        agent.conversation.history_mut().push(Message::user(&msg));
+        agent.conversation.push(Message::user(&msg));
     }
 
-    (ToolsResult { results }, agent)
+    agent
+}
+
+/// Apply human decisions to a batch of tool calls before it runs.
+///
+/// Edited calls get their new arguments, both in `raw_tool_calls` and in the
+/// assistant message in history.  Returns the `settled` list for
+/// [`execute_tools`]: rejected calls carry their error result.
+pub(crate) fn apply_approvals(
+    agent: &mut DeepseekAgent,
+    raw_tool_calls: &mut [ToolCall],
+    mut decisions: HashMap<String, ApprovalDecision>,
+) -> Vec<Option<Value>> {
+    raw_tool_calls
+        .iter_mut()
+        .map(|tc| match decisions.remove(&tc.id) {
+            None | Some(ApprovalDecision::Approve) => None,
+            Some(ApprovalDecision::Reject(reason)) => Some(serde_json::json!({
+                "error": "rejected by user",
+                "reason": reason,
+            })),
+            Some(ApprovalDecision::Edit(args)) => {
+                tc.function.arguments = args.to_string();
+                set_call_arguments(agent, &tc.id, &tc.function.arguments);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Rewrite the arguments of tool call `id` in the latest assistant message.
+fn set_call_arguments(agent: &mut DeepseekAgent, id: &str, arguments: &str) {
+    let history = agent.conversation.history_mut();
+    let call = history
+        .iter_mut()
+        .rev()
+        .filter_map(|m| m.tool_calls.as_mut())
+        .flat_map(|calls| calls.iter_mut())
+        .find(|tc| tc.id == id);
+    if let Some(call) = call {
+        call.function.arguments = arguments.to_string();
+    }
+}
+
+/// Answer tool calls that will not be executed with an error result, so every
+/// tool call in history still has a matching tool message.
+pub(crate) fn skip_tools(
+    agent: &mut DeepseekAgent,
+    raw_tool_calls: Vec<ToolCall>,
+    reason: &str,
+) -> Vec<ToolCallResult> {
+    raw_tool_calls
+        .into_iter()
+        .map(|tc| push_tool_result(agent, tc, serde_json::json!({ "error": reason })))
+        .collect()
+}
+
+/// Append the `Role::Tool` message answering `tc` and build its event payload.
+fn push_tool_result(agent: &mut DeepseekAgent, tc: ToolCall, result: Value) -> ToolCallResult {
+    agent.conversation.push(Message {
+        role: Role::Tool,
+        content: Some(result.to_string()),
+        tool_call_id: Some(tc.id.clone()),
+        ..Default::default()
+    });
+    ToolCallResult {
+        id: tc.id,
+        name: tc.function.name,
+        args: tc.function.arguments,
+        result,
+    }
+}
+
+/// The event payload for a finished call, before it is recorded in history.
+fn tool_call_result(tc: &ToolCall, result: &Value) -> ToolCallResult {
+    ToolCallResult {
+        id: tc.id.clone(),
+        name: tc.function.name.clone(),
+        args: tc.function.arguments.clone(),
+        result: result.clone(),
+    }
 }
 
 /// Finalize a completed SSE stream by assembling full [`ToolCall`] objects from
@@ -392,7 +592,7 @@ pub(crate) fn finalize_stream(data: &mut StreamingData) -> Vec<ToolCall> {
         },
         ..Default::default()
     };
-    data.agent.conversation.history_mut().push(assistant_msg);
+    data.agent.conversation.push_assistant_reply(assistant_msg);
 
     raw_tool_calls
 }
@@ -406,10 +606,18 @@ pub(crate) fn apply_chunk_delta(
     data: &mut StreamingData,
     chunk: crate::raw::ChatCompletionChunk,
 ) -> Vec<ChunkEvent> {
+    // Usage arrives on the last chunk, usually one with no choices.
+    if let Some(usage) = chunk.usage {
+        data.agent.record_usage(&chunk.model, &usage);
+        data.turn_end.usage = Some(usage);
+    }
     let choice = match chunk.choices.into_iter().next() {
         Some(c) => c,
         None => return vec![],
     };
+    if let Some(reason) = choice.finish_reason {
+        data.turn_end.finish_reason = Some(reason);
+    }
     let delta = choice.delta;
 
     if let Some(dtcs) = delta.tool_calls {
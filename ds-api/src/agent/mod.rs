@@ -5,9 +5,14 @@ This module is split into focused submodules:
 
 - `agent_core` — the public agent struct, event/response types and tool
   registration logic.
+- `approval` — human-in-the-loop approval of tool calls.
+- `budget` — per-run token, cost, time and turn limits.
+- `checkpoint` — serializable snapshots of a run, for resuming it later.
 - `executor` — pure business-logic functions: building requests, fetching
   responses, opening SSE streams, executing tools.  No `Poll` or `Context`
   here — just `async fn`s that do real work.
+- `options` — per-turn request options and the turn-hook context.
+- `typed` — `chat_typed`, final answers deserialized into a Rust type.
 - `stream` — the asynchronous `AgentStream` state machine that schedules
   calls into `executor` and drives the full agent loop.
 
@@ -16,8 +21,17 @@ reach into the submodules directly.
 */
 
 pub mod agent_core;
+pub mod approval;
+pub mod budget;
+pub mod checkpoint;
 pub(crate) mod executor;
+pub mod options;
 pub mod stream;
+mod typed;
 
 pub use agent_core::{AgentEvent, DeepseekAgent, ToolCallChunk, ToolCallResult, ToolInjection};
-pub use stream::AgentStream;
+pub use approval::{ApprovalHandle, ApprovalPolicy, PendingToolCall};
+pub use budget::{Budget, BudgetLimit};
+pub use checkpoint::Checkpoint;
+pub use options::{RequestOptions, TurnContext};
+pub use stream::{AgentStream, CancelHandle};
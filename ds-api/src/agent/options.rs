@@ -0,0 +1,194 @@
+//! Per-turn request options for [`DeepseekAgent`][crate::agent::DeepseekAgent].
+//!
+//! [`RequestOptions`] holds the sampling and tool-choice settings applied to
+//! every API turn the agent makes.  A turn hook registered with
+//! [`with_turn_hook`][crate::agent::DeepseekAgent::with_turn_hook] can adjust
+//! them for individual turns, based on the [`TurnContext`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::ApiRequest;
+use crate::raw::request::message::Message;
+use crate::raw::{ResponseFormat, ResponseFormatType};
+
+/// Request settings applied to every agent turn.  Unset fields keep the
+/// server defaults.
+///
+/// The tool choice only applies to turns that offer tools; an agent without
+/// tools sends none.
+///
+/// # Example
+///
+/// ```no_run
+/// use ds_api::{DeepseekAgent, RequestOptions};
+///
+/// let agent = DeepseekAgent::new("sk-...").with_options(
+///     RequestOptions::new()
+///         .temperature(0.2)
+///         .max_tokens(1024)
+///         .stop(["</answer>"]),
+/// );
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RequestOptions {
+    pub(crate) temperature: Option<f32>,
+    pub(crate) top_p: Option<f32>,
+    pub(crate) max_tokens: Option<u32>,
+    pub(crate) frequency_penalty: Option<f32>,
+    pub(crate) presence_penalty: Option<f32>,
+    pub(crate) stop: Option<Vec<String>>,
+    pub(crate) thinking: Option<bool>,
+    pub(crate) tool_choice: Option<ToolChoice>,
+    /// Set with [`json`][Self::json], and for the length of one run by
+    /// [`chat_typed`][crate::agent::DeepseekAgent::chat_typed] in
+    /// [`SchemaMode::ResponseFormat`][crate::api::SchemaMode::ResponseFormat].
+    pub(crate) response_format: Option<ResponseFormat>,
+}
+
+/// Which tools the model may call on a turn.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum ToolChoice {
+    Auto,
+    None,
+    Required,
+    Function(String),
+}
+
+impl RequestOptions {
+    /// Create an empty set of options (server defaults).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: sampling temperature (0–2).
+    pub fn temperature(mut self, t: f32) -> Self {
+        self.temperature = Some(t);
+        self
+    }
+
+    /// Builder: nucleus sampling probability mass (0–1).
+    pub fn top_p(mut self, p: f32) -> Self {
+        self.top_p = Some(p);
+        self
+    }
+
+    /// Builder: maximum tokens generated per turn.
+    pub fn max_tokens(mut self, n: u32) -> Self {
+        self.max_tokens = Some(n);
+        self
+    }
+
+    /// Builder: frequency penalty (-2–2).
+    pub fn frequency_penalty(mut self, v: f32) -> Self {
+        self.frequency_penalty = Some(v);
+        self
+    }
+
+    /// Builder: presence penalty (-2–2).
+    pub fn presence_penalty(mut self, v: f32) -> Self {
+        self.presence_penalty = Some(v);
+        self
+    }
+
+    /// Builder: stop sequences (at most 16).
+    pub fn stop<I, S>(mut self, sequences: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.stop = Some(sequences.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Builder: enable or disable thinking (reasoning) mode.
+    pub fn thinking(mut self, enabled: bool) -> Self {
+        self.thinking = Some(enabled);
+        self
+    }
+
+    /// Builder: let the model decide whether to call tools (the default when
+    /// the agent has tools).
+    pub fn tool_choice_auto(mut self) -> Self {
+        self.tool_choice = Some(ToolChoice::Auto);
+        self
+    }
+
+    /// Builder: forbid tool calls, so the model must answer in text.
+    pub fn tool_choice_none(mut self) -> Self {
+        self.tool_choice = Some(ToolChoice::None);
+        self
+    }
+
+    /// Builder: require at least one tool call.
+    pub fn tool_choice_required(mut self) -> Self {
+        self.tool_choice = Some(ToolChoice::Required);
+        self
+    }
+
+    /// Builder: force a call to the function `name`.
+    pub fn tool_choice_function(mut self, name: impl Into<String>) -> Self {
+        self.tool_choice = Some(ToolChoice::Function(name.into()));
+        self
+    }
+
+    /// Builder: request `json_object` output.
+    pub fn json(mut self) -> Self {
+        self.response_format = Some(ResponseFormat {
+            r#type: ResponseFormatType::JsonObject,
+            json_schema: None,
+        });
+        self
+    }
+
+    /// Apply the options to `req`, overriding what is already set.  The tool
+    /// choice is left out when `req` has no tools, where the API rejects it.
+    pub(crate) fn apply(&self, mut req: ApiRequest) -> ApiRequest {
+        if let Some(t) = self.temperature {
+            req = req.temperature(t);
+        }
+        if let Some(p) = self.top_p {
+            req = req.top_p(p);
+        }
+        if let Some(n) = self.max_tokens {
+            req = req.max_tokens(n);
+        }
+        if let Some(v) = self.frequency_penalty {
+            req = req.frequency_penalty(v);
+        }
+        if let Some(v) = self.presence_penalty {
+            req = req.presence_penalty(v);
+        }
+        if let Some(stop) = &self.stop {
+            req = req.stop(stop.iter().cloned());
+        }
+        if let Some(enabled) = self.thinking {
+            req = req.thinking(enabled);
+        }
+        if let Some(format) = &self.response_format {
+            req.raw.response_format = Some(format.clone());
+        }
+        if req.raw.tools.as_ref().is_none_or(Vec::is_empty) {
+            return req;
+        }
+        match &self.tool_choice {
+            Some(ToolChoice::Auto) => req.tool_choice_auto(),
+            Some(ToolChoice::None) => req.tool_choice_none(),
+            Some(ToolChoice::Required) => req.tool_choice_required(),
+            Some(ToolChoice::Function(name)) => req.tool_choice_function(name.clone()),
+            None => req,
+        }
+    }
+}
+
+/// What a turn hook knows about the turn being built.
+#[derive(Debug, Clone, Copy)]
+pub struct TurnContext<'a> {
+    /// 1-based index of this API turn within the current
+    /// [`chat`][crate::agent::DeepseekAgent::chat] run.
+    pub turn: u32,
+    /// The conversation history the request is built from.
+    pub history: &'a [Message],
+}
+
+/// A hook that adjusts [`RequestOptions`] for a single turn.
+pub(crate) type TurnHook = dyn Fn(&TurnContext<'_>, RequestOptions) -> RequestOptions + Send + Sync;
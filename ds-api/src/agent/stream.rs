@@ -7,30 +7,43 @@
 //! ```text
 //! AgentStream::poll_next
 //!   │
-//!   ├─ Idle              → spawn run_summarize future
+//!   ├─ Idle              → spawn run_summarize future | Done  (yield BudgetExceeded)
 //!   ├─ Summarizing       → poll future → ConnectingStream | FetchingResponse
-//!   ├─ FetchingResponse  → poll future → YieldingToolCalls | Done  (yield Token)
+//!   ├─ FetchingResponse  → poll future → YieldingToolCalls | Done  (yield Token, Usage, TurnFinished)
 //!   ├─ ConnectingStream  → poll future → StreamingChunks
-//!   ├─ StreamingChunks   → poll inner stream → yield Token | YieldingToolCalls | Done
-//!   ├─ YieldingToolCalls → drain queue → ExecutingTools  (yield ToolCall per item)
-//!   ├─ ExecutingTools    → poll future → YieldingToolResults
-//!   ├─ YieldingToolResults → drain queue → Idle  (yield ToolResult per item)
-//!   └─ Done              → Poll::Ready(None)
+//!   ├─ StreamingChunks   → poll inner stream → yield Token | YieldingToolCalls | Done  (yield Usage, TurnFinished)
+//!   ├─ YieldingToolCalls → drain queue → ExecutingTools | AwaitingApproval | Done  (yield ToolCall per item)
+//!   ├─ AwaitingApproval  → collect decisions → ExecutingTools  (yield ApprovalRequired per gated call)
+//!   ├─ ExecutingTools    → poll future → Idle  (yield ToolResult as each tool finishes)
+//!   ├─ Persisting        → poll future → Done
+//!   └─ Done              → Persisting (once, with unsaved history) | Poll::Ready(None)
 //! ```
+//!
+//! A [`CancelHandle`] ends the run at the next safe point: states that hold the
+//! agent stop right away, the request and tool futures stop early through the
+//! run's cancellation token, and summarization is allowed to finish.
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
 use futures::{Stream, StreamExt};
+use tokio::sync::mpsc;
+use tokio_util::sync::{CancellationToken, WaitForCancellationFutureOwned};
 
 use super::executor::{
-    ChunkEvent, ConnectFuture, ExecFuture, FetchFuture, StreamingData, SummarizeFuture,
-    apply_chunk_delta, connect_stream, execute_tools, fetch_response, finalize_stream,
-    run_summarize,
+    ChunkEvent, ConnectFuture, ExecFuture, FetchFuture, PersistFuture, StreamingData,
+    SummarizeFuture, TurnEnd, apply_approvals, apply_chunk_delta, connect_stream, execute_tools,
+    fetch_response, finalize_stream, run_persist, run_summarize, skip_tools,
 };
 use crate::agent::agent_core::{AgentEvent, DeepseekAgent, ToolCallChunk, ToolCallResult};
+use crate::agent::approval::ApprovalDecision;
+use crate::agent::budget::RunSpend;
+use crate::agent::checkpoint::Checkpoint;
+use crate::api::RetryEvent;
+use crate::api::retry::with_retry_listener;
 use crate::error::ApiError;
+use crate::raw::request::message::ToolCall;
 
 // ── State machine ─────────────────────────────────────────────────────────────
 
@@ -57,6 +70,14 @@ use crate::error::ApiError;
 ///         AgentEvent::ToolCall(c) => print!("{}", c.delta),
 ///         AgentEvent::ToolResult(res) => println!("[result: {}]", res.result),
 ///         AgentEvent::ReasoningToken(text) => print!("{text}"),
+///         AgentEvent::Retry(r) => eprintln!("[retrying in {:?}: {}]", r.delay, r.error),
+///         AgentEvent::Usage(u) => eprintln!("[{} tokens]", u.total_tokens),
+///         AgentEvent::TurnFinished { finish_reason } => eprintln!("[{finish_reason:?}]"),
+///         AgentEvent::BudgetExceeded(limit) => eprintln!("[stopped: {limit}]"),
+///         AgentEvent::MaxStepsReached { max_steps } => eprintln!("[{max_steps} steps used]"),
+///         AgentEvent::ApprovalRequired { call } => eprintln!("[{} needs approval]", call.name),
+///         AgentEvent::Checkpoint(_) => {}
+///         _ => {}
 ///     }
 /// }
 /// # Ok(())
@@ -69,6 +90,48 @@ pub struct AgentStream {
     /// Small queue for cases where one logical response produces multiple events
     /// (e.g. non-streaming deepseek-reasoner: ReasoningToken then Token).
     pending_events: VecDeque<AgentEvent>,
+    /// Retries performed by the client while one of our futures is in flight
+    /// are reported here (see [`with_retry_listener`]).
+    retry_tx: mpsc::UnboundedSender<RetryEvent>,
+    retry_rx: mpsc::UnboundedReceiver<RetryEvent>,
+    /// Results of tool calls, sent by the executing future as each one
+    /// finishes.
+    tool_results_tx: mpsc::UnboundedSender<ToolCallResult>,
+    tool_results_rx: mpsc::UnboundedReceiver<ToolCallResult>,
+    /// Cancelled by a [`CancelHandle`]; the in-flight futures watch it too.
+    cancel: CancellationToken,
+    /// Registers our waker with `cancel` while the stream is parked.
+    cancel_wait: Pin<Box<WaitForCancellationFutureOwned>>,
+    /// A tool batch just finished; report a checkpoint once its results are
+    /// out.
+    checkpoint_due: bool,
+    /// The final save to the conversation's store has been attempted.
+    persisted: bool,
+}
+
+/// Stops a running [`AgentStream`], typically from a "Stop" button in another
+/// task.  Obtain one with [`AgentStream::cancel_handle`].
+///
+/// After [`cancel`][Self::cancel], keep polling the stream: it reports the
+/// tool calls it answered with a `"cancelled"` error and then ends.
+/// [`AgentStream::into_agent`] then returns the agent, with a history that is
+/// ready for the next `chat()`: a reply that was still streaming is
+/// discarded, and every tool call has a matching tool message.
+#[derive(Debug, Clone)]
+pub struct CancelHandle {
+    token: CancellationToken,
+}
+
+impl CancelHandle {
+    /// End the run at the next safe point.  Calling it again has no effect.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Whether [`cancel`][Self::cancel] has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
 }
 
 /// Every variant is self-contained: it either holds the agent directly or stores
@@ -91,11 +154,20 @@ pub(crate) enum AgentStreamState {
         pending: VecDeque<crate::raw::request::message::ToolCall>,
         raw: Vec<crate::raw::request::message::ToolCall>,
         from_streaming: bool,
+        /// Reported once the tool calls are out, before execution starts.
+        turn_end: TurnEnd,
+    },
+    /// Waiting for a human decision on every call in `waiting` before the
+    /// turn's tools run.  The agent stays in `AgentStream::agent`.
+    AwaitingApproval {
+        calls: Vec<crate::raw::request::message::ToolCall>,
+        waiting: HashSet<String>,
+        decisions: HashMap<String, ApprovalDecision>,
     },
     /// Awaiting parallel/sequential tool execution.
     ExecutingTools(ExecFuture),
-    /// Yielding individual `ToolResult` events after execution completes.
-    YieldingToolResults { pending: VecDeque<ToolCallResult> },
+    /// Writing the end of the run to the conversation's store.
+    Persisting(PersistFuture),
     /// Terminal state — the stream will never produce another item.
     Done,
 }
@@ -104,30 +176,153 @@ pub(crate) enum AgentStreamState {
 
 impl AgentStream {
     /// Wrap an agent and start in the `Idle` state.
-    pub fn new(agent: DeepseekAgent) -> Self {
+    pub fn new(mut agent: DeepseekAgent) -> Self {
+        agent.turn = 0;
+        agent.spend = RunSpend::new();
+        Self::start(agent)
+    }
+
+    /// Continue a run restored from a checkpoint: run the `pending` tool
+    /// calls first, or end at once if the run had `finished`.
+    pub(crate) fn resume(agent: DeepseekAgent, pending: Vec<ToolCall>, finished: bool) -> Self {
+        let mut stream = Self::start(agent);
+        if finished {
+            stream.state = AgentStreamState::Done;
+        } else if !pending.is_empty() {
+            let agent = stream.agent.take().unwrap();
+            stream.dispatch_tools(agent, pending);
+        }
+        stream
+    }
+
+    fn start(agent: DeepseekAgent) -> Self {
+        let (retry_tx, retry_rx) = mpsc::unbounded_channel();
+        let (tool_results_tx, tool_results_rx) = mpsc::unbounded_channel();
+        let cancel = CancellationToken::new();
         Self {
             agent: Some(agent),
             state: AgentStreamState::Idle,
             pending_events: VecDeque::new(),
+            retry_tx,
+            retry_rx,
+            tool_results_tx,
+            tool_results_rx,
+            cancel_wait: Box::pin(cancel.clone().cancelled_owned()),
+            cancel,
+            checkpoint_due: false,
+            persisted: false,
+        }
+    }
+
+    /// A handle that ends this run from anywhere; see [`CancelHandle`].
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle {
+            token: self.cancel.clone(),
         }
     }
 
-    /// Consume the stream and return the agent.
+    /// Consume the stream and return the agent, so the conversation can
+    /// continue without constructing a new one.
     ///
-    /// If the stream finished normally (or was dropped mid-stream), the agent is
-    /// returned so callers can continue the conversation without constructing a
-    /// new one.
+    /// Once the stream has ended — normally, with an error, or after a
+    /// [`CancelHandle::cancel`] — the agent is always returned.  After a
+    /// cancel its history is ready for the next `chat()`:
     ///
-    /// Returns `None` only if the agent is currently owned by an in-progress
-    /// future (i.e. the stream was dropped mid-poll, which is very unusual).
+    /// - cancelled while a reply was being requested or streamed, the partial
+    ///   reply is dropped and history ends where it did before that turn (an
+    ///   assistant prefix for that turn is dropped too, as after an error);
+    /// - cancelled while tool calls were waiting for approval or running,
+    ///   the assistant message stays and every call has a tool message, those
+    ///   that did not finish answered with `{"error": "cancelled"}`.
+    ///
+    /// Called before the stream has ended, it returns `None` if a request,
+    /// summarization, tool batch or final save still owns the agent; after a
+    /// cancel, keep polling until the stream yields `None` to get it back.
+    /// Otherwise the agent is returned as it is: a partly streamed reply is
+    /// dropped, but tool calls not yet run are left without results.
     pub fn into_agent(self) -> Option<DeepseekAgent> {
         match self.state {
-            AgentStreamState::StreamingChunks(data) => Some(data.agent),
+            AgentStreamState::StreamingChunks(data) => {
+                let mut agent = data.agent;
+                agent.conversation.drop_pending_prefix();
+                Some(agent)
+            }
             _ => self.agent,
         }
     }
 }
 
+impl AgentStream {
+    /// End the run on a turn that got no reply (an error or a cancel).  A
+    /// pending assistant prefix is removed with it, so the history can take
+    /// the next user message.
+    fn abandon_turn(&mut self, mut agent: DeepseekAgent) {
+        agent.conversation.drop_pending_prefix();
+        self.agent = Some(agent);
+        self.state = AgentStreamState::Done;
+    }
+
+    /// Start on a turn's tool calls, which are already in history: skip them
+    /// if the run has to stop, wait for approvals, or run them.
+    ///
+    /// The checkpoint is taken here, once it is known whether the calls will
+    /// run: a run that stops reports a finished checkpoint with the skipped
+    /// calls answered, so resuming it cannot run them.
+    fn dispatch_tools(&mut self, mut agent: DeepseekAgent, raw_calls: Vec<ToolCall>) {
+        // Over budget: answer the calls without running them and stop.
+        if let Some(limit) = agent.budget_exceeded() {
+            let reason = format!("not executed: {limit}");
+            for result in skip_tools(&mut agent, raw_calls, &reason) {
+                self.pending_events
+                    .push_back(AgentEvent::ToolResult(result));
+            }
+            queue_checkpoint(&mut self.pending_events, &agent, &[], true);
+            self.pending_events
+                .push_back(AgentEvent::BudgetExceeded(limit));
+            self.agent = Some(agent);
+            self.state = AgentStreamState::Done;
+            return;
+        }
+        // The final, tool-free turn still called tools: skip them too.
+        if let Some(max_steps) = agent.steps_exhausted() {
+            let reason = format!("not executed: tool-call limit of {max_steps} steps reached");
+            for result in skip_tools(&mut agent, raw_calls, &reason) {
+                self.pending_events
+                    .push_back(AgentEvent::ToolResult(result));
+            }
+            queue_checkpoint(&mut self.pending_events, &agent, &[], true);
+            queue_run_end(&mut self.pending_events, &agent);
+            self.agent = Some(agent);
+            self.state = AgentStreamState::Done;
+            return;
+        }
+        queue_checkpoint(&mut self.pending_events, &agent, &raw_calls, false);
+        let gated = agent.calls_needing_approval(&raw_calls);
+        if !gated.is_empty() {
+            let waiting = gated.iter().map(|call| call.id.clone()).collect();
+            for call in gated {
+                self.pending_events
+                    .push_back(AgentEvent::ApprovalRequired { call });
+            }
+            self.agent = Some(agent);
+            self.state = AgentStreamState::AwaitingApproval {
+                calls: raw_calls,
+                waiting,
+                decisions: HashMap::new(),
+            };
+            return;
+        }
+        let settled = vec![None; raw_calls.len()];
+        self.state = AgentStreamState::ExecutingTools(Box::pin(execute_tools(
+            agent,
+            raw_calls,
+            settled,
+            self.tool_results_tx.clone(),
+            self.cancel.clone(),
+        )));
+    }
+}
+
 // ── Stream implementation ─────────────────────────────────────────────────────
 
 impl Stream for AgentStream {
@@ -142,6 +337,31 @@ impl Stream for AgentStream {
                 return Poll::Ready(Some(Ok(ev)));
             }
 
+            // Surface retries reported by the client.  Polling the receiver
+            // also registers our waker, so a retry sent from inside an
+            // in-flight future wakes the stream immediately.
+            if let Poll::Ready(Some(retry)) = this.retry_rx.poll_recv(cx) {
+                return Poll::Ready(Some(Ok(AgentEvent::Retry(retry))));
+            }
+
+            // Same for tool results.  Every result is sent before the
+            // executing future resolves, so all of them are drained here
+            // before the next turn starts.
+            if let Poll::Ready(Some(result)) = this.tool_results_rx.poll_recv(cx) {
+                return Poll::Ready(Some(Ok(AgentEvent::ToolResult(result))));
+            }
+
+            if this.cancel.is_cancelled() || this.cancel_wait.as_mut().poll(cx).is_ready() {
+                stop_at_safe_point(this);
+                // Report the calls answered on the way out, if any, then
+                // fall through to the final save.
+                if matches!(this.state, AgentStreamState::Done)
+                    && let Some(ev) = this.pending_events.pop_front()
+                {
+                    return Poll::Ready(Some(Ok(ev)));
+                }
+            }
+
             // ── StreamingChunks is handled first to avoid borrow-checker
             //    conflicts: we need to both poll the inner stream *and* replace
             //    `this.state`, which requires owning the data.
@@ -194,20 +414,25 @@ impl Stream for AgentStream {
                     }
 
                     Poll::Ready(Some(Err(e))) => {
-                        // Stream errored — salvage the agent and terminate.
-                        this.agent = Some(data.agent);
-                        // state stays Done (set above via mem::replace)
+                        // Stream errored (including the client's stream
+                        // timeouts, e.g. `StreamIdleTimeout`) — salvage the
+                        // agent and terminate.
+                        this.abandon_turn(data.agent);
                         return Poll::Ready(Some(Err(e)));
                     }
 
                     Poll::Ready(None) => {
                         // SSE stream ended — assemble full tool calls from buffers.
                         let raw_tool_calls = finalize_stream(&mut data);
+                        let turn_end = std::mem::take(&mut data.turn_end);
 
                         if raw_tool_calls.is_empty() {
+                            queue_turn_end(&mut this.pending_events, turn_end);
+                            queue_checkpoint(&mut this.pending_events, &data.agent, &[], true);
+                            queue_run_end(&mut this.pending_events, &data.agent);
                             this.agent = Some(data.agent);
                             this.state = AgentStreamState::Done;
-                            return Poll::Ready(None);
+                            continue;
                         }
 
                         this.agent = Some(data.agent);
@@ -215,6 +440,7 @@ impl Stream for AgentStream {
                             pending: VecDeque::new(),
                             raw: raw_tool_calls,
                             from_streaming: true,
+                            turn_end,
                         };
                         continue;
                     }
@@ -223,35 +449,78 @@ impl Stream for AgentStream {
 
             // ── All other states ──────────────────────────────────────────────
             match &mut this.state {
-                AgentStreamState::Done => return Poll::Ready(None),
+                AgentStreamState::Done => {
+                    // Save the last turn once before ending the stream.
+                    let unsaved = this
+                        .agent
+                        .as_ref()
+                        .is_some_and(|agent| agent.conversation.has_unpersisted());
+                    if std::mem::replace(&mut this.persisted, true) || !unsaved {
+                        return Poll::Ready(None);
+                    }
+                    let agent = this.agent.take().unwrap();
+                    this.state = AgentStreamState::Persisting(Box::pin(run_persist(agent)));
+                }
+
+                AgentStreamState::Persisting(fut) => match fut.as_mut().poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(agent) => {
+                        this.agent = Some(agent);
+                        this.state = AgentStreamState::Done;
+                    }
+                },
 
                 AgentStreamState::Idle => {
                     let agent = this.agent.as_mut().expect("agent missing in Idle state");
+                    if std::mem::take(&mut this.checkpoint_due) {
+                        let checkpoint = Checkpoint::capture(agent, &[], false);
+                        return Poll::Ready(Some(Ok(AgentEvent::Checkpoint(Box::new(checkpoint)))));
+                    }
+                    // History is complete here, so the run can stop cleanly.
+                    if let Some(limit) = agent.budget_exceeded() {
+                        this.state = AgentStreamState::Done;
+                        return Poll::Ready(Some(Ok(AgentEvent::BudgetExceeded(limit))));
+                    }
                     agent.drain_interrupts();
                     agent.drain_tool_injections();
+                    agent.turn += 1;
                     let agent = this.agent.take().unwrap();
-                    this.state = AgentStreamState::Summarizing(Box::pin(run_summarize(agent)));
+                    this.state = AgentStreamState::Summarizing(Box::pin(with_retry_listener(
+                        this.retry_tx.clone(),
+                        run_summarize(agent),
+                    )));
                 }
 
                 AgentStreamState::Summarizing(fut) => match fut.as_mut().poll(cx) {
                     Poll::Pending => return Poll::Pending,
                     Poll::Ready(agent) => {
+                        let retry_tx = this.retry_tx.clone();
+                        let cancel = this.cancel.clone();
                         this.state = if agent.streaming {
-                            AgentStreamState::ConnectingStream(Box::pin(connect_stream(agent)))
+                            AgentStreamState::ConnectingStream(Box::pin(with_retry_listener(
+                                retry_tx,
+                                connect_stream(agent, cancel),
+                            )))
                         } else {
-                            AgentStreamState::FetchingResponse(Box::pin(fetch_response(agent)))
+                            AgentStreamState::FetchingResponse(Box::pin(with_retry_listener(
+                                retry_tx,
+                                fetch_response(agent, cancel),
+                            )))
                         };
                     }
                 },
 
                 AgentStreamState::FetchingResponse(fut) => match fut.as_mut().poll(cx) {
                     Poll::Pending => return Poll::Pending,
-                    Poll::Ready((Err(e), agent)) => {
-                        this.agent = Some(agent);
-                        this.state = AgentStreamState::Done;
+                    Poll::Ready((None, agent)) => {
+                        // Cancelled before the reply arrived.
+                        this.abandon_turn(agent);
+                    }
+                    Poll::Ready((Some(Err(e)), agent)) => {
+                        this.abandon_turn(agent);
                         return Poll::Ready(Some(Err(e)));
                     }
-                    Poll::Ready((Ok(fetch), agent)) => {
+                    Poll::Ready((Some(Ok(fetch)), agent)) => {
                         this.agent = Some(agent);
 
                         if fetch.raw_tool_calls.is_empty() {
@@ -264,6 +533,11 @@ impl Stream for AgentStream {
                             if let Some(text) = fetch.content {
                                 this.pending_events.push_back(AgentEvent::Token(text));
                             }
+                            queue_turn_end(&mut this.pending_events, fetch.turn_end);
+                            if let Some(agent) = &this.agent {
+                                queue_checkpoint(&mut this.pending_events, agent, &[], true);
+                                queue_run_end(&mut this.pending_events, agent);
+                            }
                             // The pending_events drain at the top of the loop will emit them.
                             continue;
                         }
@@ -279,6 +553,7 @@ impl Stream for AgentStream {
                             pending,
                             raw: fetch.raw_tool_calls,
                             from_streaming: false,
+                            turn_end: fetch.turn_end,
                         };
 
                         if let Some(event) = maybe_text {
@@ -290,18 +565,21 @@ impl Stream for AgentStream {
 
                 AgentStreamState::ConnectingStream(fut) => match fut.as_mut().poll(cx) {
                     Poll::Pending => return Poll::Pending,
-                    Poll::Ready((Err(e), agent)) => {
-                        this.agent = Some(agent);
-                        this.state = AgentStreamState::Done;
+                    Poll::Ready((None, agent)) => {
+                        this.abandon_turn(agent);
+                    }
+                    Poll::Ready((Some(Err(e)), agent)) => {
+                        this.abandon_turn(agent);
                         return Poll::Ready(Some(Err(e)));
                     }
-                    Poll::Ready((Ok(stream), agent)) => {
+                    Poll::Ready((Some(Ok(stream)), agent)) => {
                         this.state = AgentStreamState::StreamingChunks(Box::new(StreamingData {
                             stream,
                             agent,
                             content_buf: String::new(),
                             reasoning_buf: String::new(),
                             tool_call_bufs: Vec::new(),
+                            turn_end: TurnEnd::default(),
                         }));
                         // Loop back to hit the StreamingChunks branch.
                     }
@@ -311,6 +589,7 @@ impl Stream for AgentStream {
                     pending,
                     raw,
                     from_streaming,
+                    turn_end,
                 } => {
                     if !*from_streaming && let Some(tc) = pending.pop_front() {
                         return Poll::Ready(Some(Ok(AgentEvent::ToolCall(ToolCallChunk {
@@ -326,28 +605,54 @@ impl Stream for AgentStream {
                         .take()
                         .expect("agent missing in YieldingToolCalls");
                     let raw_calls = std::mem::take(raw);
-                    this.state =
-                        AgentStreamState::ExecutingTools(Box::pin(execute_tools(agent, raw_calls)));
+                    queue_turn_end(&mut this.pending_events, std::mem::take(turn_end));
+                    this.dispatch_tools(agent, raw_calls);
+                }
+
+                AgentStreamState::AwaitingApproval {
+                    calls,
+                    waiting,
+                    decisions,
+                } => {
+                    let agent = this
+                        .agent
+                        .as_mut()
+                        .expect("agent missing in AwaitingApproval");
+                    while !waiting.is_empty() {
+                        // The agent holds a sender, so the channel never closes.
+                        let Poll::Ready(Some((id, decision))) = agent.approval_rx.poll_recv(cx)
+                        else {
+                            return Poll::Pending;
+                        };
+                        // Decisions for calls that are not waiting are dropped.
+                        if waiting.remove(&id) {
+                            decisions.insert(id, decision);
+                        }
+                    }
+                    let mut agent = this.agent.take().unwrap();
+                    let mut raw_calls = std::mem::take(calls);
+                    let settled =
+                        apply_approvals(&mut agent, &mut raw_calls, std::mem::take(decisions));
+                    this.state = AgentStreamState::ExecutingTools(Box::pin(execute_tools(
+                        agent,
+                        raw_calls,
+                        settled,
+                        this.tool_results_tx.clone(),
+                        this.cancel.clone(),
+                    )));
                 }
 
                 AgentStreamState::ExecutingTools(fut) => match fut.as_mut().poll(cx) {
                     Poll::Pending => return Poll::Pending,
-                    Poll::Ready((tools_result, agent)) => {
+                    Poll::Ready(agent) => {
+                        // Results still queued are delivered before the next
+                        // API turn by the drain at the top of the loop.
+                        this.checkpoint_due = agent.checkpoints;
                         this.agent = Some(agent);
-                        this.state = AgentStreamState::YieldingToolResults {
-                            pending: tools_result.results.into_iter().collect(),
-                        };
+                        this.state = AgentStreamState::Idle;
                     }
                 },
 
-                AgentStreamState::YieldingToolResults { pending } => {
-                    if let Some(result) = pending.pop_front() {
-                        return Poll::Ready(Some(Ok(AgentEvent::ToolResult(result))));
-                    }
-                    // All results delivered — loop back for the next API turn.
-                    this.state = AgentStreamState::Idle;
-                }
-
                 // Handled in the dedicated block above; this arm is unreachable
                 // but the compiler cannot verify that without exhaustiveness help.
                 AgentStreamState::StreamingChunks(_) => unreachable!(),
@@ -355,3 +660,77 @@ impl Stream for AgentStream {
         }
     }
 }
+
+/// Handle a cancellation request in the current state.
+///
+/// States that hold the agent stop here, answering any tool calls that will
+/// not run; the stream is left `Done`.  States that wait on a future are left
+/// alone: the request and tool futures watch the cancellation token and
+/// resolve early, and summarization, which rewrites history, and the final
+/// save are allowed to finish.  The run then stops when the loop next gets here.
+fn stop_at_safe_point(this: &mut AgentStream) {
+    let reason = "cancelled";
+    match std::mem::replace(&mut this.state, AgentStreamState::Done) {
+        AgentStreamState::Idle | AgentStreamState::Done => {}
+        // The reply so far is dropped; it never reached history.
+        AgentStreamState::StreamingChunks(data) => this.abandon_turn(data.agent),
+        AgentStreamState::YieldingToolCalls { raw, turn_end, .. } => {
+            let agent = this
+                .agent
+                .as_mut()
+                .expect("agent missing in YieldingToolCalls");
+            queue_turn_end(&mut this.pending_events, turn_end);
+            for result in skip_tools(agent, raw, reason) {
+                this.pending_events
+                    .push_back(AgentEvent::ToolResult(result));
+            }
+        }
+        AgentStreamState::AwaitingApproval { calls, .. } => {
+            let agent = this
+                .agent
+                .as_mut()
+                .expect("agent missing in AwaitingApproval");
+            for result in skip_tools(agent, calls, reason) {
+                this.pending_events
+                    .push_back(AgentEvent::ToolResult(result));
+            }
+        }
+        state @ (AgentStreamState::Summarizing(_)
+        | AgentStreamState::Persisting(_)
+        | AgentStreamState::FetchingResponse(_)
+        | AgentStreamState::ConnectingStream(_)
+        | AgentStreamState::ExecutingTools(_)) => this.state = state,
+    }
+}
+
+/// Queue `MaxStepsReached` if the turn that just ended the run was the final,
+/// tool-free one.
+fn queue_run_end(pending: &mut VecDeque<AgentEvent>, agent: &DeepseekAgent) {
+    if let Some(max_steps) = agent.steps_exhausted() {
+        pending.push_back(AgentEvent::MaxStepsReached { max_steps });
+    }
+}
+
+/// Queue a checkpoint of `agent` with `pending` tool calls, if the agent
+/// takes checkpoints.
+fn queue_checkpoint(
+    pending_events: &mut VecDeque<AgentEvent>,
+    agent: &DeepseekAgent,
+    pending: &[ToolCall],
+    finished: bool,
+) {
+    if agent.checkpoints {
+        let checkpoint = Checkpoint::capture(agent, pending, finished);
+        pending_events.push_back(AgentEvent::Checkpoint(Box::new(checkpoint)));
+    }
+}
+
+/// Queue the `Usage` (when reported) and `TurnFinished` events of a turn.
+fn queue_turn_end(pending: &mut VecDeque<AgentEvent>, turn_end: TurnEnd) {
+    if let Some(usage) = turn_end.usage {
+        pending.push_back(AgentEvent::Usage(usage));
+    }
+    pending.push_back(AgentEvent::TurnFinished {
+        finish_reason: turn_end.finish_reason,
+    });
+}
@@ -0,0 +1,175 @@
+//! Typed final answers for [`DeepseekAgent`].
+//!
+//! [`chat_typed`][DeepseekAgent::chat_typed] runs the normal agent loop, tool
+//! calls included, and deserializes the final answer into a Rust type.  The
+//! schema handling and the re-ask loop mirror
+//! [`ApiClient::send_typed`][crate::api::ApiClient::send_typed]; see
+//! [`api::typed`][crate::api::typed].
+
+use futures::StreamExt;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use tracing::debug;
+
+use crate::agent::agent_core::{AgentEvent, DeepseekAgent};
+use crate::api::typed::{Schema, SchemaMode, StructuredOutput, correction, parse};
+use crate::error::{ApiError, Result};
+use crate::raw::request::message::{Message, Role};
+use crate::raw::{JsonSchemaFormat, ResponseFormat, ResponseFormatType};
+
+impl DeepseekAgent {
+    /// Push a user message, run the agent loop to completion and deserialize
+    /// the final answer into `T`, using the default [`StructuredOutput`]
+    /// settings.
+    ///
+    /// Tools run as usual; only the last assistant message has to match the
+    /// schema.  When it does not, the error is sent back as a user message
+    /// and the loop runs again, up to the configured number of attempts.
+    /// Events are not surfaced — use [`chat`][Self::chat] to observe them.
+    ///
+    /// There is no one to ask for approval, so on an agent with an
+    /// [`ApprovalPolicy`][crate::agent::ApprovalPolicy] every gated tool call
+    /// is rejected, and the model sees the rejection as the call's result.
+    ///
+    /// A run that stops on its [budget][Self::with_budget] or
+    /// [step limit][Self::with_max_steps] without a matching answer is not
+    /// retried, since every attempt would start a fresh run: the result is
+    /// [`ApiError::BudgetExceeded`] or [`ApiError::MaxStepsReached`].
+    ///
+    /// The agent is always handed back, together with the result.
+    ///
+    /// ```no_run
+    /// use ds_api::DeepseekAgent;
+    ///
+    /// #[derive(serde::Deserialize, schemars::JsonSchema)]
+    /// struct Verdict {
+    ///     approved: bool,
+    ///     reason: String,
+    /// }
+    ///
+    /// # #[tokio::main] async fn main() {
+    /// let agent = DeepseekAgent::new("sk-...");
+    /// let (verdict, agent) = agent.chat_typed::<Verdict>("Review this PR: ...").await;
+    /// # }
+    /// ```
+    pub async fn chat_typed<T>(self, user_message: &str) -> (Result<T>, DeepseekAgent)
+    where
+        T: DeserializeOwned + JsonSchema,
+    {
+        self.chat_typed_with(user_message, StructuredOutput::default())
+            .await
+    }
+
+    /// Like [`chat_typed`][Self::chat_typed], with explicit
+    /// [`StructuredOutput`] settings.
+    ///
+    /// In [`SchemaMode::Prompt`] the schema is described in a system message
+    /// and `json_object` output is requested; in
+    /// [`SchemaMode::ResponseFormat`] it is sent as the response format.
+    /// Either way this applies to every turn of this run only: the schema
+    /// never enters the history, and the agent's own response format is put
+    /// back afterwards.
+    pub async fn chat_typed_with<T>(
+        mut self,
+        user_message: &str,
+        config: StructuredOutput,
+    ) -> (Result<T>, DeepseekAgent)
+    where
+        T: DeserializeOwned + JsonSchema,
+    {
+        let schema = Schema::of::<T>();
+        let saved_format = self.options.response_format.clone();
+        match config.mode {
+            SchemaMode::Prompt => {
+                self.instruction = Some(schema.instruction());
+                self.options.response_format = Some(ResponseFormat {
+                    r#type: ResponseFormatType::JsonObject,
+                    json_schema: None,
+                });
+            }
+            SchemaMode::ResponseFormat => {
+                self.options.response_format = Some(ResponseFormat {
+                    r#type: ResponseFormatType::JsonSchema,
+                    json_schema: Some(JsonSchemaFormat {
+                        name: schema.name.clone(),
+                        description: None,
+                        schema: schema.value.clone(),
+                        strict: Some(true),
+                    }),
+                });
+            }
+        }
+        self.conversation.push_user_input(user_message);
+
+        let mut attempt = 0;
+        let result = loop {
+            attempt += 1;
+            let (outcome, agent) = run_to_end(self).await;
+            self = agent;
+            let stopped = match outcome {
+                Ok(stopped) => stopped,
+                Err(e) => break Err(e),
+            };
+            let content = final_answer(self.history());
+            let error = match parse(&content) {
+                Ok(value) => break Ok(value),
+                Err(e) => e,
+            };
+            // Asking again would start a new run with a fresh budget.
+            if let Some(stop) = stopped {
+                break Err(stop);
+            }
+            if attempt >= config.max_attempts {
+                break Err(ApiError::SchemaMismatch {
+                    attempts: attempt,
+                    message: error,
+                    content,
+                });
+            }
+            debug!(attempt, error = %error, "final answer did not match schema, asking again");
+            self.conversation.push_user_input(correction(&error));
+        };
+        self.options.response_format = saved_format;
+        self.instruction = None;
+        (result, self)
+    }
+}
+
+/// Why gated tool calls are rejected in a typed run.
+const NO_APPROVER: &str = "tool calls needing approval are not allowed in a typed run";
+
+/// Drive the agent loop from the current history until it finishes,
+/// rejecting every tool call that needs approval.
+///
+/// Returns the limit that ended the run, if any, as the error to report.
+async fn run_to_end(agent: DeepseekAgent) -> (Result<Option<ApiError>>, DeepseekAgent) {
+    let approvals = agent.approval_handle();
+    let mut stream = agent.chat_from_history();
+    let mut outcome = Ok(None);
+    while let Some(event) = stream.next().await {
+        match event {
+            Ok(AgentEvent::ApprovalRequired { call }) => approvals.reject(call.id, NO_APPROVER),
+            Ok(AgentEvent::BudgetExceeded(limit)) => {
+                outcome = Ok(Some(ApiError::BudgetExceeded(limit)));
+            }
+            Ok(AgentEvent::MaxStepsReached { max_steps }) => {
+                outcome = Ok(Some(ApiError::MaxStepsReached { max_steps }));
+            }
+            Ok(_) => {}
+            // An error ends the stream; keep it and let the loop finish.
+            Err(e) => outcome = Err(e),
+        }
+    }
+    let agent = stream
+        .into_agent()
+        .expect("the agent is handed back once the stream has ended");
+    (outcome, agent)
+}
+
+/// The text of the final assistant message, or an empty string.
+fn final_answer(history: &[Message]) -> String {
+    match history.last() {
+        Some(m) if matches!(m.role, Role::Assistant) => m.content.clone().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
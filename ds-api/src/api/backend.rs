@@ -0,0 +1,69 @@
+//! The `ChatBackend` trait — the seam between agents and the transport.
+//!
+//! [`Conversation`][crate::conversation::Conversation],
+//! [`LlmSummarizer`][crate::conversation::LlmSummarizer] and
+//! [`DeepseekAgent`][crate::agent::DeepseekAgent] talk to the model only
+//! through this trait, so any implementation can stand in for
+//! [`ApiClient`]: a different provider, a caching layer, or the scripted
+//! [`MockBackend`][crate::api::MockBackend] used in tests.
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+
+use super::client::ApiClient;
+use super::request::ApiRequest;
+use crate::error::{ApiError, Result};
+use crate::raw::{ChatCompletionChunk, ChatCompletionResponse};
+
+/// A `'static` stream of chat completion chunks, as returned by
+/// [`ChatBackend::send_stream`].
+pub type ChunkStream = BoxStream<'static, std::result::Result<ChatCompletionChunk, ApiError>>;
+
+/// Something that can answer chat completion requests.
+///
+/// Implementations must be cheap to share: the agent and its summarizer hold
+/// the same backend behind an `Arc`.
+///
+/// # Implementing a backend
+///
+/// ```no_run
+/// use async_trait::async_trait;
+/// use ds_api::api::{ChatBackend, ChunkStream};
+/// use ds_api::error::Result;
+/// use ds_api::raw::ChatCompletionResponse;
+/// use ds_api::{ApiClient, ApiRequest};
+///
+/// /// Logs every request before forwarding it to DeepSeek.
+/// struct Logged(ApiClient);
+///
+/// #[async_trait]
+/// impl ChatBackend for Logged {
+///     async fn send(&self, req: ApiRequest) -> Result<ChatCompletionResponse> {
+///         println!("-> {} messages", req.as_raw().messages.len());
+///         self.0.send(req).await
+///     }
+///
+///     async fn send_stream(&self, req: ApiRequest) -> Result<ChunkStream> {
+///         self.0.clone().into_stream(req).await
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait ChatBackend: Send + Sync {
+    /// Send a non-streaming request and return the full response.
+    async fn send(&self, req: ApiRequest) -> Result<ChatCompletionResponse>;
+
+    /// Send a streaming request and return a `'static` stream of chunks.
+    async fn send_stream(&self, req: ApiRequest) -> Result<ChunkStream>;
+}
+
+#[async_trait]
+impl ChatBackend for ApiClient {
+    async fn send(&self, req: ApiRequest) -> Result<ChatCompletionResponse> {
+        ApiClient::send(self, req).await
+    }
+
+    async fn send_stream(&self, req: ApiRequest) -> Result<ChunkStream> {
+        self.clone().into_stream(req).await
+    }
+}
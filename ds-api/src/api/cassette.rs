@@ -0,0 +1,419 @@
+//! Record/replay cassettes for offline regression tests.
+//!
+//! A [`Cassette`] sits underneath every HTTP call made by an
+//! [`ApiClient`][crate::api::ApiClient] attached with
+//! [`with_cassette`][crate::api::ApiClient::with_cassette]:
+//!
+//! - In **record** mode each request goes to the real server; the request
+//!   body and the full response (status plus JSON body, or the sequence of
+//!   SSE `data:` payloads) are appended to a JSONL file once the response has
+//!   been consumed.
+//! - In **replay** mode no network traffic happens.  Each request is matched
+//!   against the recorded interactions by method, path, `model` and
+//!   `messages`, and the recorded response is played back as if it came from
+//!   the server.  A request without a match fails with
+//!   [`ApiError::CassetteMismatch`] showing a diff against the closest
+//!   recorded request.
+//!
+//! Because the cassette works at the HTTP layer, everything built on the
+//! client — `send`, `send_stream`, `stream_text`, summarizers and
+//! [`AgentStream`][crate::agent::AgentStream] — behaves the same in both
+//! modes.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use futures::StreamExt;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Body, Request, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use crate::error::{ApiError, Result};
+
+/// Whether a [`Cassette`] talks to the network or plays back a recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Forward requests to the server and append every interaction to the file.
+    Record,
+    /// Serve responses from the file; never touch the network.
+    Replay,
+}
+
+/// One recorded request/response pair — a single line of the cassette file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    method: String,
+    path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request: Option<Value>,
+    status: u16,
+    /// Non-streaming response body (a JSON value, or a string for non-JSON
+    /// bodies such as plain-text errors).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<Value>,
+    /// SSE `data:` payloads, in order, including the final `[DONE]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    events: Option<Vec<String>>,
+}
+
+impl Interaction {
+    /// The part of the interaction used for matching.
+    fn key(&self) -> Value {
+        match_key(&self.method, &self.path, self.request.as_ref())
+    }
+
+    /// Rebuild the recorded response.
+    fn to_response(&self) -> Response {
+        let (content_type, body) = match (&self.events, &self.body) {
+            (Some(events), _) => (
+                "text/event-stream",
+                events
+                    .iter()
+                    .map(|e| format!("data: {e}\n\n"))
+                    .collect::<String>(),
+            ),
+            (None, Some(Value::String(text))) => ("text/plain", text.clone()),
+            (None, Some(json)) => ("application/json", json.to_string()),
+            (None, None) => ("text/plain", String::new()),
+        };
+        let resp = http::Response::builder()
+            .status(self.status)
+            .header(CONTENT_TYPE, content_type)
+            .body(body)
+            .expect("recorded status code is valid");
+        Response::from(resp)
+    }
+}
+
+fn match_key(method: &str, path: &str, body: Option<&Value>) -> Value {
+    let field = |name| body.and_then(|b| b.get(name)).cloned();
+    serde_json::json!({
+        "method": method,
+        "path": path,
+        "model": field("model"),
+        "messages": field("messages"),
+    })
+}
+
+#[derive(Debug)]
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    used: Vec<bool>,
+    /// Open handle in record mode.
+    file: Option<File>,
+}
+
+/// A record/replay cassette backed by a JSONL file.
+///
+/// # Example
+///
+/// ```no_run
+/// use ds_api::{ApiClient, Cassette};
+///
+/// # fn main() -> Result<(), ds_api::ApiError> {
+/// // Run once against the real API to capture the traffic …
+/// let client = ApiClient::new("sk-...")
+///     .with_cassette(Cassette::record("tests/cassettes/weather.jsonl")?);
+///
+/// // … then replay it offline in CI.
+/// let client = ApiClient::new("unused")
+///     .with_cassette(Cassette::replay("tests/cassettes/weather.jsonl")?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    state: Mutex<CassetteState>,
+}
+
+impl Cassette {
+    /// Start recording into `path`, truncating any existing file.
+    pub fn record(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = File::create(&path)?;
+        Ok(Self {
+            path,
+            mode: CassetteMode::Record,
+            state: Mutex::new(CassetteState {
+                interactions: vec![],
+                used: vec![],
+                file: Some(file),
+            }),
+        })
+    }
+
+    /// Load the interactions recorded in `path` for replay.
+    pub fn replay(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut interactions = Vec::new();
+        for line in BufReader::new(File::open(&path)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            interactions.push(serde_json::from_str::<Interaction>(&line)?);
+        }
+        Ok(Self {
+            path,
+            mode: CassetteMode::Replay,
+            state: Mutex::new(CassetteState {
+                used: vec![false; interactions.len()],
+                interactions,
+                file: None,
+            }),
+        })
+    }
+
+    /// The mode this cassette was opened in.
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// The cassette file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of recorded interactions (so far, in record mode).
+    pub fn len(&self) -> usize {
+        self.lock().interactions.len()
+    }
+
+    /// Returns `true` if no interactions have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// In replay mode, the number of interactions not played back yet.
+    pub fn unplayed(&self) -> usize {
+        self.lock().used.iter().filter(|u| !**u).count()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CassetteState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Serve `req` from the recording.
+    ///
+    /// The first unplayed interaction with a matching key wins; if every
+    /// match has been played already the most recent one is served again.
+    pub(crate) fn replay_request(&self, req: &Request) -> Result<Response> {
+        let key = request_key(req);
+        let mut state = self.lock();
+
+        let matches: Vec<usize> = (0..state.interactions.len())
+            .filter(|&i| state.interactions[i].key() == key)
+            .collect();
+        let hit = matches
+            .iter()
+            .copied()
+            .find(|&i| !state.used[i])
+            .or_else(|| matches.last().copied());
+
+        match hit {
+            Some(i) => {
+                state.used[i] = true;
+                debug!(index = i, path = %req.url().path(), "replaying cassette interaction");
+                Ok(state.interactions[i].to_response())
+            }
+            None => {
+                let closest = state
+                    .interactions
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, it)| {
+                        !state.used[*i]
+                            && it.method == req.method().as_str()
+                            && it.path == req.url().path()
+                    })
+                    .map(|(_, it)| it)
+                    .next()
+                    .or_else(|| state.interactions.first());
+                let diff = match closest {
+                    Some(it) => diff_json(&it.key(), &key),
+                    None => format!("cassette {} is empty", self.path.display()),
+                };
+                Err(ApiError::CassetteMismatch(diff))
+            }
+        }
+    }
+
+    /// Wrap a live response so its body is appended to the cassette once it
+    /// has been consumed (or dropped).
+    pub(crate) fn record_response(self: &Arc<Self>, req: &Request, resp: Response) -> Response {
+        let status = resp.status();
+        let sse = resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("text/event-stream"));
+        let mut recorder = Recorder {
+            cassette: self.clone(),
+            interaction: Some(Interaction {
+                method: req.method().to_string(),
+                path: req.url().path().to_string(),
+                request: request_body(req),
+                status: status.as_u16(),
+                body: None,
+                events: None,
+            }),
+            sse,
+            buf: Vec::new(),
+        };
+
+        let mut builder = http::Response::builder().status(status);
+        for (name, value) in resp.headers() {
+            builder = builder.header(name, value);
+        }
+        let tee = resp.bytes_stream().map(move |chunk| {
+            if let Ok(bytes) = &chunk {
+                recorder.buf.extend_from_slice(bytes);
+            }
+            chunk
+        });
+        let rebuilt = builder
+            .body(Body::wrap_stream(tee))
+            .expect("status and headers come from a valid response");
+        Response::from(rebuilt)
+    }
+
+    fn append(&self, interaction: Interaction) {
+        let mut state = self.lock();
+        if let Some(file) = state.file.as_mut() {
+            let written = serde_json::to_string(&interaction)
+                .map_err(std::io::Error::from)
+                .and_then(|line| writeln!(file, "{line}"));
+            if let Err(e) = written {
+                warn!(error = %e, path = %self.path.display(), "failed to write cassette");
+            }
+        }
+        state.interactions.push(interaction);
+        state.used.push(true);
+    }
+}
+
+/// Accumulates a response body while it streams through and writes the
+/// interaction to the cassette when dropped.
+struct Recorder {
+    cassette: Arc<Cassette>,
+    interaction: Option<Interaction>,
+    sse: bool,
+    buf: Vec<u8>,
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        let Some(mut interaction) = self.interaction.take() else {
+            return;
+        };
+        let text = String::from_utf8_lossy(&self.buf);
+        if self.sse {
+            interaction.events = Some(sse_data(&text));
+        } else {
+            interaction.body = Some(
+                serde_json::from_str(&text).unwrap_or_else(|_| Value::String(text.into_owned())),
+            );
+        }
+        self.cassette.append(interaction);
+    }
+}
+
+fn request_body(req: &Request) -> Option<Value> {
+    req.body()
+        .and_then(|b| b.as_bytes())
+        .and_then(|bytes| serde_json::from_slice(bytes).ok())
+}
+
+fn request_key(req: &Request) -> Value {
+    match_key(
+        req.method().as_str(),
+        req.url().path(),
+        request_body(req).as_ref(),
+    )
+}
+
+/// Extract the `data:` payload of every event in an SSE body.
+fn sse_data(text: &str) -> Vec<String> {
+    text.replace("\r\n", "\n")
+        .split("\n\n")
+        .filter_map(|block| {
+            let data: Vec<&str> = block
+                .lines()
+                .filter_map(|l| l.strip_prefix("data:"))
+                .map(|d| d.strip_prefix(' ').unwrap_or(d))
+                .collect();
+            (!data.is_empty()).then(|| data.join("\n"))
+        })
+        .collect()
+}
+
+/// A line diff of two pretty-printed JSON values (`-` recorded, `+` actual).
+fn diff_json(recorded: &Value, actual: &Value) -> String {
+    let pretty = |v: &Value| serde_json::to_string_pretty(v).unwrap_or_default();
+    let (a, b) = (pretty(recorded), pretty(actual));
+    let a: Vec<&str> = a.lines().collect();
+    let b: Vec<&str> = b.lines().collect();
+
+    // Longest common subsequence table.
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::from("--- recorded\n+++ actual\n");
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            out.push_str(&format!("  {}\n", a[i]));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push_str(&format!("- {}\n", a[i]));
+            i += 1;
+        } else {
+            out.push_str(&format!("+ {}\n", b[j]));
+            j += 1;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn sse_data_extracts_payloads() {
+        let body = "data: {\"a\":1}\n\n: keep-alive\n\ndata: [DONE]\n\n";
+        assert_eq!(sse_data(body), ["{\"a\":1}", "[DONE]"]);
+    }
+
+    #[test]
+    fn diff_marks_changed_lines() {
+        let diff = diff_json(
+            &json!({ "model": "deepseek-chat", "messages": ["hi"] }),
+            &json!({ "model": "deepseek-chat", "messages": ["hello"] }),
+        );
+        assert!(diff.contains("-     \"hi\"\n+     \"hello\""), "{diff}");
+        assert!(
+            diff.contains("\n    \"model\": \"deepseek-chat\""),
+            "{diff}"
+        );
+    }
+}
@@ -1,22 +1,89 @@
+use std::sync::Arc;
 use std::time::Duration;
 
 use eventsource_stream::Eventsource;
 use futures::{StreamExt, stream::BoxStream};
-use reqwest::{Client, Response};
+use reqwest::{Client, RequestBuilder, Response};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::time::Instant;
 
 use tracing::{debug, info, instrument, warn};
 
+use super::cassette::{Cassette, CassetteMode};
+use super::cost::CostTracker;
+use super::fim::FimRequest;
+use super::key_pool::{KeyPool, KeyStats};
+use super::rate_limit::{
+    Permit, RateLimit, RateLimitStats, RateLimiter, estimate_completion_tokens,
+    estimate_request_tokens,
+};
 use super::request::ApiRequest;
+use super::retry::{RetryEvent, RetryPolicy, notify_retry, parse_retry_after};
+use super::stream_timeout::{StreamTimeouts, with_timeouts};
+use super::typed::{self, StructuredOutput};
 use crate::error::{ApiError, Result};
-use crate::raw::{ChatCompletionChunk, ChatCompletionResponse};
+use crate::raw::{
+    Balance, ChatCompletionChunk, ChatCompletionResponse, CompletionChunk, CompletionResponse,
+    Model, ModelList, StreamOptions, Usage,
+};
+
+/// A successful response plus what must be held or settled while it is
+/// consumed.
+struct Sent {
+    resp: Response,
+    /// Rate-limiter slot; released when dropped.
+    permit: Permit,
+    /// Estimated token cost, reconciled against the real usage.
+    estimated: u32,
+    /// Index of the pooled key the request was sent with.
+    key: Option<usize>,
+    /// When the successful attempt was sent.
+    started: Instant,
+}
+
+/// Streamed chunk types that may carry the request's final usage.
+trait ChunkUsage {
+    fn usage(&self) -> Option<&Usage>;
+    fn model(&self) -> &str;
+}
+
+impl ChunkUsage for ChatCompletionChunk {
+    fn usage(&self) -> Option<&Usage> {
+        self.usage.as_ref()
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+}
+
+impl ChunkUsage for CompletionChunk {
+    fn usage(&self) -> Option<&Usage> {
+        self.usage.as_ref()
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+}
 
 /// Lightweight API HTTP client.
 #[derive(Clone, Debug)]
 pub struct ApiClient {
     token: String,
     base_url: String,
+    beta_base_url: Option<String>,
     client: Client,
     timeout: Option<Duration>,
+    retry: Option<RetryPolicy>,
+    /// Shared by every clone of this client.
+    rate_limiter: Option<Arc<RateLimiter>>,
+    cassette: Option<Arc<Cassette>>,
+    key_pool: Option<Arc<KeyPool>>,
+    stream_timeouts: Option<StreamTimeouts>,
+    cost_tracker: Option<CostTracker>,
 }
 
 impl ApiClient {
@@ -33,8 +100,15 @@ impl ApiClient {
         let client = Self {
             token: token_str,
             base_url: "https://api.deepseek.com".to_string(),
+            beta_base_url: None,
             client,
             timeout: None,
+            retry: None,
+            rate_limiter: None,
+            cassette: None,
+            key_pool: None,
+            stream_timeouts: None,
+            cost_tracker: None,
         };
         tracing::Span::current().record("masked_token", "***");
         client
@@ -46,76 +120,388 @@ impl ApiClient {
         self
     }
 
+    /// Replace the base URL used for beta endpoints such as FIM completion
+    /// and chat prefix completion
+    /// (builder style).
+    ///
+    /// Defaults to `{base_url}/beta`.
+    pub fn with_beta_base_url(mut self, base: impl Into<String>) -> Self {
+        self.beta_base_url = Some(base.into());
+        self
+    }
+
     /// Replace token (builder style).
     pub fn with_token(mut self, token: impl Into<String>) -> Self {
         self.token = token.into();
         self
     }
 
+    /// Rotate requests across a pool of API keys (builder style).
+    ///
+    /// The pool replaces the client's token and is shared by every clone of
+    /// the client.  Keys rejected as unauthorized (401, 403) or out of
+    /// balance (402, `insufficient_quota`) are evicted and the request is
+    /// re-sent with another key; this does not count as a retry.
+    /// See [`KeyPool`] for the selection strategies.
+    pub fn with_key_pool(mut self, pool: KeyPool) -> Self {
+        self.key_pool = Some(Arc::new(pool));
+        self
+    }
+
+    /// Per-key usage counters (with masked keys), or `None` if no key pool is
+    /// configured.
+    pub fn key_stats(&self) -> Option<Vec<KeyStats>> {
+        self.key_pool.as_ref().map(|p| p.stats())
+    }
+
     /// Set optional timeout for non-streaming requests.
+    ///
+    /// Streams are bounded separately with
+    /// [`with_stream_timeouts`][Self::with_stream_timeouts].
     pub fn with_timeout(mut self, t: Duration) -> Self {
         self.timeout = Some(t);
         self
     }
 
+    /// Set first-byte, idle and total timeouts for streaming requests
+    /// (builder style).
+    ///
+    /// A stream that exceeds one of them yields
+    /// [`ApiError::StreamFirstByteTimeout`], [`ApiError::StreamIdleTimeout`]
+    /// or [`ApiError::StreamDeadlineExceeded`] and ends.
+    pub fn with_stream_timeouts(mut self, timeouts: StreamTimeouts) -> Self {
+        self.stream_timeouts = Some(timeouts);
+        self
+    }
+
+    /// Retry transient failures according to `policy` (builder style).
+    ///
+    /// By default the client makes a single attempt.  With a policy set,
+    /// non-streaming requests and the connect phase of streaming requests are
+    /// retried on the failures the policy classifies as transient.  See
+    /// [`RetryPolicy`] for details.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Enforce client-side quotas before each request (builder style).
+    ///
+    /// The limiter is created here and shared by every clone of the client
+    /// made afterwards, so agents, summarizers and worker tasks holding clones
+    /// all draw from one budget.  Each retry attempt counts as a new request.
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(&limit)));
+        self
+    }
+
+    /// Record or replay all HTTP traffic through `cassette` (builder style).
+    ///
+    /// See [`Cassette`] for the file format and matching rules.  The
+    /// cassette is shared by every clone of the client.
+    pub fn with_cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(Arc::new(cassette));
+        self
+    }
+
+    /// The cassette attached with [`with_cassette`][Self::with_cassette], if any.
+    pub fn cassette(&self) -> Option<&Cassette> {
+        self.cassette.as_deref()
+    }
+
+    /// Price every chat and FIM call with `tracker` (builder style).
+    ///
+    /// The tracker is shared by every clone of the client, so the
+    /// summarizer's calls are included when it shares the client.  Streamed
+    /// calls are counted only when they report usage (see
+    /// [`ApiRequest::include_usage`]).
+    pub fn with_cost_tracker(mut self, tracker: CostTracker) -> Self {
+        self.cost_tracker = Some(tracker);
+        self
+    }
+
+    /// The tracker attached with [`with_cost_tracker`][Self::with_cost_tracker], if any.
+    pub fn cost_tracker(&self) -> Option<&CostTracker> {
+        self.cost_tracker.as_ref()
+    }
+
+    /// Snapshot of the shared rate limiter (queue depth, in-flight requests,
+    /// remaining budget), or `None` if no rate limit is configured.
+    pub fn rate_limit_stats(&self) -> Option<RateLimitStats> {
+        self.rate_limiter.as_ref().map(|l| l.stats())
+    }
+
     // ── Private helpers ───────────────────────────────────────────────────────
 
     fn completions_url(&self) -> String {
         format!("{}/chat/completions", self.base_url.trim_end_matches('/'))
     }
 
-    /// Send an HTTP POST to the completions endpoint and return the raw
-    /// [`Response`], checking for non-2xx status codes.
-    async fn post_raw(&self, req: ApiRequest, stream: bool) -> Result<Response> {
+    fn beta_url(&self, path: &str) -> String {
+        match &self.beta_base_url {
+            Some(beta) => format!("{}/{}", beta.trim_end_matches('/'), path),
+            None => format!("{}/beta/{}", self.base_url.trim_end_matches('/'), path),
+        }
+    }
+
+    /// Validate and send a chat completion request, returning the raw
+    /// [`Response`] together with its rate-limiter [`Permit`] and estimated
+    /// token cost.
+    async fn post_chat(&self, req: ApiRequest, stream: bool) -> Result<Sent> {
+        let req = if stream { req.stream(true) } else { req };
+        req.validate()?;
+        // Prefix completion is a beta feature.
+        let url = if req.has_assistant_prefix() {
+            self.beta_url("chat/completions")
+        } else {
+            self.completions_url()
+        };
+        let raw = req.into_raw();
+        let estimated = estimate_request_tokens(&raw);
+        self.post_raw(&url, &raw, stream, estimated).await
+    }
+
+    /// Send a FIM completion request to the beta endpoint; see
+    /// [`post_chat`][Self::post_chat].
+    async fn post_fim(&self, req: FimRequest, stream: bool) -> Result<Sent> {
+        req.validate()?;
         let mut raw = req.into_raw();
         if stream {
             raw.stream = Some(true);
+            raw.stream_options = Some(StreamOptions {
+                include_usage: true,
+            });
         }
+        let estimated = estimate_completion_tokens(&raw);
+        self.post_raw(&self.beta_url("completions"), &raw, stream, estimated)
+            .await
+    }
 
-        let url = self.completions_url();
+    /// Send an HTTP POST with a JSON `body` to `url`; see
+    /// [`execute`][Self::execute].
+    async fn post_raw<B: Serialize>(
+        &self,
+        url: &str,
+        body: &B,
+        stream: bool,
+        estimated: u32,
+    ) -> Result<Sent> {
         debug!(method = "POST", %url, %stream, "sending request");
+        self.execute(|| self.client.post(url).json(body), stream, estimated)
+            .await
+    }
 
-        let mut builder = self.client.post(&url).bearer_auth(&self.token).json(&raw);
+    /// Send an HTTP GET to `{base_url}/{path}` and parse the JSON response.
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let url = format!("{}/{}", self.base_url.trim_end_matches('/'), path);
+        debug!(method = "GET", %url, "sending request");
+        let Sent {
+            resp,
+            permit: _permit,
+            ..
+        } = self.execute(|| self.client.get(&url), false, 0).await?;
+        resp.json::<T>().await.map_err(|e| {
+            warn!(error = %e, "failed to parse response body");
+            ApiError::Reqwest(e)
+        })
+    }
+
+    /// Send the request built by `make` and return the raw [`Response`],
+    /// checking for non-2xx status codes.
+    ///
+    /// `make` is called once per attempt.  Transient failures are retried
+    /// according to the configured [`RetryPolicy`], if any.  Each attempt
+    /// first waits for the rate limiter to admit `estimated` tokens; the
+    /// returned [`Permit`] must be kept alive for as long as the response is
+    /// being consumed.
+    ///
+    /// With a [`KeyPool`], each attempt checks out a key; a key evicted by an
+    /// authentication or insufficient-balance error is replaced immediately
+    /// without consuming a retry.
+    async fn execute(
+        &self,
+        make: impl Fn() -> RequestBuilder,
+        stream: bool,
+        estimated: u32,
+    ) -> Result<Sent> {
+        let max_attempts = self.retry.as_ref().map_or(1, |p| p.max_attempts);
+        let mut attempt = 1;
+
+        loop {
+            let permit = match &self.rate_limiter {
+                Some(limiter) => limiter.acquire(estimated).await,
+                None => Permit::unlimited(),
+            };
+            let key = match &self.key_pool {
+                Some(pool) => Some(pool.checkout()?),
+                None => None,
+            };
+            let token = key
+                .as_ref()
+                .map_or(self.token.as_str(), |(_, k)| k.as_str());
+            let started = Instant::now();
+            let (err, retry_after) = match self.send_once(make(), token, stream).await {
+                Ok(resp) => {
+                    return Ok(Sent {
+                        resp,
+                        permit,
+                        estimated,
+                        key: key.map(|(index, _)| index),
+                        started,
+                    });
+                }
+                Err(failure) => failure,
+            };
+            drop(permit);
+
+            if let (Some(pool), Some((index, _))) = (&self.key_pool, &key)
+                && pool.evict_on(*index, &err)
+                && pool.usable() > 0
+            {
+                continue;
+            }
+
+            let delay = self
+                .retry
+                .as_ref()
+                .and_then(|p| p.next_delay(attempt, &err, retry_after));
+            let Some(delay) = delay else {
+                return Err(err);
+            };
+
+            let event = RetryEvent {
+                attempt,
+                max_attempts,
+                delay,
+                status: err.status(),
+                error: err.to_string(),
+            };
+            warn!(
+                attempt,
+                max_attempts,
+                delay_ms = delay.as_millis() as u64,
+                error = %err,
+                "retrying request"
+            );
+            notify_retry(&event);
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Perform a single attempt.
+    ///
+    /// On failure the error is returned together with the server's
+    /// `Retry-After` hint, if one was sent.
+    async fn send_once(
+        &self,
+        builder: RequestBuilder,
+        token: &str,
+        stream: bool,
+    ) -> std::result::Result<Response, (ApiError, Option<Duration>)> {
+        let mut builder = builder.bearer_auth(token);
         if !stream && let Some(t) = self.timeout {
             builder = builder.timeout(t);
             debug!(timeout_ms = ?t.as_millis(), "request timeout set");
         }
+        let (client, request) = builder.build_split();
+        let request = request.map_err(|e| (ApiError::Reqwest(e), None))?;
 
-        let resp = builder.send().await.map_err(|e| {
-            warn!(error = %e, "http send failed");
-            ApiError::Reqwest(e)
-        })?;
+        let resp = match &self.cassette {
+            Some(c) if c.mode() == CassetteMode::Replay => {
+                c.replay_request(&request).map_err(|e| (e, None))?
+            }
+            cassette => {
+                let recording = cassette.as_ref().map(|c| (c.clone(), request.try_clone()));
+                let connect_limit = self.stream_timeouts.and_then(|t| t.connect_limit());
+                let resp = match connect_limit {
+                    Some(limit) if stream => {
+                        tokio::time::timeout(limit.duration(), client.execute(request))
+                            .await
+                            .map_err(|_| {
+                                let err = limit.error();
+                                warn!(error = %err, "stream connect timed out");
+                                (err, None)
+                            })?
+                    }
+                    _ => client.execute(request).await,
+                }
+                .map_err(|e| {
+                    warn!(error = %e, "http send failed");
+                    (ApiError::Reqwest(e), None)
+                })?;
+                match recording {
+                    Some((c, Some(req))) => c.record_response(&req, resp),
+                    _ => resp,
+                }
+            }
+        };
 
         if !resp.status().is_success() {
             let status = resp.status();
+            let retry_after = parse_retry_after(resp.headers());
             let text = resp.text().await.unwrap_or_else(|e| e.to_string());
             warn!(%status, "non-success response");
-            return Err(ApiError::http_error(status, text));
+            return Err((ApiError::http_error(status, text), retry_after));
         }
 
         Ok(resp)
     }
 
     /// Convert a successful streaming [`Response`] into a
-    /// `BoxStream<Result<ChatCompletionChunk, ApiError>>`.
+    /// `BoxStream<Result<T, ApiError>>`, where `T` is the chunk type of the
+    /// endpoint ([`ChatCompletionChunk`] or [`CompletionChunk`]).
     ///
     /// This is the single source of truth for SSE → chunk parsing.
-    fn response_into_chunk_stream(
-        resp: Response,
-    ) -> BoxStream<'static, std::result::Result<ChatCompletionChunk, ApiError>> {
+    ///
+    /// The rate-limiter permit is moved into the stream so the slot is
+    /// released only when the stream is dropped.  Usage reported by a chunk
+    /// reconciles the rate limiter's token estimate and is added to the
+    /// pooled key's counters and the cost tracker, as [`settle`][Self::settle]
+    /// does for a non-streaming reply.  The configured [`StreamTimeouts`] are
+    /// applied.
+    fn response_into_chunk_stream<T>(
+        &self,
+        sent: Sent,
+    ) -> BoxStream<'static, std::result::Result<T, ApiError>>
+    where
+        T: DeserializeOwned + ChunkUsage + Send + 'static,
+    {
+        let Sent {
+            resp,
+            permit,
+            estimated,
+            key,
+            started,
+        } = sent;
+        let limiter = self.rate_limiter.clone();
+        let pool = key.and_then(|index| Some((self.key_pool.clone()?, index)));
+        let tracker = self.cost_tracker.clone();
         let event_stream = resp.bytes_stream().eventsource();
 
-        event_stream
-            .filter_map(|ev_res| async move {
-                match ev_res {
+        let chunks = event_stream
+            .filter_map(move |ev_res| {
+                let _held = &permit;
+                let mut actual = None;
+                let item = match ev_res {
                     Ok(ev) => {
                         if ev.data == "[DONE]" {
                             debug!("received [DONE] event");
                             None
                         } else {
-                            match serde_json::from_str::<ChatCompletionChunk>(&ev.data) {
+                            match serde_json::from_str::<T>(&ev.data) {
                                 Ok(chunk) => {
                                     debug!("parsed chunk");
+                                    if let Some(usage) = chunk.usage() {
+                                        actual = Some(usage.total_tokens);
+                                        if let Some((pool, index)) = &pool {
+                                            pool.record_usage(*index, usage);
+                                        }
+                                        if let Some(tracker) = &tracker {
+                                            tracker.record(chunk.model(), usage);
+                                        }
+                                    }
                                     Some(Ok(chunk))
                                 }
                                 Err(e) => {
@@ -129,9 +515,35 @@ impl ApiClient {
                         warn!(error = %e, "eventsource error");
                         Some(Err(ApiError::EventSource(e.to_string())))
                     }
+                };
+                let limiter = limiter.clone();
+                async move {
+                    if let (Some(limiter), Some(actual)) = (limiter, actual) {
+                        limiter.reconcile(estimated, actual).await;
+                    }
+                    item
                 }
             })
-            .boxed()
+            .boxed();
+
+        match self.stream_timeouts {
+            Some(timeouts) => with_timeouts(chunks, timeouts, started),
+            None => chunks,
+        }
+    }
+
+    /// Reconcile the rate limiter, the pooled key's counters and the cost
+    /// tracker with the real `usage` of a completed non-streaming request.
+    async fn settle(&self, estimated: u32, key: Option<usize>, model: &str, usage: &Usage) {
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.reconcile(estimated, usage.total_tokens).await;
+        }
+        if let (Some(pool), Some(index)) = (&self.key_pool, key) {
+            pool.record_usage(index, usage);
+        }
+        if let Some(tracker) = &self.cost_tracker {
+            tracker.record(model, usage);
+        }
     }
 
     // ── Public API ────────────────────────────────────────────────────────────
@@ -139,7 +551,13 @@ impl ApiClient {
     /// Send a non-streaming request and parse the full [`ChatCompletionResponse`].
     #[instrument(level = "info", skip(self, req))]
     pub async fn send(&self, req: ApiRequest) -> Result<ChatCompletionResponse> {
-        let resp = self.post_raw(req, false).await?;
+        let Sent {
+            resp,
+            permit: _permit,
+            estimated,
+            key,
+            ..
+        } = self.post_chat(req, false).await?;
         debug!("received HTTP response; deserialising");
 
         let parsed = resp.json::<ChatCompletionResponse>().await.map_err(|e| {
@@ -147,10 +565,36 @@ impl ApiClient {
             ApiError::Reqwest(e)
         })?;
 
+        self.settle(estimated, key, parsed.model.as_str(), &parsed.usage)
+            .await;
+
         info!("request completed successfully");
         Ok(parsed)
     }
 
+    /// Send a request and deserialize the reply into `T`, using the default
+    /// [`StructuredOutput`] settings (schema in the prompt, three attempts).
+    ///
+    /// A JSON Schema generated from `T` tells the model what to produce.  A
+    /// reply that does not deserialize is sent back with the error and the
+    /// model is asked again; if every attempt fails the result is
+    /// [`ApiError::SchemaMismatch`].
+    pub async fn send_typed<T>(&self, req: ApiRequest) -> Result<T>
+    where
+        T: DeserializeOwned + JsonSchema,
+    {
+        self.send_typed_with(req, StructuredOutput::default()).await
+    }
+
+    /// Like [`send_typed`][Self::send_typed], with explicit
+    /// [`StructuredOutput`] settings.
+    pub async fn send_typed_with<T>(&self, req: ApiRequest, config: StructuredOutput) -> Result<T>
+    where
+        T: DeserializeOwned + JsonSchema,
+    {
+        typed::send_typed(self, req, config).await
+    }
+
     /// Send a streaming (SSE) request and return a `BoxStream` of parsed
     /// [`ChatCompletionChunk`]s.
     ///
@@ -163,9 +607,9 @@ impl ApiClient {
         &self,
         req: ApiRequest,
     ) -> Result<BoxStream<'_, std::result::Result<ChatCompletionChunk, ApiError>>> {
-        let resp = self.post_raw(req, true).await?;
+        let sent = self.post_chat(req, true).await?;
         info!("stream connected");
-        Ok(Self::response_into_chunk_stream(resp))
+        Ok(self.response_into_chunk_stream(sent))
     }
 
     /// Send a streaming (SSE) request, consuming `self`, and return a
@@ -179,9 +623,80 @@ impl ApiClient {
         self,
         req: ApiRequest,
     ) -> Result<BoxStream<'static, std::result::Result<ChatCompletionChunk, ApiError>>> {
-        let resp = self.post_raw(req, true).await?;
+        let sent = self.post_chat(req, true).await?;
         info!("stream connected (owned)");
-        Ok(Self::response_into_chunk_stream(resp))
+        Ok(self.response_into_chunk_stream(sent))
+    }
+
+    /// Send a non-streaming FIM (fill-in-the-middle) request to the beta
+    /// `/completions` endpoint and parse the full [`CompletionResponse`].
+    #[instrument(level = "info", skip(self, req))]
+    pub async fn complete(&self, req: FimRequest) -> Result<CompletionResponse> {
+        let Sent {
+            resp,
+            permit: _permit,
+            estimated,
+            key,
+            ..
+        } = self.post_fim(req, false).await?;
+        debug!("received HTTP response; deserialising");
+
+        let parsed = resp.json::<CompletionResponse>().await.map_err(|e| {
+            warn!(error = %e, "failed to parse CompletionResponse");
+            ApiError::Reqwest(e)
+        })?;
+
+        self.settle(estimated, key, parsed.model.as_str(), &parsed.usage)
+            .await;
+
+        info!("completion finished successfully");
+        Ok(parsed)
+    }
+
+    /// Send a streaming FIM request and return a `BoxStream` of parsed
+    /// [`CompletionChunk`]s.
+    ///
+    /// The final chunk carries the request's `usage`.
+    #[instrument(level = "info", skip(self, req))]
+    pub async fn complete_stream(
+        &self,
+        req: FimRequest,
+    ) -> Result<BoxStream<'_, std::result::Result<CompletionChunk, ApiError>>> {
+        let sent = self.post_fim(req, true).await?;
+        info!("completion stream connected");
+        Ok(self.response_into_chunk_stream(sent))
+    }
+
+    /// List the models available to this API key (`GET /models`).
+    ///
+    /// Useful as a startup health check: it fails with an HTTP 401 error if
+    /// the token is invalid.
+    #[instrument(level = "info", skip(self))]
+    pub async fn list_models(&self) -> Result<ModelList> {
+        self.get_json("models").await
+    }
+
+    /// Fetch the account balance (`GET /user/balance`).
+    #[instrument(level = "info", skip(self))]
+    pub async fn balance(&self) -> Result<Balance> {
+        self.get_json("user/balance").await
+    }
+
+    /// Check that `model` is served by this endpoint.
+    ///
+    /// Returns [`ApiError::UnknownModel`] listing the available models if it
+    /// is not.
+    #[instrument(level = "info", skip(self))]
+    pub async fn validate_model(&self, model: &Model) -> Result<()> {
+        let list = self.list_models().await?;
+        if list.contains(model) {
+            Ok(())
+        } else {
+            Err(ApiError::UnknownModel {
+                model: model.as_str().to_string(),
+                available: list.ids().map(str::to_string).collect(),
+            })
+        }
     }
 
     /// Convenience: stream only text fragments (`delta.content`) as [`String`]
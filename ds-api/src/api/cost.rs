@@ -0,0 +1,293 @@
+//! Cost accounting.
+//!
+//! A [`CostTracker`] prices every call it sees with a [`PriceTable`] and keeps
+//! running totals per model.  Attach one wherever you want costs aggregated:
+//!
+//! - [`ApiClient::with_cost_tracker`][crate::api::ApiClient::with_cost_tracker]
+//!   counts every chat and FIM call made through the client and its clones.
+//! - [`Conversation::with_cost_tracker`][crate::conversation::Conversation::with_cost_tracker]
+//!   and [`DeepseekAgent::with_cost_tracker`][crate::agent::DeepseekAgent::with_cost_tracker]
+//!   count the calls made for one conversation or agent, including the
+//!   summarizer's.
+//!
+//! Trackers are cheap to clone and clones share their totals, so one tracker
+//! per tenant gives per-tenant attribution.  Attach a given tracker at one
+//! level only, or the calls are counted twice.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::raw::Usage;
+
+/// Prices for one model, per million tokens.
+///
+/// The currency is whatever the [`PriceTable`] is written in; the built-in
+/// DeepSeek table uses USD.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// Input tokens served from the context cache.
+    pub cache_hit_input: f64,
+    /// Input tokens not found in the cache.
+    pub cache_miss_input: f64,
+    /// Output tokens, reasoning tokens included.
+    pub output: f64,
+}
+
+impl ModelPrice {
+    pub const fn new(cache_hit_input: f64, cache_miss_input: f64, output: f64) -> Self {
+        Self {
+            cache_hit_input,
+            cache_miss_input,
+            output,
+        }
+    }
+
+    /// The price of one call with the given `usage`.
+    ///
+    /// Providers that do not report cache hits are billed at the cache-miss
+    /// rate for every input token.
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        let (hit, miss) = input_split(usage);
+        (f64::from(hit) * self.cache_hit_input
+            + f64::from(miss) * self.cache_miss_input
+            + f64::from(usage.completion_tokens) * self.output)
+            / 1_000_000.0
+    }
+}
+
+/// Cache-hit and cache-miss input tokens of a call.
+fn input_split(usage: &Usage) -> (u32, u32) {
+    let hit = usage.prompt_cache_hit_tokens.unwrap_or(0);
+    let miss = usage
+        .prompt_cache_miss_tokens
+        .unwrap_or(usage.prompt_tokens.saturating_sub(hit));
+    (hit, miss)
+}
+
+/// Per-model prices, keyed by the model name the API reports.
+///
+/// The default table holds DeepSeek's list prices for `deepseek-chat` and
+/// `deepseek-reasoner` at the time of writing; override them with
+/// [`with_price`][Self::with_price] when they change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceTable {
+    prices: HashMap<String, ModelPrice>,
+}
+
+impl Default for PriceTable {
+    fn default() -> Self {
+        let price = ModelPrice::new(0.028, 0.28, 0.42);
+        Self::new()
+            .with_price("deepseek-chat", price)
+            .with_price("deepseek-reasoner", price)
+    }
+}
+
+impl PriceTable {
+    /// An empty table; every call is unpriced until prices are added.
+    pub fn new() -> Self {
+        Self {
+            prices: HashMap::new(),
+        }
+    }
+
+    /// Builder: set the price of `model`.
+    pub fn with_price(mut self, model: impl Into<String>, price: ModelPrice) -> Self {
+        self.prices.insert(model.into(), price);
+        self
+    }
+
+    /// The price of `model`, if the table has one.
+    pub fn get(&self, model: &str) -> Option<ModelPrice> {
+        self.prices.get(model).copied()
+    }
+}
+
+/// Token counts and cost, per model or in total, from a [`CostTracker`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostStats {
+    /// Calls recorded.
+    pub requests: u64,
+    pub prompt_cache_hit_tokens: u64,
+    pub prompt_cache_miss_tokens: u64,
+    /// Output tokens, reasoning tokens included.
+    pub completion_tokens: u64,
+    pub reasoning_tokens: u64,
+    /// Cost of the priced calls, in the price table's currency.
+    pub cost: f64,
+    /// Calls whose model had no price; their tokens are counted, their cost
+    /// is not.
+    pub unpriced_requests: u64,
+}
+
+impl CostStats {
+    fn add(&mut self, other: &CostStats) {
+        self.requests += other.requests;
+        self.prompt_cache_hit_tokens += other.prompt_cache_hit_tokens;
+        self.prompt_cache_miss_tokens += other.prompt_cache_miss_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.reasoning_tokens += other.reasoning_tokens;
+        self.cost += other.cost;
+        self.unpriced_requests += other.unpriced_requests;
+    }
+}
+
+#[derive(Debug)]
+struct TrackerState {
+    prices: PriceTable,
+    models: HashMap<String, CostStats>,
+}
+
+/// Running cost totals, shared by every clone.
+///
+/// # Example
+///
+/// ```no_run
+/// use ds_api::{ApiClient, CostTracker, DeepseekAgent};
+///
+/// let tracker = CostTracker::new();
+/// let agent = DeepseekAgent::new("sk-...").with_cost_tracker(tracker.clone());
+/// // ... run the agent ...
+/// println!("spent ${:.4}", tracker.total().cost);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CostTracker {
+    state: Arc<Mutex<TrackerState>>,
+}
+
+impl Default for CostTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CostTracker {
+    /// A tracker using the default DeepSeek [`PriceTable`].
+    pub fn new() -> Self {
+        Self::with_prices(PriceTable::default())
+    }
+
+    /// A tracker using `prices`.
+    pub fn with_prices(prices: PriceTable) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(TrackerState {
+                prices,
+                models: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Record one call to `model` and return its cost, or `None` if the
+    /// model has no price.
+    pub fn record(&self, model: &str, usage: &Usage) -> Option<f64> {
+        let mut state = self.state.lock().unwrap();
+        let price = state.prices.get(model);
+        let (hit, miss) = input_split(usage);
+        let cost = price.map(|p| p.cost(usage));
+
+        let stats = state.models.entry(model.to_string()).or_default();
+        stats.requests += 1;
+        stats.prompt_cache_hit_tokens += u64::from(hit);
+        stats.prompt_cache_miss_tokens += u64::from(miss);
+        stats.completion_tokens += u64::from(usage.completion_tokens);
+        stats.reasoning_tokens += u64::from(usage.reasoning_tokens());
+        match cost {
+            Some(c) => stats.cost += c,
+            None => stats.unpriced_requests += 1,
+        }
+        cost
+    }
+
+    /// Totals across all models.
+    pub fn total(&self) -> CostStats {
+        let state = self.state.lock().unwrap();
+        let mut total = CostStats::default();
+        for stats in state.models.values() {
+            total.add(stats);
+        }
+        total
+    }
+
+    /// Totals for each model that has been called.
+    pub fn by_model(&self) -> HashMap<String, CostStats> {
+        self.state.lock().unwrap().models.clone()
+    }
+
+    /// Clear the totals, keeping the price table.
+    pub fn reset(&self) {
+        self.state.lock().unwrap().models.clear();
+    }
+}
+
+tokio::task_local! {
+    static COST_SCOPE: CostTracker;
+}
+
+/// Run `fut` with `tracker` installed for calls that are made out of sight
+/// of the caller, such as the summarizer's.
+pub(crate) async fn with_cost_scope<F: Future>(tracker: Option<CostTracker>, fut: F) -> F::Output {
+    match tracker {
+        Some(tracker) => COST_SCOPE.scope(tracker, fut).await,
+        None => fut.await,
+    }
+}
+
+/// Record a call in the tracker installed for the current task, if any.
+pub(crate) fn record_in_scope(model: &str, usage: &Usage) {
+    let _ = COST_SCOPE.try_with(|tracker| tracker.record(model, usage));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::CompletionTokensDetails;
+
+    fn usage(prompt: u32, hit: Option<u32>, completion: u32) -> Usage {
+        Usage {
+            completion_tokens: completion,
+            prompt_tokens: prompt,
+            prompt_cache_hit_tokens: hit,
+            prompt_cache_miss_tokens: hit.map(|h| prompt - h),
+            total_tokens: prompt + completion,
+            completion_tokens_details: Some(CompletionTokensDetails {
+                reasoning_tokens: completion / 2,
+            }),
+        }
+    }
+
+    #[test]
+    fn prices_cache_hits_misses_and_output_separately() {
+        let price = ModelPrice::new(1.0, 10.0, 100.0);
+        let cost = price.cost(&usage(1_000_000, Some(400_000), 10_000));
+        assert!((cost - (0.4 + 6.0 + 1.0)).abs() < 1e-9);
+        // No cache report: all input at the miss rate.
+        let cost = price.cost(&usage(1_000_000, None, 0));
+        assert!((cost - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tracker_aggregates_per_model_and_counts_unpriced_calls() {
+        let tracker = CostTracker::with_prices(
+            PriceTable::new().with_price("a", ModelPrice::new(0.0, 1_000_000.0, 0.0)),
+        );
+        let clone = tracker.clone();
+        assert_eq!(tracker.record("a", &usage(3, Some(1), 4)), Some(2.0));
+        assert_eq!(clone.record("a", &usage(5, None, 2)), Some(5.0));
+        assert_eq!(tracker.record("b", &usage(10, None, 6)), None);
+
+        let total = tracker.total();
+        assert_eq!(total.requests, 3);
+        assert_eq!(total.prompt_cache_hit_tokens, 1);
+        assert_eq!(total.prompt_cache_miss_tokens, 2 + 5 + 10);
+        assert_eq!(total.reasoning_tokens, 2 + 1 + 3);
+        assert_eq!(total.cost, 7.0);
+        assert_eq!(total.unpriced_requests, 1);
+        assert_eq!(tracker.by_model()["b"].completion_tokens, 6);
+
+        tracker.reset();
+        assert_eq!(clone.total(), CostStats::default());
+    }
+}
@@ -0,0 +1,499 @@
+//! Provider fallback across several endpoints.
+//!
+//! A [`FallbackClient`] holds an ordered list of [`Endpoint`]s — typically
+//! DeepSeek first, then an OpenAI-compatible provider or a different model —
+//! and sends each request to the first healthy one.  When an endpoint fails
+//! in a way the [`FailoverRules`] classify as "try the next one", the request
+//! moves down the list.
+//!
+//! Each endpoint has a circuit breaker: after
+//! [`failure_threshold`][CircuitBreaker::failure_threshold] consecutive
+//! failures it is skipped for [`cooldown`][CircuitBreaker::cooldown], then
+//! tried again (half-open).  One success closes the circuit.
+//!
+//! `FallbackClient` implements [`ChatBackend`], so it can be handed to
+//! [`DeepseekAgent::from_client`][crate::agent::DeepseekAgent::from_client].
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+use super::backend::{ChatBackend, ChunkStream};
+use super::client::ApiClient;
+use super::request::ApiRequest;
+use crate::error::{ApiError, ErrorKind, Result};
+use crate::raw::{ChatCompletionResponse, FinishReason};
+
+// ── Configuration ─────────────────────────────────────────────────────────────
+
+/// One backend in a [`FallbackClient`] chain.
+#[derive(Clone)]
+pub struct Endpoint {
+    name: String,
+    backend: Arc<dyn ChatBackend>,
+    model: Option<String>,
+}
+
+impl Endpoint {
+    /// An OpenAI-compatible endpoint at `base_url`, answering with `model`.
+    pub fn new(
+        base_url: impl Into<String>,
+        token: impl Into<String>,
+        model: impl Into<String>,
+    ) -> Self {
+        let base_url = base_url.into();
+        let client = ApiClient::new(token).with_base_url(base_url.clone());
+        Self::from_backend(base_url, client).with_model(model)
+    }
+
+    /// Wrap any backend (e.g. a pre-configured [`ApiClient`]) under `name`.
+    ///
+    /// Requests keep the model they were built with unless
+    /// [`with_model`][Endpoint::with_model] is set.
+    pub fn from_backend(name: impl Into<String>, backend: impl ChatBackend + 'static) -> Self {
+        Self {
+            name: name.into(),
+            backend: Arc::new(backend),
+            model: None,
+        }
+    }
+
+    /// Builder: rewrite the `model` of every request sent to this endpoint.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// The name used in logs and [`EndpointStatus`].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn prepare(&self, req: &ApiRequest) -> ApiRequest {
+        match &self.model {
+            Some(model) => req.clone().with_model(model.clone()),
+            None => req.clone(),
+        }
+    }
+}
+
+/// Which failures move a request on to the next endpoint.
+///
+/// Every rule is enabled by default.  Failures that no rule covers (e.g. a
+/// 400 for a malformed request) are returned immediately — the next provider
+/// would reject the request too.
+#[derive(Debug, Clone)]
+pub struct FailoverRules {
+    pub(crate) server_errors: bool,
+    pub(crate) rate_limited: bool,
+    pub(crate) timeouts: bool,
+    pub(crate) connect_errors: bool,
+    pub(crate) insufficient_resource: bool,
+}
+
+impl Default for FailoverRules {
+    fn default() -> Self {
+        Self {
+            server_errors: true,
+            rate_limited: true,
+            timeouts: true,
+            connect_errors: true,
+            insufficient_resource: true,
+        }
+    }
+}
+
+impl FailoverRules {
+    /// All rules enabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: fail over on HTTP 5xx responses.
+    pub fn server_errors(mut self, enabled: bool) -> Self {
+        self.server_errors = enabled;
+        self
+    }
+
+    /// Builder: fail over on HTTP 429 responses.
+    pub fn rate_limited(mut self, enabled: bool) -> Self {
+        self.rate_limited = enabled;
+        self
+    }
+
+    /// Builder: fail over on request timeouts.
+    pub fn timeouts(mut self, enabled: bool) -> Self {
+        self.timeouts = enabled;
+        self
+    }
+
+    /// Builder: fail over when the endpoint cannot be reached or the
+    /// connection breaks.
+    pub fn connect_errors(mut self, enabled: bool) -> Self {
+        self.connect_errors = enabled;
+        self
+    }
+
+    /// Builder: fail over when a non-streaming response finishes with
+    /// `insufficient_system_resource`.
+    pub fn insufficient_resource(mut self, enabled: bool) -> Self {
+        self.insufficient_resource = enabled;
+        self
+    }
+
+    /// Returns `true` if `err` should move the request to the next endpoint.
+    pub fn should_fail_over(&self, err: &ApiError) -> bool {
+        match err.kind() {
+            ErrorKind::Server | ErrorKind::Overloaded => self.server_errors,
+            ErrorKind::RateLimited => self.rate_limited,
+            ErrorKind::Timeout => self.timeouts,
+            ErrorKind::Network => self.connect_errors,
+            _ => false,
+        }
+    }
+}
+
+/// Circuit-breaker settings shared by every endpoint of a [`FallbackClient`].
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    pub(crate) failure_threshold: u32,
+    pub(crate) cooldown: Duration,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+impl CircuitBreaker {
+    /// Open after 3 consecutive failures, retry after 30 s.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: consecutive failures that open the circuit (at least 1).
+    pub fn failure_threshold(mut self, n: u32) -> Self {
+        self.failure_threshold = n.max(1);
+        self
+    }
+
+    /// Builder: how long an open circuit skips the endpoint.
+    pub fn cooldown(mut self, d: Duration) -> Self {
+        self.cooldown = d;
+        self
+    }
+}
+
+/// Health snapshot of one endpoint, from [`FallbackClient::status`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointStatus {
+    pub name: String,
+    /// Consecutive failures since the last success.
+    pub consecutive_failures: u32,
+    /// `true` while the circuit is open and the endpoint is being skipped.
+    pub open: bool,
+}
+
+// ── Client ────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Default)]
+struct Health {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+struct Slot {
+    endpoint: Endpoint,
+    health: Mutex<Health>,
+}
+
+impl Slot {
+    fn health(&self) -> std::sync::MutexGuard<'_, Health> {
+        self.health.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn is_open(&self) -> bool {
+        self.health()
+            .open_until
+            .is_some_and(|until| Instant::now() < until)
+    }
+}
+
+/// A [`ChatBackend`] that fails over across an ordered list of endpoints.
+///
+/// Clones share the endpoint list and circuit-breaker state.
+///
+/// # Example
+///
+/// ```no_run
+/// use ds_api::{DeepseekAgent, Endpoint, FallbackClient};
+///
+/// let backend = FallbackClient::new(vec![
+///     Endpoint::new("https://api.deepseek.com", "sk-ds-...", "deepseek-chat"),
+///     Endpoint::new(
+///         "https://openrouter.ai/api/v1",
+///         "sk-or-...",
+///         "deepseek/deepseek-chat",
+///     ),
+/// ]);
+/// let agent = DeepseekAgent::from_client(backend, "deepseek-chat");
+/// ```
+#[derive(Clone)]
+pub struct FallbackClient {
+    slots: Arc<Vec<Slot>>,
+    rules: FailoverRules,
+    breaker: CircuitBreaker,
+}
+
+impl FallbackClient {
+    /// Create a client trying `endpoints` in order, with default rules and
+    /// circuit breaker.
+    pub fn new(endpoints: impl IntoIterator<Item = Endpoint>) -> Self {
+        Self {
+            slots: Arc::new(
+                endpoints
+                    .into_iter()
+                    .map(|endpoint| Slot {
+                        endpoint,
+                        health: Mutex::new(Health::default()),
+                    })
+                    .collect(),
+            ),
+            rules: FailoverRules::default(),
+            breaker: CircuitBreaker::default(),
+        }
+    }
+
+    /// Builder: replace the failover rules.
+    pub fn with_rules(mut self, rules: FailoverRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Builder: replace the circuit-breaker settings.
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
+    /// Health of every endpoint, in order.
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        self.slots
+            .iter()
+            .map(|slot| {
+                let open = slot.is_open();
+                EndpointStatus {
+                    name: slot.endpoint.name.clone(),
+                    consecutive_failures: slot.health().consecutive_failures,
+                    open,
+                }
+            })
+            .collect()
+    }
+
+    /// Endpoints to try, in order: the ones with a closed (or half-open)
+    /// circuit, or every endpoint if all circuits are open.
+    fn candidates(&self) -> Vec<&Slot> {
+        let closed: Vec<&Slot> = self.slots.iter().filter(|s| !s.is_open()).collect();
+        if closed.is_empty() {
+            debug!("all circuits open; trying every endpoint");
+            self.slots.iter().collect()
+        } else {
+            closed
+        }
+    }
+
+    fn record_success(&self, slot: &Slot) {
+        *slot.health() = Health::default();
+    }
+
+    fn record_failure(&self, slot: &Slot, reason: &str) {
+        let mut health = slot.health();
+        health.consecutive_failures += 1;
+        if health.consecutive_failures >= self.breaker.failure_threshold {
+            health.open_until = Some(Instant::now() + self.breaker.cooldown);
+        }
+        let failures = health.consecutive_failures;
+        drop(health);
+        warn!(
+            endpoint = %slot.endpoint.name,
+            failures,
+            open = slot.is_open(),
+            reason,
+            "endpoint failed; falling back"
+        );
+    }
+
+    fn no_endpoints() -> ApiError {
+        ApiError::Other("FallbackClient has no endpoints".to_string())
+    }
+}
+
+#[async_trait]
+impl ChatBackend for FallbackClient {
+    /// Send to the first healthy endpoint, moving down the list on failures
+    /// covered by the [`FailoverRules`].
+    ///
+    /// If every endpoint answers with `insufficient_system_resource`, the
+    /// last such response is returned rather than an error.
+    async fn send(&self, req: ApiRequest) -> Result<ChatCompletionResponse> {
+        let mut last: Option<Result<ChatCompletionResponse>> = None;
+        for slot in self.candidates() {
+            match slot
+                .endpoint
+                .backend
+                .send(slot.endpoint.prepare(&req))
+                .await
+            {
+                Ok(resp) => {
+                    let exhausted = resp.choices.first().map(|c| c.finish_reason)
+                        == Some(FinishReason::InsufficientSystemResource);
+                    if exhausted && self.rules.insufficient_resource {
+                        self.record_failure(slot, "insufficient_system_resource");
+                        last = Some(Ok(resp));
+                        continue;
+                    }
+                    self.record_success(slot);
+                    return Ok(resp);
+                }
+                Err(e) if self.rules.should_fail_over(&e) => {
+                    self.record_failure(slot, &e.to_string());
+                    last = Some(Err(e));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        last.unwrap_or_else(|| Err(Self::no_endpoints()))
+    }
+
+    /// Connect to the first healthy endpoint.
+    ///
+    /// Failover happens only while connecting; once chunks are flowing the
+    /// stream is tied to that endpoint, so an `insufficient_system_resource`
+    /// finish reason at the end of a stream is not retried elsewhere.
+    async fn send_stream(&self, req: ApiRequest) -> Result<ChunkStream> {
+        let mut last = None;
+        for slot in self.candidates() {
+            match slot
+                .endpoint
+                .backend
+                .send_stream(slot.endpoint.prepare(&req))
+                .await
+            {
+                Ok(stream) => {
+                    self.record_success(slot);
+                    return Ok(stream);
+                }
+                Err(e) if self.rules.should_fail_over(&e) => {
+                    self.record_failure(slot, &e.to_string());
+                    last = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last.unwrap_or_else(Self::no_endpoints))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{MockBackend, MockReply};
+    use crate::raw::Message;
+    use reqwest::StatusCode;
+
+    fn busy() -> ApiError {
+        ApiError::http_error(StatusCode::SERVICE_UNAVAILABLE, "busy")
+    }
+
+    fn req() -> ApiRequest {
+        ApiRequest::builder().add_message(Message::user("hi"))
+    }
+
+    #[tokio::test]
+    async fn falls_over_to_next_endpoint_and_rewrites_model() {
+        let primary = MockBackend::new().error(busy());
+        let secondary = MockBackend::new().reply(MockReply::text("from backup"));
+        let client = FallbackClient::new([
+            Endpoint::from_backend("primary", primary.clone()),
+            Endpoint::from_backend("backup", secondary.clone()).with_model("other-model"),
+        ]);
+
+        let resp = client.send(req()).await.unwrap();
+        assert_eq!(resp.content(), Some("from backup"));
+        assert_eq!(secondary.requests()[0].model.as_str(), "other-model");
+        assert_eq!(client.status()[0].consecutive_failures, 1);
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_failed_over() {
+        let primary =
+            MockBackend::new().error(ApiError::http_error(StatusCode::BAD_REQUEST, "bad"));
+        let secondary = MockBackend::new().reply(MockReply::text("unused"));
+        let client = FallbackClient::new([
+            Endpoint::from_backend("primary", primary),
+            Endpoint::from_backend("backup", secondary.clone()),
+        ]);
+
+        assert!(client.send(req()).await.is_err());
+        assert!(secondary.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn insufficient_resource_falls_over() {
+        let primary = MockBackend::new().reply(
+            MockReply::text("partial").with_finish_reason(FinishReason::InsufficientSystemResource),
+        );
+        let secondary = MockBackend::new().reply(MockReply::text("complete"));
+        let client = FallbackClient::new([
+            Endpoint::from_backend("primary", primary),
+            Endpoint::from_backend("backup", secondary),
+        ]);
+
+        assert_eq!(
+            client.send(req()).await.unwrap().content(),
+            Some("complete")
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn open_circuit_skips_endpoint_until_cooldown() {
+        let primary = MockBackend::new()
+            .error(busy())
+            .reply(MockReply::text("recovered"));
+        let secondary = MockBackend::new()
+            .reply(MockReply::text("b1"))
+            .reply(MockReply::text("b2"));
+        let client = FallbackClient::new([
+            Endpoint::from_backend("primary", primary.clone()),
+            Endpoint::from_backend("backup", secondary),
+        ])
+        .with_circuit_breaker(
+            CircuitBreaker::new()
+                .failure_threshold(1)
+                .cooldown(Duration::from_secs(10)),
+        );
+
+        assert_eq!(client.send(req()).await.unwrap().content(), Some("b1"));
+        assert!(client.status()[0].open);
+
+        // Primary is skipped while its circuit is open.
+        assert_eq!(client.send(req()).await.unwrap().content(), Some("b2"));
+        assert_eq!(primary.requests().len(), 1);
+
+        // After the cooldown it is tried again and closes on success.
+        tokio::time::advance(Duration::from_secs(11)).await;
+        assert_eq!(
+            client.send(req()).await.unwrap().content(),
+            Some("recovered")
+        );
+        assert!(!client.status()[0].open);
+    }
+}
@@ -0,0 +1,138 @@
+//! FimRequest builder module.
+//!
+//! Provides a chainable builder for the (beta) fill-in-the-middle completion
+//! endpoint that wraps the internal `crate::raw::CompletionRequest`.
+
+use super::request::{check_range, invalid};
+use crate::error::Result;
+use crate::raw::{CompletionRequest, Model, Stop};
+
+/// A chainable request builder for FIM (fill-in-the-middle) completion.
+///
+/// The model generates the text that belongs between
+/// [`prompt`][FimRequest::new] and [`suffix`][FimRequest::suffix].  Send it
+/// with [`ApiClient::complete`][crate::api::ApiClient::complete] or
+/// [`ApiClient::complete_stream`][crate::api::ApiClient::complete_stream].
+///
+/// ```
+/// use ds_api::FimRequest;
+///
+/// let req = FimRequest::new("fn add(a: i32, b: i32) -> i32 {\n")
+///     .suffix("\n}\n")
+///     .max_tokens(64);
+/// ```
+#[derive(Debug)]
+pub struct FimRequest {
+    raw: CompletionRequest,
+}
+
+impl FimRequest {
+    /// Start a new request for `deepseek-chat` with the given prompt.
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            raw: CompletionRequest {
+                model: Model::DeepseekChat,
+                prompt: prompt.into(),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Set the model by string (builder-style).
+    pub fn with_model(mut self, name: impl Into<String>) -> Self {
+        self.raw.model = Model::Custom(name.into());
+        self
+    }
+
+    /// Set the text that follows the completion.
+    pub fn suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.raw.suffix = Some(suffix.into());
+        self
+    }
+
+    /// Set max tokens.
+    pub fn max_tokens(mut self, n: u32) -> Self {
+        self.raw.max_tokens = Some(n);
+        self
+    }
+
+    /// Set temperature.
+    pub fn temperature(mut self, t: f32) -> Self {
+        self.raw.temperature = Some(t);
+        self
+    }
+
+    /// Set nucleus sampling probability mass.
+    pub fn top_p(mut self, p: f32) -> Self {
+        self.raw.top_p = Some(p);
+        self
+    }
+
+    /// Set the frequency penalty.
+    pub fn frequency_penalty(mut self, v: f32) -> Self {
+        self.raw.frequency_penalty = Some(v);
+        self
+    }
+
+    /// Set the presence penalty.
+    pub fn presence_penalty(mut self, v: f32) -> Self {
+        self.raw.presence_penalty = Some(v);
+        self
+    }
+
+    /// Stop generating when any of the given sequences is produced.
+    pub fn stop<I, S>(mut self, sequences: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.raw.stop = Some(Stop::Array(sequences.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// Echo the prompt back in front of the completion.
+    pub fn echo(mut self, enabled: bool) -> Self {
+        self.raw.echo = Some(enabled);
+        self
+    }
+
+    /// Return the log-probabilities of the `n` most likely tokens at each
+    /// position.
+    pub fn logprobs(mut self, n: u32) -> Self {
+        self.raw.logprobs = Some(n);
+        self
+    }
+
+    /// Add a single extra top-level field to the request body (builder-style).
+    pub fn with_extra_field(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.raw
+            .extra_body
+            .get_or_insert_with(serde_json::Map::new)
+            .insert(key.into(), value);
+        self
+    }
+
+    /// Check every field against the ranges the API accepts; see
+    /// [`ApiRequest::validate`][crate::api::ApiRequest::validate].
+    pub fn validate(&self) -> Result<()> {
+        let raw = &self.raw;
+        check_range("temperature", raw.temperature, 0.0..=2.0)?;
+        check_range("top_p", raw.top_p, 0.0..=1.0)?;
+        check_range("frequency_penalty", raw.frequency_penalty, -2.0..=2.0)?;
+        check_range("presence_penalty", raw.presence_penalty, -2.0..=2.0)?;
+        if raw.max_tokens == Some(0) {
+            return Err(invalid("max_tokens", "must be at least 1"));
+        }
+        if let Some(n) = raw.logprobs
+            && n > 20
+        {
+            return Err(invalid("logprobs", format!("must be at most 20, got {n}")));
+        }
+        Ok(())
+    }
+
+    /// Build and return the internal raw request (crate-internal use).
+    pub(crate) fn into_raw(self) -> CompletionRequest {
+        self.raw
+    }
+}
@@ -0,0 +1,261 @@
+//! Rotating pool of API keys.
+//!
+//! A [`KeyPool`] attached with
+//! [`ApiClient::with_key_pool`][crate::api::ApiClient::with_key_pool] replaces
+//! the client's single token: every attempt picks a key according to the
+//! pool's [`KeySelection`] strategy.  Keys rejected as unauthorized (401,
+//! 403) or out of balance (402, `insufficient_quota`) are evicted and the
+//! request is re-sent with the next key.
+//!
+//! The pool keeps per-key counters fed from each response's
+//! [`Usage`]; [`ApiClient::key_stats`][crate::api::ApiClient::key_stats]
+//! returns them with the keys masked, so they are safe to log.
+
+use std::fmt;
+use std::sync::Mutex;
+
+use reqwest::StatusCode;
+use tracing::{debug, warn};
+
+use crate::error::{ApiError, ErrorKind, Result};
+use crate::raw::Usage;
+
+/// How a [`KeyPool`] picks the key for the next request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeySelection {
+    /// Cycle through the usable keys in order.
+    #[default]
+    RoundRobin,
+    /// Pick the key that has consumed the fewest tokens so far (ties go to
+    /// the key with fewer requests, then to the earlier key).
+    LeastUsed,
+}
+
+/// Usage counters for one key, from
+/// [`ApiClient::key_stats`][crate::api::ApiClient::key_stats].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyStats {
+    /// The key with everything but its last four characters masked.
+    pub masked_key: String,
+    /// Requests sent with this key (every attempt counts).
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    /// The status code that evicted the key, if it has been evicted.
+    pub evicted: Option<StatusCode>,
+}
+
+struct KeyEntry {
+    key: String,
+    stats: KeyStats,
+}
+
+struct PoolState {
+    keys: Vec<KeyEntry>,
+    cursor: usize,
+}
+
+/// A set of API keys shared by a client and all of its clones.
+///
+/// # Example
+///
+/// ```no_run
+/// use ds_api::{ApiClient, KeyPool, KeySelection};
+///
+/// let client = ApiClient::new("unused").with_key_pool(
+///     KeyPool::new(["sk-first...", "sk-second...", "sk-third..."])
+///         .selection(KeySelection::LeastUsed),
+/// );
+///
+/// for stats in client.key_stats().unwrap() {
+///     println!("{}: {} tokens", stats.masked_key, stats.total_tokens);
+/// }
+/// ```
+pub struct KeyPool {
+    selection: KeySelection,
+    state: Mutex<PoolState>,
+}
+
+impl KeyPool {
+    /// Create a round-robin pool over `keys`.
+    pub fn new<I, S>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let keys = keys
+            .into_iter()
+            .map(|key| {
+                let key = key.into();
+                KeyEntry {
+                    stats: KeyStats {
+                        masked_key: mask_key(&key),
+                        ..Default::default()
+                    },
+                    key,
+                }
+            })
+            .collect();
+        Self {
+            selection: KeySelection::default(),
+            state: Mutex::new(PoolState { keys, cursor: 0 }),
+        }
+    }
+
+    /// Builder: set the selection strategy.
+    pub fn selection(mut self, selection: KeySelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Per-key counters, in the order the keys were given.
+    pub fn stats(&self) -> Vec<KeyStats> {
+        self.lock().keys.iter().map(|e| e.stats.clone()).collect()
+    }
+
+    /// Number of keys that have not been evicted.
+    pub fn usable(&self) -> usize {
+        self.lock()
+            .keys
+            .iter()
+            .filter(|e| e.stats.evicted.is_none())
+            .count()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Pick a key for the next attempt and count the request against it.
+    ///
+    /// Returns the key's index and value, or [`ApiError::NoUsableKeys`] if
+    /// every key has been evicted.
+    pub(crate) fn checkout(&self) -> Result<(usize, String)> {
+        let mut state = self.lock();
+        let len = state.keys.len();
+        let usable = |i: &usize| state.keys[*i].stats.evicted.is_none();
+        let index = match self.selection {
+            KeySelection::RoundRobin => (0..len)
+                .map(|offset| (state.cursor + offset) % len)
+                .find(usable),
+            KeySelection::LeastUsed => (0..len).filter(usable).min_by_key(|&i| {
+                (
+                    state.keys[i].stats.total_tokens,
+                    state.keys[i].stats.requests,
+                )
+            }),
+        }
+        .ok_or(ApiError::NoUsableKeys)?;
+
+        state.cursor = (index + 1) % len;
+        let entry = &mut state.keys[index];
+        entry.stats.requests += 1;
+        debug!(key = %entry.stats.masked_key, "selected API key");
+        Ok((index, entry.key.clone()))
+    }
+
+    /// Evict the key at `index` if `err` says it can no longer be used
+    /// ([`ErrorKind::Authentication`] or [`ErrorKind::InsufficientBalance`]).
+    ///
+    /// Returns `true` if the key was evicted by this call.
+    pub(crate) fn evict_on(&self, index: usize, err: &ApiError) -> bool {
+        let Some(status) = err.status() else {
+            return false;
+        };
+        if !matches!(
+            err.kind(),
+            ErrorKind::Authentication | ErrorKind::InsufficientBalance
+        ) {
+            return false;
+        }
+        let mut state = self.lock();
+        let stats = &mut state.keys[index].stats;
+        if stats.evicted.is_some() {
+            return false;
+        }
+        stats.evicted = Some(status);
+        warn!(key = %stats.masked_key, %status, "evicting API key from pool");
+        true
+    }
+
+    /// Add `usage` to the counters of the key at `index`.
+    pub(crate) fn record_usage(&self, index: usize, usage: &Usage) {
+        let mut state = self.lock();
+        let stats = &mut state.keys[index].stats;
+        stats.prompt_tokens += u64::from(usage.prompt_tokens);
+        stats.completion_tokens += u64::from(usage.completion_tokens);
+        stats.total_tokens += u64::from(usage.total_tokens);
+    }
+}
+
+impl fmt::Debug for KeyPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyPool")
+            .field("selection", &self.selection)
+            .field("keys", &self.stats())
+            .finish()
+    }
+}
+
+/// Mask all but the last four characters of `key` (all of it if it is short).
+pub(crate) fn mask_key(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() <= 8 {
+        return "***".to_string();
+    }
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("***{tail}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(total: u32) -> Usage {
+        Usage {
+            completion_tokens: total,
+            prompt_tokens: 0,
+            prompt_cache_hit_tokens: None,
+            prompt_cache_miss_tokens: None,
+            total_tokens: total,
+            completion_tokens_details: None,
+        }
+    }
+
+    #[test]
+    fn masks_keys() {
+        assert_eq!(mask_key("sk-1234567890abcd"), "***abcd");
+        assert_eq!(mask_key("short"), "***");
+    }
+
+    #[test]
+    fn round_robin_skips_evicted_keys() {
+        let pool = KeyPool::new(["a", "b", "c"]);
+        assert_eq!(pool.checkout().unwrap().1, "a");
+        assert_eq!(pool.checkout().unwrap().1, "b");
+        assert!(pool.evict_on(2, &ApiError::http_error(StatusCode::PAYMENT_REQUIRED, "")));
+        assert_eq!(pool.checkout().unwrap().1, "a");
+        assert_eq!(pool.checkout().unwrap().1, "b");
+        assert_eq!(pool.usable(), 2);
+    }
+
+    #[test]
+    fn least_used_prefers_fewest_tokens() {
+        let pool = KeyPool::new(["a", "b"]).selection(KeySelection::LeastUsed);
+        let (i, _) = pool.checkout().unwrap();
+        pool.record_usage(i, &usage(100));
+        assert_eq!(pool.checkout().unwrap().1, "b");
+        pool.record_usage(1, &usage(10));
+        assert_eq!(pool.checkout().unwrap().1, "b");
+    }
+
+    #[test]
+    fn other_statuses_do_not_evict_and_empty_pool_errors() {
+        let pool = KeyPool::new(["a"]);
+        assert!(!pool.evict_on(0, &ApiError::http_error(StatusCode::TOO_MANY_REQUESTS, "")));
+        assert!(pool.evict_on(0, &ApiError::http_error(StatusCode::UNAUTHORIZED, "")));
+        assert!(matches!(pool.checkout(), Err(ApiError::NoUsableKeys)));
+        assert_eq!(pool.stats()[0].evicted, Some(StatusCode::UNAUTHORIZED));
+    }
+}
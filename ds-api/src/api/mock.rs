@@ -0,0 +1,381 @@
+//! A scripted [`ChatBackend`] for deterministic tests.
+//!
+//! [`MockBackend`] answers requests from a queue of [`MockReply`]s (or errors)
+//! in order and records every request it receives.  The same reply can be
+//! consumed either as a full response (non-streaming agents) or as a stream
+//! of deltas (agents built with `with_streaming()`), so one script exercises
+//! both paths.
+//!
+//! ```
+//! use ds_api::{AgentEvent, DeepseekAgent, MockBackend, MockReply};
+//! use futures::StreamExt;
+//! use serde_json::json;
+//!
+//! # #[tokio::main] async fn main() {
+//! let mock = MockBackend::new()
+//!     .reply(MockReply::tool_call("call_1", "lookup", json!({ "key": "a" })))
+//!     .reply(MockReply::text("done"));
+//!
+//! let mut stream = DeepseekAgent::from_client(mock.clone(), "deepseek-chat").chat("go");
+//! while let Some(event) = stream.next().await {
+//!     let _ = event;
+//! }
+//! assert_eq!(mock.requests().len(), 2);
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde_json::Value;
+
+use super::backend::{ChatBackend, ChunkStream};
+use super::request::ApiRequest;
+use crate::error::{ApiError, Result};
+use crate::raw::request::message::{FunctionCall, Message, Role, ToolCall, ToolType};
+use crate::raw::{
+    ChatCompletionChunk, ChatCompletionRequest, ChatCompletionResponse, Choice, ChunkChoice,
+    ChunkObjectType, Delta, DeltaFunctionCall, DeltaToolCall, FinishReason, Model, ObjectType,
+    Usage,
+};
+
+// ── MockReply ─────────────────────────────────────────────────────────────────
+
+/// One scripted assistant turn.
+///
+/// When streamed, the reply is split into one chunk per reasoning delta, per
+/// content delta and per tool call, followed by a final chunk carrying the
+/// finish reason and usage.
+#[derive(Debug, Clone, Default)]
+pub struct MockReply {
+    reasoning: Vec<String>,
+    deltas: Vec<String>,
+    tool_calls: Vec<ToolCall>,
+    usage: Option<(u32, u32)>,
+    finish_reason: Option<FinishReason>,
+}
+
+impl MockReply {
+    /// A plain text reply, streamed as a single delta.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::deltas([text])
+    }
+
+    /// A text reply streamed as the given deltas (joined when not streaming).
+    pub fn deltas<I, S>(deltas: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            deltas: deltas.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// A reply that requests a single tool call.
+    pub fn tool_call(id: impl Into<String>, name: impl Into<String>, args: Value) -> Self {
+        Self::default().with_tool_call(id, name, args)
+    }
+
+    /// Builder: add a tool call to the reply.
+    pub fn with_tool_call(
+        mut self,
+        id: impl Into<String>,
+        name: impl Into<String>,
+        args: Value,
+    ) -> Self {
+        self.tool_calls.push(ToolCall {
+            id: id.into(),
+            r#type: ToolType::Function,
+            function: FunctionCall {
+                name: name.into(),
+                arguments: args.to_string(),
+            },
+        });
+        self
+    }
+
+    /// Builder: add reasoning content, emitted before any text.
+    pub fn with_reasoning(mut self, reasoning: impl Into<String>) -> Self {
+        self.reasoning.push(reasoning.into());
+        self
+    }
+
+    /// Builder: report the given token usage.
+    pub fn with_usage(mut self, prompt_tokens: u32, completion_tokens: u32) -> Self {
+        self.usage = Some((prompt_tokens, completion_tokens));
+        self
+    }
+
+    /// Builder: override the finish reason (defaults to `tool_calls` when the
+    /// reply has tool calls and `stop` otherwise).
+    pub fn with_finish_reason(mut self, reason: FinishReason) -> Self {
+        self.finish_reason = Some(reason);
+        self
+    }
+
+    fn finish_reason(&self) -> FinishReason {
+        self.finish_reason.unwrap_or(if self.tool_calls.is_empty() {
+            FinishReason::Stop
+        } else {
+            FinishReason::ToolCalls
+        })
+    }
+
+    fn usage(&self) -> Usage {
+        let (prompt, completion) = self.usage.unwrap_or_default();
+        Usage {
+            completion_tokens: completion,
+            prompt_tokens: prompt,
+            prompt_cache_hit_tokens: None,
+            prompt_cache_miss_tokens: None,
+            total_tokens: prompt + completion,
+            completion_tokens_details: None,
+        }
+    }
+
+    fn into_response(self, id: String, model: Model) -> ChatCompletionResponse {
+        let content = self.deltas.concat();
+        let reasoning = self.reasoning.concat();
+        let finish_reason = self.finish_reason();
+        let usage = self.usage();
+        let message = Message {
+            role: Role::Assistant,
+            content: (!content.is_empty() || self.tool_calls.is_empty()).then_some(content),
+            reasoning_content: (!reasoning.is_empty()).then_some(reasoning),
+            tool_calls: (!self.tool_calls.is_empty()).then_some(self.tool_calls),
+            ..Default::default()
+        };
+        ChatCompletionResponse {
+            id,
+            choices: vec![Choice {
+                finish_reason,
+                index: 0,
+                message,
+                logprobs: None,
+            }],
+            created: 0,
+            model,
+            system_fingerprint: None,
+            object: ObjectType::ChatCompletion,
+            usage,
+        }
+    }
+
+    fn into_chunks(self, id: String, model: Model) -> Vec<ChatCompletionChunk> {
+        let chunk = |delta: Delta, finish_reason, usage| ChatCompletionChunk {
+            id: id.clone(),
+            choices: vec![ChunkChoice {
+                index: 0,
+                delta,
+                finish_reason,
+                logprobs: None,
+            }],
+            created: 0,
+            model: model.as_str().to_string(),
+            system_fingerprint: None,
+            object: ChunkObjectType::ChatCompletionChunk,
+            usage,
+        };
+        let empty = || Delta {
+            content: None,
+            reasoning_content: None,
+            role: None,
+            tool_calls: None,
+        };
+
+        let finish_reason = self.finish_reason();
+        let usage = self.usage.map(|_| self.usage());
+        let mut chunks = Vec::new();
+        for r in self.reasoning {
+            let delta = Delta {
+                reasoning_content: Some(r),
+                ..empty()
+            };
+            chunks.push(chunk(delta, None, None));
+        }
+        for d in self.deltas {
+            let delta = Delta {
+                content: Some(d),
+                ..empty()
+            };
+            chunks.push(chunk(delta, None, None));
+        }
+        for (index, call) in self.tool_calls.into_iter().enumerate() {
+            let delta = Delta {
+                tool_calls: Some(vec![DeltaToolCall {
+                    index: index as u32,
+                    id: Some(call.id),
+                    r#type: Some(ToolType::Function),
+                    function: Some(DeltaFunctionCall {
+                        name: Some(call.function.name),
+                        arguments: Some(call.function.arguments),
+                    }),
+                }]),
+                ..empty()
+            };
+            chunks.push(chunk(delta, None, None));
+        }
+        chunks.push(chunk(empty(), Some(finish_reason), usage));
+        chunks
+    }
+}
+
+// ── MockBackend ───────────────────────────────────────────────────────────────
+
+enum Scripted {
+    Reply(MockReply),
+    Error(ApiError),
+}
+
+#[derive(Default)]
+struct MockState {
+    script: VecDeque<Scripted>,
+    requests: Vec<ChatCompletionRequest>,
+    served: usize,
+}
+
+/// A [`ChatBackend`] that replays a script of queued replies.
+///
+/// Clones share the same script and request log, so keep a clone around to
+/// inspect [`requests`][MockBackend::requests] after handing the backend to
+/// an agent.  When the script runs out, requests fail with
+/// [`ApiError::Other`].
+#[derive(Clone, Default)]
+pub struct MockBackend {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockBackend {
+    /// Create a backend with an empty script.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: queue a reply.
+    pub fn reply(self, reply: MockReply) -> Self {
+        self.push_reply(reply);
+        self
+    }
+
+    /// Builder: queue an error.
+    pub fn error(self, err: ApiError) -> Self {
+        self.push_error(err);
+        self
+    }
+
+    /// Queue a reply on a backend that is already in use.
+    pub fn push_reply(&self, reply: MockReply) {
+        self.lock().script.push_back(Scripted::Reply(reply));
+    }
+
+    /// Queue an error on a backend that is already in use.
+    pub fn push_error(&self, err: ApiError) {
+        self.lock().script.push_back(Scripted::Error(err));
+    }
+
+    /// Every request received so far, in order.
+    pub fn requests(&self) -> Vec<ChatCompletionRequest> {
+        self.lock().requests.clone()
+    }
+
+    /// Number of scripted entries not yet consumed.
+    pub fn remaining(&self) -> usize {
+        self.lock().script.len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record `req` and pop the next scripted entry.
+    fn next(&self, req: ApiRequest) -> Result<(MockReply, String, Model)> {
+        let raw = req.into_raw();
+        let model = raw.model.clone();
+        let mut state = self.lock();
+        state.requests.push(raw);
+        state.served += 1;
+        let id = format!("mock-{}", state.served);
+        match state.script.pop_front() {
+            Some(Scripted::Reply(reply)) => Ok((reply, id, model)),
+            Some(Scripted::Error(err)) => Err(err),
+            None => Err(ApiError::Other(
+                "MockBackend: no scripted reply left".to_string(),
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl ChatBackend for MockBackend {
+    async fn send(&self, req: ApiRequest) -> Result<ChatCompletionResponse> {
+        let (reply, id, model) = self.next(req)?;
+        Ok(reply.into_response(id, model))
+    }
+
+    async fn send_stream(&self, req: ApiRequest) -> Result<ChunkStream> {
+        let (reply, id, model) = self.next(req)?;
+        Ok(futures::stream::iter(reply.into_chunks(id, model).into_iter().map(Ok)).boxed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn replays_script_in_order_and_records_requests() {
+        let mock = MockBackend::new()
+            .reply(MockReply::text("one"))
+            .error(ApiError::Other("boom".into()));
+
+        let req = ApiRequest::builder().add_message(Message::user("hi"));
+        let resp = mock.send(req.clone()).await.unwrap();
+        assert_eq!(resp.content(), Some("one"));
+        assert!(mock.send(req.clone()).await.is_err());
+        assert!(mock.send(req).await.is_err());
+
+        assert_eq!(mock.requests().len(), 3);
+        assert_eq!(mock.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn streams_deltas_then_finish_chunk() {
+        let mock = MockBackend::new().reply(MockReply::deltas(["a", "b"]).with_usage(3, 2));
+
+        let chunks: Vec<_> = mock
+            .send_stream(ApiRequest::builder())
+            .await
+            .unwrap()
+            .collect()
+            .await;
+
+        assert_eq!(chunks.len(), 3);
+        let last = chunks.last().unwrap().as_ref().unwrap();
+        assert_eq!(last.choices[0].finish_reason, Some(FinishReason::Stop));
+        assert_eq!(last.usage.as_ref().unwrap().total_tokens, 5);
+    }
+
+    #[tokio::test]
+    async fn tool_call_reply_has_no_content() {
+        let mock = MockBackend::new().reply(MockReply::tool_call(
+            "c1",
+            "f",
+            serde_json::json!({ "x": 1 }),
+        ));
+
+        let resp = mock.send(ApiRequest::builder()).await.unwrap();
+        let choice = &resp.choices[0];
+        assert_eq!(choice.finish_reason, FinishReason::ToolCalls);
+        assert!(choice.message.content.is_none());
+        assert_eq!(
+            choice.message.tool_calls.as_ref().unwrap()[0]
+                .function
+                .arguments,
+            r#"{"x":1}"#
+        );
+    }
+}
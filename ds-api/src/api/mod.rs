@@ -2,8 +2,30 @@
 ds-api-workspace/ds-api/src/api.rs
 */
 
+pub mod backend;
+pub mod cassette;
 pub mod client;
+pub mod cost;
+pub mod fallback;
+pub mod fim;
+pub mod key_pool;
+pub mod mock;
+pub mod rate_limit;
 pub mod request;
+pub mod retry;
+pub mod stream_timeout;
+pub mod typed;
 
+pub use backend::{ChatBackend, ChunkStream};
+pub use cassette::{Cassette, CassetteMode};
 pub use client::ApiClient;
+pub use cost::{CostStats, CostTracker, ModelPrice, PriceTable};
+pub use fallback::{CircuitBreaker, Endpoint, EndpointStatus, FailoverRules, FallbackClient};
+pub use fim::FimRequest;
+pub use key_pool::{KeyPool, KeySelection, KeyStats};
+pub use mock::{MockBackend, MockReply};
+pub use rate_limit::{RateLimit, RateLimitStats};
 pub use request::ApiRequest;
+pub use retry::{RetryEvent, RetryPolicy};
+pub use stream_timeout::StreamTimeouts;
+pub use typed::{SchemaMode, StructuredOutput};
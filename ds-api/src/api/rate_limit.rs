@@ -0,0 +1,346 @@
+//! Client-side rate limiting.
+//!
+//! A [`RateLimit`] describes the quotas a client should stay under: requests
+//! per minute, estimated tokens per minute, and the number of requests allowed
+//! in flight at once.  Attaching it with
+//! [`ApiClient::with_rate_limit`][crate::api::ApiClient::with_rate_limit]
+//! creates a single limiter that lives inside the client, so every clone of the
+//! client — including the ones held by agents and
+//! [`LlmSummarizer`][crate::conversation::LlmSummarizer] — draws from the same
+//! budget.
+//!
+//! The per-minute quotas are enforced with token buckets that refill
+//! continuously.  Callers that cannot proceed wait in FIFO order; the number
+//! of waiting callers is available from
+//! [`ApiClient::rate_limit_stats`][crate::api::ApiClient::rate_limit_stats].
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+use tracing::debug;
+
+use crate::conversation::summarizer::{char_weight, message_weight};
+use crate::raw::{ChatCompletionRequest, CompletionRequest};
+
+/// Quotas enforced by the client before a request is sent.
+///
+/// Every limit is optional; an empty `RateLimit` never delays anything.
+///
+/// # Example
+///
+/// ```no_run
+/// use ds_api::{ApiClient, RateLimit};
+///
+/// let client = ApiClient::new("sk-...").with_rate_limit(
+///     RateLimit::new()
+///         .requests_per_minute(600)
+///         .tokens_per_minute(1_000_000)
+///         .max_in_flight(16),
+/// );
+///
+/// // Every clone shares the same limiter.
+/// let worker_client = client.clone();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RateLimit {
+    pub(crate) requests_per_minute: Option<u32>,
+    pub(crate) tokens_per_minute: Option<u32>,
+    pub(crate) max_in_flight: Option<usize>,
+}
+
+impl RateLimit {
+    /// Create an empty configuration (no limits).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: maximum number of requests started per minute.
+    pub fn requests_per_minute(mut self, n: u32) -> Self {
+        self.requests_per_minute = Some(n.max(1));
+        self
+    }
+
+    /// Builder: maximum number of estimated tokens (prompt + `max_tokens`)
+    /// per minute.
+    pub fn tokens_per_minute(mut self, n: u32) -> Self {
+        self.tokens_per_minute = Some(n.max(1));
+        self
+    }
+
+    /// Builder: maximum number of requests in flight at once.
+    ///
+    /// For streaming requests the slot is held until the stream is dropped.
+    pub fn max_in_flight(mut self, n: usize) -> Self {
+        self.max_in_flight = Some(n.max(1));
+        self
+    }
+}
+
+/// A point-in-time snapshot of a client's rate limiter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitStats {
+    /// Callers currently waiting for capacity.
+    pub queued: usize,
+    /// Requests currently holding an in-flight slot.
+    pub in_flight: usize,
+    /// Request budget available right now, if a request quota is set.
+    pub available_requests: Option<f64>,
+    /// Token budget available right now, if a token quota is set.
+    pub available_tokens: Option<f64>,
+}
+
+/// A continuously refilling token bucket.
+///
+/// `available` may go negative when a request turns out to be larger than
+/// estimated; the debt is paid back by the refill.
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    per_sec: f64,
+    available: f64,
+}
+
+impl Bucket {
+    fn per_minute(n: u32) -> Self {
+        let capacity = n as f64;
+        Self {
+            capacity,
+            per_sec: capacity / 60.0,
+            available: capacity,
+        }
+    }
+
+    fn refill(&mut self, elapsed: Duration) {
+        self.available = (self.available + elapsed.as_secs_f64() * self.per_sec).min(self.capacity);
+    }
+
+    /// Time until `amount` is available, or zero if it already is.
+    fn wait_for(&self, amount: f64) -> Duration {
+        let missing = amount.min(self.capacity) - self.available;
+        if missing <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(missing / self.per_sec)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Buckets {
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+    refilled_at: Instant,
+}
+
+impl Buckets {
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now - self.refilled_at;
+        self.refilled_at = now;
+        for b in [&mut self.requests, &mut self.tokens].into_iter().flatten() {
+            b.refill(elapsed);
+        }
+    }
+}
+
+/// The shared limiter stored inside an `ApiClient`.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    buckets: Mutex<Buckets>,
+    /// FIFO gate: the waiter at the head of the queue holds it while it waits
+    /// for the buckets to refill.
+    gate: Mutex<()>,
+    slots: Option<(Arc<Semaphore>, usize)>,
+    queued: AtomicUsize,
+}
+
+/// Proof that a request was admitted by the limiter.
+///
+/// Holds the in-flight slot (if any) until dropped.
+#[derive(Debug)]
+pub(crate) struct Permit {
+    _slot: Option<OwnedSemaphorePermit>,
+}
+
+impl Permit {
+    /// A permit for a client without a limiter.
+    pub(crate) fn unlimited() -> Self {
+        Self { _slot: None }
+    }
+}
+
+/// Decrements the queue counter when a waiter leaves, even if it is cancelled.
+struct QueueGuard<'a>(&'a AtomicUsize);
+
+impl Drop for QueueGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: &RateLimit) -> Self {
+        Self {
+            buckets: Mutex::new(Buckets {
+                requests: config.requests_per_minute.map(Bucket::per_minute),
+                tokens: config.tokens_per_minute.map(Bucket::per_minute),
+                refilled_at: Instant::now(),
+            }),
+            gate: Mutex::new(()),
+            slots: config
+                .max_in_flight
+                .map(|n| (Arc::new(Semaphore::new(n)), n)),
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    /// Wait until one request costing `tokens` estimated tokens may start.
+    pub(crate) async fn acquire(&self, tokens: u32) -> Permit {
+        self.queued.fetch_add(1, Ordering::SeqCst);
+        let _queued = QueueGuard(&self.queued);
+
+        let slot = match &self.slots {
+            Some((sem, _)) => Some(
+                sem.clone()
+                    .acquire_owned()
+                    .await
+                    .expect("rate limiter semaphore is never closed"),
+            ),
+            None => None,
+        };
+
+        let _gate = self.gate.lock().await;
+        loop {
+            let wait = {
+                let mut b = self.buckets.lock().await;
+                b.refill();
+                let wait = b
+                    .requests
+                    .as_ref()
+                    .map_or(Duration::ZERO, |r| r.wait_for(1.0))
+                    .max(
+                        b.tokens
+                            .as_ref()
+                            .map_or(Duration::ZERO, |t| t.wait_for(tokens as f64)),
+                    );
+                if wait.is_zero() {
+                    if let Some(r) = b.requests.as_mut() {
+                        r.available -= 1.0;
+                    }
+                    if let Some(t) = b.tokens.as_mut() {
+                        t.available -= tokens as f64;
+                    }
+                }
+                wait
+            };
+            if wait.is_zero() {
+                break;
+            }
+            debug!(
+                wait_ms = wait.as_millis() as u64,
+                tokens, "rate limited; waiting"
+            );
+            tokio::time::sleep(wait).await;
+        }
+
+        Permit { _slot: slot }
+    }
+
+    /// Correct the token bucket once the real usage of a request is known.
+    pub(crate) async fn reconcile(&self, estimated: u32, actual: u32) {
+        let mut b = self.buckets.lock().await;
+        if let Some(t) = b.tokens.as_mut() {
+            t.available += estimated as f64 - actual as f64;
+            t.available = t.available.min(t.capacity);
+        }
+    }
+
+    pub(crate) fn stats(&self) -> RateLimitStats {
+        let (available_requests, available_tokens) = match self.buckets.try_lock() {
+            Ok(mut b) => {
+                b.refill();
+                (
+                    b.requests.as_ref().map(|r| r.available),
+                    b.tokens.as_ref().map(|t| t.available),
+                )
+            }
+            Err(_) => (None, None),
+        };
+        RateLimitStats {
+            queued: self.queued.load(Ordering::SeqCst),
+            in_flight: self
+                .slots
+                .as_ref()
+                .map_or(0, |(sem, n)| n - sem.available_permits()),
+            available_requests,
+            available_tokens,
+        }
+    }
+}
+
+/// Estimate the tokens a request will consume: the prompt (by the same
+/// character heuristic the summarizer uses) plus the `max_tokens` reservation.
+pub(crate) fn estimate_request_tokens(raw: &ChatCompletionRequest) -> u32 {
+    let prompt = raw.messages.iter().map(message_weight).sum::<usize>() / 4;
+    (prompt as u32).saturating_add(raw.max_tokens.unwrap_or(0))
+}
+
+/// Estimate the tokens a FIM completion request will consume: prompt and
+/// suffix plus the `max_tokens` reservation.
+pub(crate) fn estimate_completion_tokens(raw: &CompletionRequest) -> u32 {
+    let prompt = (char_weight(&raw.prompt) + raw.suffix.as_deref().map_or(0, char_weight)) / 4;
+    (prompt as u32).saturating_add(raw.max_tokens.unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn request_bucket_delays_once_exhausted() {
+        let limiter = RateLimiter::new(&RateLimit::new().requests_per_minute(2));
+        let start = Instant::now();
+
+        limiter.acquire(0).await;
+        limiter.acquire(0).await;
+        assert!(start.elapsed() < Duration::from_millis(1));
+
+        // Third request has to wait for half a request-minute (30 s) to refill.
+        limiter.acquire(0).await;
+        assert!(start.elapsed() >= Duration::from_secs(30));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn oversized_token_requests_are_clamped_to_capacity() {
+        let limiter = RateLimiter::new(&RateLimit::new().tokens_per_minute(100));
+        // Would never fit otherwise.
+        limiter.acquire(1_000).await;
+    }
+
+    #[tokio::test]
+    async fn in_flight_slots_are_released_on_drop() {
+        let limiter = RateLimiter::new(&RateLimit::new().max_in_flight(1));
+        let permit = limiter.acquire(0).await;
+        assert_eq!(limiter.stats().in_flight, 1);
+        drop(permit);
+        assert_eq!(limiter.stats().in_flight, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn queued_counts_waiters() {
+        let limiter = Arc::new(RateLimiter::new(&RateLimit::new().max_in_flight(1)));
+        let held = limiter.acquire(0).await;
+
+        let l = limiter.clone();
+        let waiter = tokio::spawn(async move { l.acquire(0).await });
+        tokio::task::yield_now().await;
+        assert_eq!(limiter.stats().queued, 1);
+
+        drop(held);
+        waiter.await.unwrap();
+        assert_eq!(limiter.stats().queued, 0);
+    }
+}
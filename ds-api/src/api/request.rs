@@ -3,7 +3,21 @@
 //! Provides a safe, chainable request builder that wraps the internal
 //! `crate::raw::ChatCompletionRequest`.
 
-use crate::raw::{ChatCompletionRequest, Message, ResponseFormat, ResponseFormatType, Tool};
+use std::ops::RangeInclusive;
+
+use crate::error::{ApiError, Result};
+use crate::raw::{
+    ChatCompletionRequest, FunctionName, JsonSchemaFormat, Message, ResponseFormat,
+    ResponseFormatType, Role, Stop, StreamOptions, Thinking, ThinkingType, Tool, ToolChoice,
+    ToolChoiceObject, ToolChoiceType, ToolType,
+};
+
+/// Most stop sequences the API accepts.
+const MAX_STOP_SEQUENCES: usize = 16;
+/// Most tools the API accepts.
+const MAX_TOOLS: usize = 128;
+/// Largest `top_logprobs` the API accepts.
+const MAX_TOP_LOGPROBS: u32 = 20;
 
 /// A safe, chainable request builder that wraps `ChatCompletionRequest`.
 ///
@@ -11,9 +25,14 @@ use crate::raw::{ChatCompletionRequest, Message, ResponseFormat, ResponseFormatT
 /// or the convenience constructors [`deepseek_chat`][ApiRequest::deepseek_chat]
 /// and [`deepseek_reasoner`][ApiRequest::deepseek_reasoner] for the standard
 /// DeepSeek models.
-#[derive(Debug)]
+///
+/// Setters never fail; call [`validate`][ApiRequest::validate] to check value
+/// ranges.  [`ApiClient`][crate::api::ApiClient] validates every request
+/// before sending it and returns [`ApiError::Validation`] instead of a 400
+/// from the server.
+#[derive(Debug, Clone)]
 pub struct ApiRequest {
-    raw: ChatCompletionRequest,
+    pub(crate) raw: ChatCompletionRequest,
 }
 
 impl ApiRequest {
@@ -62,6 +81,26 @@ impl ApiRequest {
         self
     }
 
+    /// Append an assistant prefix message that the model must continue from
+    /// (beta chat prefix completion).
+    ///
+    /// Call this last: the prefix has to be the final message.  Requests with
+    /// a prefix are sent to the beta endpoint
+    /// (see [`ApiClient::with_beta_base_url`][crate::api::ApiClient::with_beta_base_url])
+    /// and the reply contains only the continuation.
+    pub fn assistant_prefix(mut self, text: impl Into<String>) -> Self {
+        self.raw
+            .messages
+            .push(Message::assistant_prefix(&text.into()));
+        self
+    }
+
+    /// Returns `true` if the last message is an assistant prefix, i.e. the
+    /// request needs the beta endpoint.
+    pub fn has_assistant_prefix(&self) -> bool {
+        self.raw.messages.last().is_some_and(Message::is_prefix)
+    }
+
     /// Replace messages.
     pub fn messages(mut self, msgs: Vec<Message>) -> Self {
         self.raw.messages = msgs;
@@ -72,6 +111,28 @@ impl ApiRequest {
     pub fn json(mut self) -> Self {
         self.raw.response_format = Some(ResponseFormat {
             r#type: ResponseFormatType::JsonObject,
+            json_schema: None,
+        });
+        self
+    }
+
+    /// Request a response constrained by a JSON Schema (`json_schema` mode,
+    /// strict).  Only for OpenAI-compatible providers that support it; the
+    /// DeepSeek API accepts [`json`][Self::json] only.
+    ///
+    /// The schema is sent as given, so it must meet the provider's strict-mode
+    /// rules (typically: `additionalProperties: false` and every property
+    /// required).  [`send_typed`][crate::api::ApiClient::send_typed] prepares
+    /// schemas generated from Rust types this way.
+    pub fn json_schema(mut self, name: impl Into<String>, schema: serde_json::Value) -> Self {
+        self.raw.response_format = Some(ResponseFormat {
+            r#type: ResponseFormatType::JsonSchema,
+            json_schema: Some(JsonSchemaFormat {
+                name: name.into(),
+                description: None,
+                schema,
+                strict: Some(true),
+            }),
         });
         self
     }
@@ -80,22 +141,86 @@ impl ApiRequest {
     pub fn text(mut self) -> Self {
         self.raw.response_format = Some(ResponseFormat {
             r#type: ResponseFormatType::Text,
+            json_schema: None,
         });
         self
     }
 
-    /// Set temperature.
+    /// Set temperature (0–2).
     pub fn temperature(mut self, t: f32) -> Self {
         self.raw.temperature = Some(t);
         self
     }
 
+    /// Set nucleus sampling probability mass (0–1).
+    pub fn top_p(mut self, p: f32) -> Self {
+        self.raw.top_p = Some(p);
+        self
+    }
+
+    /// Set the frequency penalty (-2–2).
+    pub fn frequency_penalty(mut self, v: f32) -> Self {
+        self.raw.frequency_penalty = Some(v);
+        self
+    }
+
+    /// Set the presence penalty (-2–2).
+    pub fn presence_penalty(mut self, v: f32) -> Self {
+        self.raw.presence_penalty = Some(v);
+        self
+    }
+
     /// Set max tokens.
     pub fn max_tokens(mut self, n: u32) -> Self {
         self.raw.max_tokens = Some(n);
         self
     }
 
+    /// Stop generating when any of the given sequences (at most 16) is
+    /// produced.
+    pub fn stop<I, S>(mut self, sequences: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.raw.stop = Some(Stop::Array(sequences.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// Enable or disable thinking (reasoning) mode.
+    pub fn thinking(mut self, enabled: bool) -> Self {
+        self.raw.thinking = Some(Thinking {
+            r#type: if enabled {
+                ThinkingType::Enabled
+            } else {
+                ThinkingType::Disabled
+            },
+        });
+        self
+    }
+
+    /// Return log-probabilities of the output tokens.
+    pub fn logprobs(mut self, enabled: bool) -> Self {
+        self.raw.logprobs = Some(enabled);
+        self
+    }
+
+    /// Return the `n` (at most 20) most likely tokens at each position.
+    /// Also enables [`logprobs`][Self::logprobs].
+    pub fn top_logprobs(mut self, n: u32) -> Self {
+        self.raw.logprobs = Some(true);
+        self.raw.top_logprobs = Some(n);
+        self
+    }
+
+    /// Ask for a final chunk carrying `usage` when streaming.
+    pub fn include_usage(mut self, enabled: bool) -> Self {
+        self.raw.stream_options = Some(StreamOptions {
+            include_usage: enabled,
+        });
+        self
+    }
+
     /// Add a raw tool definition (from `crate::raw::Tool`).
     pub fn add_tool(mut self, tool: Tool) -> Self {
         if let Some(ref mut v) = self.raw.tools {
@@ -108,11 +233,32 @@ impl ApiRequest {
 
     /// Set tool choice to Auto.
     pub fn tool_choice_auto(mut self) -> Self {
-        use crate::raw::request::tool_choice::{ToolChoice, ToolChoiceType};
         self.raw.tool_choice = Some(ToolChoice::String(ToolChoiceType::Auto));
         self
     }
 
+    /// Set tool choice to None: the model answers without calling tools.
+    pub fn tool_choice_none(mut self) -> Self {
+        self.raw.tool_choice = Some(ToolChoice::String(ToolChoiceType::None));
+        self
+    }
+
+    /// Set tool choice to Required: the model must call at least one tool.
+    pub fn tool_choice_required(mut self) -> Self {
+        self.raw.tool_choice = Some(ToolChoice::String(ToolChoiceType::Required));
+        self
+    }
+
+    /// Force the model to call the function `name`, which must be one of the
+    /// request's tools.
+    pub fn tool_choice_function(mut self, name: impl Into<String>) -> Self {
+        self.raw.tool_choice = Some(ToolChoice::Object(ToolChoiceObject {
+            r#type: ToolType::Function,
+            function: FunctionName { name: name.into() },
+        }));
+        self
+    }
+
     /// Enable/disable streaming (stream: true).
     pub fn stream(mut self, enabled: bool) -> Self {
         self.raw.stream = Some(enabled);
@@ -185,8 +331,262 @@ impl ApiRequest {
         self.with_extra_field(key, value)
     }
 
-    /// Build and return the internal raw request (crate-internal use).
-    pub(crate) fn into_raw(self) -> ChatCompletionRequest {
+    /// Check every field against the ranges the API accepts.
+    ///
+    /// Returns the first problem found as [`ApiError::Validation`].
+    ///
+    /// ```
+    /// use ds_api::{ApiError, ApiRequest};
+    ///
+    /// let err = ApiRequest::builder().temperature(3.0).validate().unwrap_err();
+    /// assert!(matches!(err, ApiError::Validation { field: "temperature", .. }));
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        let raw = &self.raw;
+        check_range("temperature", raw.temperature, 0.0..=2.0)?;
+        check_range("top_p", raw.top_p, 0.0..=1.0)?;
+        check_range("frequency_penalty", raw.frequency_penalty, -2.0..=2.0)?;
+        check_range("presence_penalty", raw.presence_penalty, -2.0..=2.0)?;
+
+        if let Some(pos) = raw.messages.iter().position(|m| m.prefix == Some(true))
+            && (pos + 1 != raw.messages.len() || !raw.messages[pos].is_prefix())
+        {
+            return Err(invalid(
+                "messages",
+                "a prefix message must be the last message and have the assistant role",
+            ));
+        }
+        for m in &raw.messages {
+            if m.parts.as_ref().is_some_and(Vec::is_empty) {
+                return Err(invalid("messages", "content parts must not be empty"));
+            }
+            if m.image_count() > 0 && !matches!(m.role, Role::User) {
+                return Err(invalid("messages", "only user messages may contain images"));
+            }
+        }
+        if raw.max_tokens == Some(0) {
+            return Err(invalid("max_tokens", "must be at least 1"));
+        }
+        if let Some(Stop::Array(stops)) = &raw.stop
+            && stops.len() > MAX_STOP_SEQUENCES
+        {
+            return Err(invalid(
+                "stop",
+                format!(
+                    "at most {MAX_STOP_SEQUENCES} sequences are allowed, got {}",
+                    stops.len()
+                ),
+            ));
+        }
+        if let Some(n) = raw.top_logprobs {
+            if n > MAX_TOP_LOGPROBS {
+                return Err(invalid(
+                    "top_logprobs",
+                    format!("must be at most {MAX_TOP_LOGPROBS}, got {n}"),
+                ));
+            }
+            if raw.logprobs != Some(true) {
+                return Err(invalid("top_logprobs", "requires `logprobs` to be true"));
+            }
+        }
+        if raw.stream_options.is_some() && raw.stream != Some(true) {
+            return Err(invalid(
+                "stream_options",
+                "only allowed on streaming requests",
+            ));
+        }
+        if let Some(format) = &raw.response_format
+            && matches!(format.r#type, ResponseFormatType::JsonSchema)
+        {
+            let Some(schema) = &format.json_schema else {
+                return Err(invalid(
+                    "response_format",
+                    "`json_schema` mode needs a schema",
+                ));
+            };
+            if schema.name.is_empty()
+                || !schema
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                return Err(invalid(
+                    "response_format",
+                    format!("invalid schema name {:?}", schema.name),
+                ));
+            }
+        }
+
+        let tools = raw.tools.as_deref().unwrap_or_default();
+        if tools.len() > MAX_TOOLS {
+            return Err(invalid(
+                "tools",
+                format!("at most {MAX_TOOLS} tools are allowed, got {}", tools.len()),
+            ));
+        }
+        if let Some(ToolChoice::Object(choice)) = &raw.tool_choice
+            && !tools
+                .iter()
+                .any(|t| t.function.name == choice.function.name)
+        {
+            return Err(invalid(
+                "tool_choice",
+                format!("no tool named {:?} in the request", choice.function.name),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Borrow the underlying raw request.
+    ///
+    /// Useful for [`ChatBackend`][crate::api::ChatBackend] implementations
+    /// that need to inspect or translate the request.
+    pub fn as_raw(&self) -> &ChatCompletionRequest {
+        &self.raw
+    }
+
+    /// Build and return the underlying raw request.
+    pub fn into_raw(self) -> ChatCompletionRequest {
         self.raw
     }
 }
+
+/// Build an [`ApiError::Validation`].
+pub(crate) fn invalid(field: &'static str, message: impl Into<String>) -> ApiError {
+    ApiError::Validation {
+        field,
+        message: message.into(),
+    }
+}
+
+/// Fail if `value` is set and outside `range` (or NaN).
+pub(crate) fn check_range(
+    field: &'static str,
+    value: Option<f32>,
+    range: RangeInclusive<f32>,
+) -> Result<()> {
+    match value {
+        Some(v) if !range.contains(&v) => Err(invalid(
+            field,
+            format!(
+                "must be between {} and {}, got {v}",
+                range.start(),
+                range.end()
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::Function;
+    use serde_json::json;
+
+    fn field_of(req: ApiRequest) -> &'static str {
+        match req.validate() {
+            Err(ApiError::Validation { field, .. }) => field,
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn accepts_values_at_the_bounds() {
+        ApiRequest::builder()
+            .temperature(2.0)
+            .top_p(0.0)
+            .frequency_penalty(-2.0)
+            .presence_penalty(2.0)
+            .top_logprobs(20)
+            .stop(["a"; 16])
+            .validate()
+            .unwrap();
+    }
+
+    #[test]
+    fn rejects_out_of_range_values() {
+        assert_eq!(
+            field_of(ApiRequest::builder().temperature(2.1)),
+            "temperature"
+        );
+        assert_eq!(
+            field_of(ApiRequest::builder().temperature(f32::NAN)),
+            "temperature"
+        );
+        assert_eq!(field_of(ApiRequest::builder().top_p(1.5)), "top_p");
+        assert_eq!(
+            field_of(ApiRequest::builder().frequency_penalty(-2.5)),
+            "frequency_penalty"
+        );
+        assert_eq!(
+            field_of(ApiRequest::builder().presence_penalty(3.0)),
+            "presence_penalty"
+        );
+        assert_eq!(field_of(ApiRequest::builder().max_tokens(0)), "max_tokens");
+        assert_eq!(field_of(ApiRequest::builder().stop(["a"; 17])), "stop");
+        assert_eq!(
+            field_of(ApiRequest::builder().top_logprobs(21)),
+            "top_logprobs"
+        );
+        assert_eq!(
+            field_of(ApiRequest::builder().top_logprobs(5).logprobs(false)),
+            "top_logprobs"
+        );
+        assert_eq!(
+            field_of(ApiRequest::builder().include_usage(true)),
+            "stream_options"
+        );
+    }
+
+    #[test]
+    fn named_tool_choice_must_match_a_tool() {
+        let tool = Tool {
+            r#type: ToolType::Function,
+            function: Function {
+                name: "lookup".to_string(),
+                description: None,
+                parameters: json!({ "type": "object" }),
+                strict: None,
+            },
+        };
+        let req = ApiRequest::builder().add_tool(tool);
+        req.clone()
+            .tool_choice_function("lookup")
+            .validate()
+            .unwrap();
+        assert_eq!(field_of(req.tool_choice_function("other")), "tool_choice");
+    }
+
+    #[test]
+    fn prefix_must_be_the_last_assistant_message() {
+        let req = ApiRequest::builder()
+            .add_message(Message::user("hi"))
+            .assistant_prefix("```");
+        assert!(req.has_assistant_prefix());
+        req.clone().validate().unwrap();
+        assert_eq!(
+            field_of(req.add_message(Message::user("again"))),
+            "messages"
+        );
+
+        let mut user_prefix = Message::user("hi");
+        user_prefix.prefix = Some(true);
+        let req = ApiRequest::builder().add_message(user_prefix);
+        assert!(!req.has_assistant_prefix());
+        assert_eq!(field_of(req), "messages");
+    }
+
+    #[test]
+    fn serializes_new_fields() {
+        let raw = ApiRequest::builder()
+            .thinking(false)
+            .tool_choice_required()
+            .stop(["END"])
+            .into_raw();
+        let v = serde_json::to_value(&raw).unwrap();
+        assert_eq!(v["thinking"], json!({ "type": "disabled" }));
+        assert_eq!(v["tool_choice"], json!("required"));
+        assert_eq!(v["stop"], json!(["END"]));
+    }
+}
@@ -0,0 +1,320 @@
+//! Retry policy for transient API failures.
+//!
+//! [`RetryPolicy`] decides whether a failed request should be attempted again
+//! and how long to wait before doing so.  It is attached to an
+//! [`ApiClient`][crate::api::ApiClient] via
+//! [`with_retry`][crate::api::ApiClient::with_retry] and applies to every
+//! request the client makes — `send`, `send_stream` / `into_stream` (at
+//! connect time only; a stream that fails half-way is never replayed), and the
+//! internal calls made by [`LlmSummarizer`][crate::conversation::LlmSummarizer]
+//! when it shares the client.
+//!
+//! Every retry is logged as a `tracing` warning.  When the request is made on
+//! behalf of an agent, the retry is also surfaced as
+//! [`AgentEvent::Retry`][crate::agent::AgentEvent::Retry].
+
+use std::future::Future;
+use std::time::{Duration, SystemTime};
+
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use tokio::sync::mpsc;
+
+use crate::error::{ApiError, ErrorKind};
+
+/// Describes a single retry that is about to happen.
+///
+/// Emitted as a `tracing` event by the client and, inside an agent run, as
+/// [`AgentEvent::Retry`][crate::agent::AgentEvent::Retry].
+#[derive(Debug, Clone)]
+pub struct RetryEvent {
+    /// The 1-based attempt that just failed.
+    pub attempt: u32,
+    /// Total number of attempts allowed by the policy.
+    pub max_attempts: u32,
+    /// How long the client will wait before the next attempt.
+    pub delay: Duration,
+    /// HTTP status of the failed attempt, if the server answered at all.
+    pub status: Option<StatusCode>,
+    /// Human-readable description of the failure.
+    pub error: String,
+}
+
+/// Configurable retry behaviour with jittered exponential backoff.
+///
+/// The delay before retry `n` (1-based) is
+/// `min(max_backoff, initial_backoff * multiplier^(n-1))`.  With jitter
+/// enabled (the default) the actual delay is drawn uniformly from the upper
+/// half of that range, so many clients hitting the same rate limit do not
+/// retry in lock-step.
+///
+/// When the server sends a `Retry-After` header (seconds or HTTP date) its
+/// value is used instead of the computed backoff.  If it asks for longer than
+/// [`max_retry_after`][RetryPolicy::max_retry_after] the request is not
+/// retried and the original error is returned.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use ds_api::{ApiClient, RetryPolicy};
+///
+/// let client = ApiClient::new("sk-...").with_retry(
+///     RetryPolicy::new()
+///         .max_attempts(5)
+///         .initial_backoff(Duration::from_millis(250)),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub(crate) max_attempts: u32,
+    pub(crate) initial_backoff: Duration,
+    pub(crate) max_backoff: Duration,
+    pub(crate) multiplier: f64,
+    pub(crate) jitter: bool,
+    pub(crate) max_retry_after: Duration,
+    pub(crate) retryable_statuses: Vec<StatusCode>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy with sensible defaults: 3 attempts, 500 ms initial
+    /// backoff doubling up to 30 s, jitter on, and retries on 408, 429, 500,
+    /// 502, 503 and 504.
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: true,
+            max_retry_after: Duration::from_secs(60),
+            retryable_statuses: vec![
+                StatusCode::REQUEST_TIMEOUT,
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+        }
+    }
+
+    /// A policy that never retries (a single attempt).
+    pub fn none() -> Self {
+        Self::new().max_attempts(1)
+    }
+
+    /// Builder: total number of attempts, including the first one.
+    ///
+    /// Values below 1 are clamped to 1.
+    pub fn max_attempts(mut self, n: u32) -> Self {
+        self.max_attempts = n.max(1);
+        self
+    }
+
+    /// Builder: delay before the first retry.
+    pub fn initial_backoff(mut self, d: Duration) -> Self {
+        self.initial_backoff = d;
+        self
+    }
+
+    /// Builder: upper bound for the computed backoff.
+    pub fn max_backoff(mut self, d: Duration) -> Self {
+        self.max_backoff = d;
+        self
+    }
+
+    /// Builder: growth factor applied to the backoff after each attempt.
+    pub fn multiplier(mut self, m: f64) -> Self {
+        self.multiplier = m.max(1.0);
+        self
+    }
+
+    /// Builder: enable or disable jitter (enabled by default).
+    pub fn jitter(mut self, enabled: bool) -> Self {
+        self.jitter = enabled;
+        self
+    }
+
+    /// Builder: longest `Retry-After` value the client is willing to honour.
+    pub fn max_retry_after(mut self, d: Duration) -> Self {
+        self.max_retry_after = d;
+        self
+    }
+
+    /// Builder: replace the set of HTTP status codes that are retried.
+    pub fn retryable_statuses(mut self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.retryable_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Returns `true` if a response with this status should be retried.
+    pub fn is_retryable_status(&self, status: StatusCode) -> bool {
+        self.retryable_statuses.contains(&status)
+    }
+
+    /// Returns `true` if `err` represents a transient failure worth retrying.
+    ///
+    /// HTTP errors are classified by status code, except that an exhausted
+    /// balance or quota is never retried.  Other errors follow
+    /// [`ApiError::is_retryable`].
+    pub fn is_retryable(&self, err: &ApiError) -> bool {
+        match err {
+            ApiError::Http { status, .. } => {
+                self.is_retryable_status(*status) && err.kind() != ErrorKind::InsufficientBalance
+            }
+            _ => err.is_retryable(),
+        }
+    }
+
+    /// Compute the exponential backoff before retry number `attempt` (1-based),
+    /// including jitter when enabled.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self.multiplier.powi(attempt.saturating_sub(1) as i32);
+        let base = self.initial_backoff.as_secs_f64() * exp;
+        let capped = base.min(self.max_backoff.as_secs_f64());
+        let secs = if self.jitter {
+            capped / 2.0 + capped / 2.0 * jitter_fraction()
+        } else {
+            capped
+        };
+        Duration::from_secs_f64(secs)
+    }
+
+    /// Decide whether the failed attempt `attempt` (1-based) should be retried
+    /// and, if so, how long to wait first.
+    pub(crate) fn next_delay(
+        &self,
+        attempt: u32,
+        err: &ApiError,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts || !self.is_retryable(err) {
+            return None;
+        }
+        match retry_after {
+            Some(d) if d > self.max_retry_after => None,
+            Some(d) => Some(d),
+            None => Some(self.backoff(attempt)),
+        }
+    }
+}
+
+/// Parse a `Retry-After` header given either as delay-seconds or an HTTP date.
+pub(crate) fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = httpdate::parse_http_date(value).ok()?;
+    Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+/// A cheap pseudo-random number in `[0, 1)`; good enough for backoff jitter.
+fn jitter_fraction() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// ── Retry notifications ───────────────────────────────────────────────────────
+
+tokio::task_local! {
+    static RETRY_LISTENER: mpsc::UnboundedSender<RetryEvent>;
+}
+
+/// Run `fut` with `tx` installed as the retry listener for the current task.
+///
+/// Any retry performed by an [`ApiClient`][crate::api::ApiClient] while `fut`
+/// is being polled is forwarded to `tx`.  This is how the agent state machine
+/// learns about retries without every backend having to thread a channel
+/// through its API.
+pub(crate) async fn with_retry_listener<F: Future>(
+    tx: mpsc::UnboundedSender<RetryEvent>,
+    fut: F,
+) -> F::Output {
+    RETRY_LISTENER.scope(tx, fut).await
+}
+
+/// Forward `event` to the listener installed for the current task, if any.
+pub(crate) fn notify_retry(event: &RetryEvent) {
+    let _ = RETRY_LISTENER.try_with(|tx| tx.send(event.clone()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn backoff_grows_and_is_capped() {
+        let p = RetryPolicy::new()
+            .jitter(false)
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(350));
+        assert_eq!(p.backoff(1), Duration::from_millis(100));
+        assert_eq!(p.backoff(2), Duration::from_millis(200));
+        assert_eq!(p.backoff(3), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn jittered_backoff_stays_in_upper_half() {
+        let p = RetryPolicy::new().initial_backoff(Duration::from_millis(1000));
+        for _ in 0..50 {
+            let d = p.backoff(1);
+            assert!(d >= Duration::from_millis(500) && d <= Duration::from_millis(1000));
+        }
+    }
+
+    #[test]
+    fn next_delay_respects_attempts_and_status() {
+        let p = RetryPolicy::new().max_attempts(2).jitter(false);
+        let busy = ApiError::http_error(StatusCode::SERVICE_UNAVAILABLE, "busy");
+        let bad = ApiError::http_error(StatusCode::BAD_REQUEST, "bad");
+
+        assert!(p.next_delay(1, &busy, None).is_some());
+        assert!(p.next_delay(2, &busy, None).is_none());
+        assert!(p.next_delay(1, &bad, None).is_none());
+    }
+
+    #[test]
+    fn retry_after_overrides_backoff_up_to_limit() {
+        let p = RetryPolicy::new().max_retry_after(Duration::from_secs(10));
+        let err = ApiError::http_error(StatusCode::TOO_MANY_REQUESTS, "slow down");
+
+        assert_eq!(
+            p.next_delay(1, &err, Some(Duration::from_secs(3))),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(p.next_delay(1, &err, Some(Duration::from_secs(30))), None);
+    }
+
+    #[test]
+    fn parses_retry_after_seconds_and_dates() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(7)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(parse_retry_after(&headers), None);
+    }
+}
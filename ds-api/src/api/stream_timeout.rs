@@ -0,0 +1,253 @@
+//! Timeouts for streaming (SSE) responses.
+//!
+//! [`ApiClient::with_timeout`][crate::api::ApiClient::with_timeout] bounds a
+//! whole non-streaming request, which makes no sense for a stream that may
+//! legitimately run for minutes.  [`StreamTimeouts`] instead bounds the parts
+//! of a stream that indicate a stall:
+//!
+//! - **first byte** — from the start of the request until the first chunk;
+//! - **idle** — between two consecutive chunks;
+//! - **total** — from the start of the request until the stream ends.
+//!
+//! When a timeout fires, the stream yields the matching [`ApiError`] variant
+//! and ends.
+
+use std::time::Duration;
+
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::error::ApiError;
+
+/// Timeouts applied to streaming requests; every limit is optional.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use ds_api::{ApiClient, StreamTimeouts};
+///
+/// let client = ApiClient::new("sk-...").with_stream_timeouts(
+///     StreamTimeouts::new()
+///         .first_byte(Duration::from_secs(30))
+///         .idle(Duration::from_secs(20))
+///         .total(Duration::from_secs(600)),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StreamTimeouts {
+    pub(crate) first_byte: Option<Duration>,
+    pub(crate) idle: Option<Duration>,
+    pub(crate) total: Option<Duration>,
+}
+
+impl StreamTimeouts {
+    /// Create an empty configuration (no timeouts).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: maximum time from sending the request until the first chunk
+    /// arrives.  Covers connecting and waiting for response headers, so a
+    /// connect that exceeds it can be retried by the client's
+    /// [`RetryPolicy`][crate::api::RetryPolicy].
+    pub fn first_byte(mut self, d: Duration) -> Self {
+        self.first_byte = Some(d);
+        self
+    }
+
+    /// Builder: maximum gap between two chunks.  Also applies before the
+    /// first chunk when no [`first_byte`][Self::first_byte] timeout is set.
+    pub fn idle(mut self, d: Duration) -> Self {
+        self.idle = Some(d);
+        self
+    }
+
+    /// Builder: maximum duration of the whole stream, measured from sending
+    /// the request.  Connecting and waiting for response headers count
+    /// towards it.
+    pub fn total(mut self, d: Duration) -> Self {
+        self.total = Some(d);
+        self
+    }
+
+    /// The limit on connecting and receiving the response headers: the
+    /// earlier of [`first_byte`][Self::first_byte] and
+    /// [`total`][Self::total].
+    pub(crate) fn connect_limit(&self) -> Option<Limit> {
+        let first_byte = self.first_byte.map(Limit::FirstByte);
+        let total = self.total.map(Limit::Total);
+        first_byte
+            .into_iter()
+            .chain(total)
+            .min_by_key(|l| l.duration())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Limit {
+    FirstByte(Duration),
+    Idle(Duration),
+    Total(Duration),
+}
+
+impl Limit {
+    pub(crate) fn duration(self) -> Duration {
+        match self {
+            Limit::FirstByte(d) | Limit::Idle(d) | Limit::Total(d) => d,
+        }
+    }
+
+    pub(crate) fn error(self) -> ApiError {
+        match self {
+            Limit::FirstByte(d) => ApiError::StreamFirstByteTimeout(d),
+            Limit::Idle(d) => ApiError::StreamIdleTimeout(d),
+            Limit::Total(d) => ApiError::StreamDeadlineExceeded(d),
+        }
+    }
+}
+
+struct Timed<T> {
+    inner: BoxStream<'static, Result<T, ApiError>>,
+    timeouts: StreamTimeouts,
+    started: Instant,
+    seen_first: bool,
+    done: bool,
+}
+
+impl<T> Timed<T> {
+    /// The deadline that will fire first for the next chunk, if any.
+    fn next_deadline(&self) -> Option<(Instant, Limit)> {
+        let per_chunk = match (self.seen_first, self.timeouts.first_byte) {
+            (false, Some(d)) => Some((self.started + d, Limit::FirstByte(d))),
+            _ => self
+                .timeouts
+                .idle
+                .map(|d| (Instant::now() + d, Limit::Idle(d))),
+        };
+        let total = self
+            .timeouts
+            .total
+            .map(|d| (self.started + d, Limit::Total(d)));
+        per_chunk.into_iter().chain(total).min_by_key(|(at, _)| *at)
+    }
+}
+
+/// Wrap `stream` so it fails with a timeout error (and then ends) when one of
+/// `timeouts` is exceeded.  `started` is when the request was sent.
+pub(crate) fn with_timeouts<T: Send + 'static>(
+    stream: BoxStream<'static, Result<T, ApiError>>,
+    timeouts: StreamTimeouts,
+    started: Instant,
+) -> BoxStream<'static, Result<T, ApiError>> {
+    let state = Timed {
+        inner: stream,
+        timeouts,
+        started,
+        seen_first: false,
+        done: false,
+    };
+    futures::stream::unfold(state, |mut s| async move {
+        if s.done {
+            return None;
+        }
+        let next = match s.next_deadline() {
+            None => s.inner.next().await,
+            Some((at, limit)) => match tokio::time::timeout_at(at, s.inner.next()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    let err = limit.error();
+                    warn!(error = %err, "stream timed out");
+                    s.done = true;
+                    return Some((Err(err), s));
+                }
+            },
+        };
+        s.seen_first = true;
+        next.map(|item| (item, s))
+    })
+    .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stream that yields `1`, `2`, `3` with the given delays before each.
+    fn delayed(delays: [u64; 3]) -> BoxStream<'static, Result<u32, ApiError>> {
+        futures::stream::iter(delays.into_iter().zip(1..))
+            .then(|(ms, n)| async move {
+                tokio::time::sleep(Duration::from_millis(ms)).await;
+                Ok(n)
+            })
+            .boxed()
+    }
+
+    async fn collect(
+        stream: BoxStream<'static, Result<u32, ApiError>>,
+        timeouts: StreamTimeouts,
+    ) -> Vec<Result<u32, ApiError>> {
+        with_timeouts(stream, timeouts, Instant::now())
+            .collect()
+            .await
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn passes_through_within_limits() {
+        let items = collect(
+            delayed([10, 10, 10]),
+            StreamTimeouts::new()
+                .first_byte(Duration::from_millis(50))
+                .idle(Duration::from_millis(50))
+                .total(Duration::from_millis(100)),
+        )
+        .await;
+        assert_eq!(items.len(), 3);
+        assert!(items.iter().all(Result::is_ok));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn first_byte_timeout() {
+        let items = collect(
+            delayed([100, 0, 0]),
+            StreamTimeouts::new()
+                .first_byte(Duration::from_millis(50))
+                .idle(Duration::from_millis(500)),
+        )
+        .await;
+        assert!(matches!(
+            items.as_slice(),
+            [Err(ApiError::StreamFirstByteTimeout(_))]
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_timeout_after_first_chunk() {
+        let items = collect(
+            delayed([10, 100, 0]),
+            StreamTimeouts::new().idle(Duration::from_millis(50)),
+        )
+        .await;
+        assert!(matches!(
+            items.as_slice(),
+            [Ok(1), Err(ApiError::StreamIdleTimeout(_))]
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn total_deadline() {
+        let items = collect(
+            delayed([40, 40, 40]),
+            StreamTimeouts::new()
+                .idle(Duration::from_millis(50))
+                .total(Duration::from_millis(100)),
+        )
+        .await;
+        assert!(matches!(
+            items.as_slice(),
+            [Ok(1), Ok(2), Err(ApiError::StreamDeadlineExceeded(_))]
+        ));
+    }
+}
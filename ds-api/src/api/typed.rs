@@ -0,0 +1,316 @@
+//! Typed structured output.
+//!
+//! [`ApiClient::send_typed`][crate::api::ApiClient::send_typed] and
+//! [`DeepseekAgent::chat_typed`][crate::agent::DeepseekAgent::chat_typed] ask
+//! the model for a JSON reply shaped like a Rust type `T`:
+//!
+//! 1. A JSON Schema is generated from `T` with [`schemars`] and made
+//!    strict-mode compatible: objects are closed and list every property as
+//!    required, with `Option` fields nullable.
+//! 2. The schema is sent to the model, either as an instruction in the prompt
+//!    ([`SchemaMode::Prompt`], works with DeepSeek) or as a `json_schema`
+//!    response format ([`SchemaMode::ResponseFormat`], for providers that
+//!    support it).
+//! 3. The reply is deserialized into `T`.  When that fails, the model is shown
+//!    the error and asked again, up to
+//!    [`max_attempts`][StructuredOutput::max_attempts] replies in total, after
+//!    which [`ApiError::SchemaMismatch`] is returned.
+
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tracing::debug;
+
+use super::backend::ChatBackend;
+use super::request::ApiRequest;
+use crate::error::{ApiError, Result};
+use crate::raw::{Message, Role};
+
+/// How the schema reaches the model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SchemaMode {
+    /// Describe the schema in the prompt and request `json_object` output.
+    /// Works with every provider, including DeepSeek.
+    #[default]
+    Prompt,
+    /// Send the schema as a strict `json_schema` response format.  Only for
+    /// OpenAI-compatible providers that support it.
+    ResponseFormat,
+}
+
+/// Settings for typed structured output.
+///
+/// # Example
+///
+/// ```no_run
+/// use ds_api::{ApiClient, ApiRequest, SchemaMode, StructuredOutput};
+/// use ds_api::raw::request::message::Message;
+///
+/// #[derive(serde::Deserialize, schemars::JsonSchema)]
+/// struct City {
+///     name: String,
+///     population: u64,
+/// }
+///
+/// # #[tokio::main] async fn main() -> ds_api::error::Result<()> {
+/// let client = ApiClient::new("sk-...");
+/// let req = ApiRequest::deepseek_chat(vec![Message::user("The largest city in Japan?")]);
+/// let city: City = client
+///     .send_typed_with(req, StructuredOutput::new().max_attempts(2))
+///     .await?;
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct StructuredOutput {
+    pub(crate) mode: SchemaMode,
+    pub(crate) max_attempts: u32,
+}
+
+impl Default for StructuredOutput {
+    fn default() -> Self {
+        Self {
+            mode: SchemaMode::Prompt,
+            max_attempts: 3,
+        }
+    }
+}
+
+impl StructuredOutput {
+    /// Prompt mode, three attempts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder: how the schema reaches the model.
+    pub fn mode(mut self, mode: SchemaMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Builder: maximum number of replies to request, including the first
+    /// (at least 1).
+    pub fn max_attempts(mut self, n: u32) -> Self {
+        self.max_attempts = n.max(1);
+        self
+    }
+}
+
+/// A JSON Schema generated from a Rust type.
+pub(crate) struct Schema {
+    /// Schema name, restricted to the characters `json_schema` accepts.
+    pub(crate) name: String,
+    pub(crate) value: Value,
+}
+
+impl Schema {
+    pub(crate) fn of<T: JsonSchema>() -> Self {
+        let name = T::schema_name()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let mut value = schemars::schema_for!(T).to_value();
+        make_strict(&mut value);
+        Self { name, value }
+    }
+
+    /// The instruction that describes the schema in the prompt.
+    pub(crate) fn instruction(&self) -> String {
+        format!(
+            "Respond with a single JSON object, and nothing else, that conforms to this JSON Schema:\n{}",
+            self.value
+        )
+    }
+
+    /// Prepare `req` so the model answers with this schema.
+    fn apply(&self, mut req: ApiRequest, mode: SchemaMode) -> ApiRequest {
+        match mode {
+            SchemaMode::Prompt => {
+                insert_system(&mut req.raw.messages, &self.instruction());
+                req.json()
+            }
+            SchemaMode::ResponseFormat => req.json_schema(self.name.clone(), self.value.clone()),
+        }
+    }
+}
+
+/// Insert a system message after the leading system prompts, so those stay
+/// first.
+pub(crate) fn insert_system(messages: &mut Vec<Message>, text: &str) {
+    let at = messages
+        .iter()
+        .take_while(|m| matches!(m.role, Role::System))
+        .count();
+    messages.insert(at, Message::system(text));
+}
+
+/// Rewrite a generated schema so providers accept it in `json_schema` strict
+/// mode: every object lists all of its properties as required and sets
+/// `additionalProperties: false`, and the `$schema`, `title` and `default`
+/// annotations are dropped.  `Option` fields are already nullable.
+fn make_strict(schema: &mut Value) {
+    let Value::Object(map) = schema else {
+        return;
+    };
+    for key in ["$schema", "title", "default"] {
+        map.remove(key);
+    }
+    if let Some(Value::Object(properties)) = map.get_mut("properties") {
+        properties.values_mut().for_each(make_strict);
+        let required = properties.keys().cloned().map(Value::String).collect();
+        map.insert("required".into(), Value::Array(required));
+        map.insert("additionalProperties".into(), Value::Bool(false));
+    }
+    for key in ["$defs", "definitions"] {
+        if let Some(Value::Object(defs)) = map.get_mut(key) {
+            defs.values_mut().for_each(make_strict);
+        }
+    }
+    for key in ["items", "additionalProperties", "not"] {
+        if let Some(sub) = map.get_mut(key) {
+            make_strict(sub);
+        }
+    }
+    for key in ["anyOf", "oneOf", "allOf", "prefixItems"] {
+        if let Some(Value::Array(subs)) = map.get_mut(key) {
+            subs.iter_mut().for_each(make_strict);
+        }
+    }
+}
+
+/// Deserialize a reply, tolerating surrounding whitespace and a Markdown code
+/// fence.
+pub(crate) fn parse<T: DeserializeOwned>(content: &str) -> std::result::Result<T, String> {
+    let mut text = content.trim();
+    if let Some(rest) = text.strip_prefix("```") {
+        let rest = rest.strip_prefix("json").unwrap_or(rest);
+        text = rest.strip_suffix("```").unwrap_or(rest).trim();
+    }
+    serde_json::from_str(text).map_err(|e| e.to_string())
+}
+
+/// The follow-up message sent after a reply that did not deserialize.
+pub(crate) fn correction(error: &str) -> String {
+    format!(
+        "Your reply did not match the required JSON Schema: {error}. Reply again with only the corrected JSON object."
+    )
+}
+
+/// Send `req` through `backend` and deserialize the reply into `T`,
+/// re-asking on mismatch.
+pub(crate) async fn send_typed<T>(
+    backend: &dyn ChatBackend,
+    req: ApiRequest,
+    config: StructuredOutput,
+) -> Result<T>
+where
+    T: DeserializeOwned + JsonSchema,
+{
+    let schema = Schema::of::<T>();
+    let mut req = schema.apply(req, config.mode);
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let resp = backend.send(req.clone()).await?;
+        let content = resp
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|c| c.message.content)
+            .unwrap_or_default();
+        let error = match parse(&content) {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+        if attempt >= config.max_attempts {
+            return Err(ApiError::SchemaMismatch {
+                attempts: attempt,
+                message: error,
+                content,
+            });
+        }
+        debug!(attempt, error = %error, "reply did not match schema, asking again");
+        req = req
+            .add_message(Message::assistant(&content))
+            .add_message(Message::user(&correction(&error)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, serde::Deserialize, JsonSchema)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[test]
+    fn parses_plain_and_fenced_replies() {
+        let expected = Point { x: 1, y: 2 };
+        assert_eq!(parse::<Point>(r#" {"x":1,"y":2} "#).unwrap(), expected);
+        assert_eq!(
+            parse::<Point>("```json\n{\"x\":1,\"y\":2}\n```").unwrap(),
+            expected
+        );
+        assert!(parse::<Point>(r#"{"x":1}"#).unwrap_err().contains("y"));
+    }
+
+    #[test]
+    fn prompt_mode_inserts_instruction_after_system_prompts() {
+        let req = ApiRequest::builder()
+            .add_message(Message::system("be brief"))
+            .add_message(Message::user("where?"));
+        let req = Schema::of::<Point>().apply(req, SchemaMode::Prompt);
+        let raw = req.as_raw();
+        assert_eq!(raw.messages.len(), 3);
+        let instruction = raw.messages[1].content.as_deref().unwrap();
+        assert!(instruction.contains("JSON Schema") && instruction.contains("\"x\""));
+        assert!(matches!(raw.messages[2].role, Role::User));
+    }
+
+    #[derive(serde::Deserialize, JsonSchema)]
+    #[allow(dead_code)]
+    struct Contact {
+        name: String,
+        email: Option<String>,
+        home: Option<Point>,
+    }
+
+    #[test]
+    fn schema_is_strict_mode_compatible() {
+        let schema = Schema::of::<Contact>().value;
+        assert!(schema.get("$schema").is_none() && schema.get("title").is_none());
+        assert_eq!(schema["additionalProperties"], false);
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["email", "home", "name"])
+        );
+        assert_eq!(
+            schema["properties"]["email"]["type"],
+            serde_json::json!(["string", "null"])
+        );
+        assert_eq!(schema["properties"]["home"]["anyOf"][1]["type"], "null");
+
+        let point = &schema["$defs"]["Point"];
+        assert_eq!(point["additionalProperties"], false);
+        assert_eq!(point["required"], serde_json::json!(["x", "y"]));
+    }
+
+    #[test]
+    fn response_format_mode_sends_named_schema() {
+        let req =
+            Schema::of::<Vec<Point>>().apply(ApiRequest::builder(), SchemaMode::ResponseFormat);
+        req.validate().unwrap();
+        let format = req.as_raw().response_format.as_ref().unwrap();
+        let schema = format.json_schema.as_ref().unwrap();
+        assert_eq!(schema.name, "Array_of_Point");
+        assert_eq!(schema.strict, Some(true));
+    }
+}
@@ -1,15 +1,26 @@
 //! The `Conversation` struct — manages history and context-window compression.
 
+use std::sync::Arc;
+
+use futures::StreamExt;
 use futures::stream::BoxStream;
+use tracing::warn;
 
-use crate::api::{ApiClient, ApiRequest};
+use crate::api::cost::with_cost_scope;
+use crate::api::{ApiRequest, ChatBackend, CostTracker};
 use crate::error::{ApiError, Result};
+use crate::raw::Usage;
+use crate::raw::request::content_part::ContentPart;
 use crate::raw::request::message::{Message, Role};
 
-use crate::conversation::{LlmSummarizer, Summarizer};
+use crate::conversation::{ConversationStore, LlmSummarizer, Summarizer};
 
 /// Maintains a conversation history and handles context-window compression.
 ///
+/// The conversation talks to the model through a [`ChatBackend`] — usually an
+/// [`ApiClient`][crate::api::ApiClient], or a
+/// [`MockBackend`][crate::api::MockBackend] in tests.
+///
 /// This is the primary building block used by [`DeepseekAgent`][crate::agent::DeepseekAgent].
 /// You can also use it directly for simple back-and-forth conversations that do not need tools.
 ///
@@ -25,25 +36,52 @@ use crate::conversation::{LlmSummarizer, Summarizer};
 /// let conv = Conversation::new(ApiClient::new("sk-..."))
 ///     .with_summarizer(SlidingWindowSummarizer::new(20));
 /// ```
+///
+/// # Persistence
+///
+/// Attach a [`ConversationStore`] with [`with_store`][Conversation::with_store]
+/// and the history is written to it as the conversation goes.
 pub struct Conversation {
-    pub(crate) client: ApiClient,
+    pub(crate) backend: Arc<dyn ChatBackend>,
     pub(crate) history: Vec<Message>,
     summarizer: Box<dyn Summarizer + Send + Sync>,
     auto_summary: bool,
+    cost_tracker: Option<CostTracker>,
+    store: Option<StoreBinding>,
+}
+
+/// The store a conversation writes to, and how much of the history it holds.
+struct StoreBinding {
+    store: Arc<dyn ConversationStore>,
+    session: String,
+    /// Number of leading history messages already in the store.
+    persisted: usize,
+    /// The stored messages may no longer match the start of the history
+    /// (it was edited, replaced or summarized); the next write replaces the
+    /// session.
+    rewrite: bool,
 }
 
 impl Conversation {
-    /// Create a new conversation backed by `client`.
+    /// Create a new conversation backed by `backend`.
     ///
     /// The default summarizer is [`LlmSummarizer`] with sensible defaults
-    /// (~60 000 estimated tokens trigger, retain last 10 turns).
-    pub fn new(client: ApiClient) -> Self {
-        let summarizer = LlmSummarizer::new(client.clone());
+    /// (~60 000 estimated tokens trigger, retain last 10 turns), sharing the
+    /// same backend.
+    pub fn new(backend: impl ChatBackend + 'static) -> Self {
+        Self::from_shared(Arc::new(backend))
+    }
+
+    /// Create a new conversation around an already shared backend.
+    pub fn from_shared(backend: Arc<dyn ChatBackend>) -> Self {
+        let summarizer = LlmSummarizer::from_shared(backend.clone());
         Self {
-            client,
+            backend,
             history: vec![],
             summarizer: Box::new(summarizer),
             auto_summary: true,
+            cost_tracker: None,
+            store: None,
         }
     }
 
@@ -62,11 +100,127 @@ impl Conversation {
     }
 
     /// Seed the conversation with an existing message history.
+    ///
+    /// With a [store][Self::with_store] already attached, the next write
+    /// replaces the stored session with this history.
     pub fn with_history(mut self, history: Vec<Message>) -> Self {
-        self.history = history;
+        self.replace_history(history);
+        self
+    }
+
+    /// Price the calls made for this conversation with `tracker`, including
+    /// the ones [`LlmSummarizer`] makes.
+    ///
+    /// See [`CostTracker`] for how this differs from a tracker on the client.
+    pub fn with_cost_tracker(mut self, tracker: CostTracker) -> Self {
+        self.cost_tracker = Some(tracker);
         self
     }
 
+    /// The tracker attached with [`with_cost_tracker`][Self::with_cost_tracker], if any.
+    pub fn cost_tracker(&self) -> Option<&CostTracker> {
+        self.cost_tracker.as_ref()
+    }
+
+    /// Persist the history to `store` under `session`.
+    ///
+    /// Messages are appended as they are added — [`send_once`][Self::send_once]
+    /// writes after each reply — and the whole session is replaced atomically
+    /// when the history changes in any other way: summarization,
+    /// [`with_history`][Self::with_history] or an edit through
+    /// [`history_mut`][Self::history_mut].  A failed write is logged and
+    /// retried on the next one.
+    ///
+    /// The store is not read: the first write replaces whatever the session
+    /// holds with this conversation's history.  Call
+    /// [`load_history`][Self::load_history] right after attaching the store
+    /// to continue a stored session instead.
+    pub fn with_store(
+        mut self,
+        store: impl ConversationStore + 'static,
+        session: impl Into<String>,
+    ) -> Self {
+        self.store = Some(StoreBinding {
+            store: Arc::new(store),
+            session: session.into(),
+            persisted: 0,
+            rewrite: true,
+        });
+        self
+    }
+
+    /// Replace the history with the messages stored for this conversation's
+    /// session.  Does nothing without a store.
+    pub async fn load_history(&mut self) -> Result<()> {
+        let Some(binding) = &mut self.store else {
+            return Ok(());
+        };
+        self.history = binding.store.load(&binding.session).await?;
+        binding.persisted = self.history.len();
+        binding.rewrite = false;
+        Ok(())
+    }
+
+    /// Write the messages added since the last write to the store, or the
+    /// whole history if it was rewritten.  Does nothing without a store.
+    ///
+    /// A trailing [assistant prefix][Self::push_assistant_prefix] is left out
+    /// until the reply is stitched onto it.
+    pub async fn persist(&mut self) -> Result<()> {
+        let Some(binding) = &mut self.store else {
+            return Ok(());
+        };
+        let mut end = self.history.len();
+        if self.history.last().is_some_and(Message::is_prefix) {
+            end -= 1;
+        }
+        if binding.rewrite || end < binding.persisted {
+            binding
+                .store
+                .replace(&binding.session, &self.history[..end])
+                .await?;
+        } else if end > binding.persisted {
+            binding
+                .store
+                .append(&binding.session, &self.history[binding.persisted..end])
+                .await?;
+        }
+        binding.persisted = end;
+        binding.rewrite = false;
+        Ok(())
+    }
+
+    /// [`persist`][Self::persist], logging a failure instead of returning it
+    /// so a storage problem does not abort the conversation.
+    pub(crate) async fn persist_or_warn(&mut self) {
+        if let Err(e) = self.persist().await {
+            warn!(error = %e, "failed to persist conversation history");
+        }
+    }
+
+    /// Replace the whole history; the next write to the store replaces the
+    /// session rather than appending to it.
+    pub(crate) fn replace_history(&mut self, history: Vec<Message>) {
+        self.history = history;
+        if let Some(binding) = &mut self.store {
+            binding.rewrite = true;
+        }
+    }
+
+    /// Whether the history has changes the store has not seen yet.
+    pub(crate) fn has_unpersisted(&self) -> bool {
+        self.store
+            .as_ref()
+            .is_some_and(|b| b.rewrite || b.persisted != self.history.len())
+    }
+
+    /// Record a call made for this conversation in its cost tracker.
+    pub(crate) fn record_usage(&self, model: &str, usage: &Usage) {
+        if let Some(tracker) = &self.cost_tracker {
+            tracker.record(model, usage);
+        }
+    }
+
     // ── History access ────────────────────────────────────────────────────────
 
     /// Read-only view of the current history.
@@ -75,20 +229,69 @@ impl Conversation {
     }
 
     /// Mutable access to the raw history (advanced use).
+    ///
+    /// With a [store][Self::with_store], the next write replaces the stored
+    /// session, since any message may have changed.
     pub fn history_mut(&mut self) -> &mut Vec<Message> {
+        if let Some(binding) = &mut self.store {
+            binding.rewrite = true;
+        }
         &mut self.history
     }
 
+    /// Append `message` to the history.  Unlike
+    /// [`history_mut`][Self::history_mut], this keeps the stored messages
+    /// valid, so the next write only appends.
+    pub(crate) fn push(&mut self, message: Message) {
+        self.history.push(message);
+    }
+
     // ── Mutation helpers ──────────────────────────────────────────────────────
 
     /// Append an arbitrary message (any role) to the history.
     pub fn add_message(&mut self, message: Message) {
-        self.history.push(message);
+        self.push(message);
     }
 
     /// Append a `Role::User` message to the history.
     pub fn push_user_input(&mut self, text: impl Into<String>) {
-        self.history.push(Message::new(Role::User, &text.into()));
+        self.push(Message::new(Role::User, &text.into()));
+    }
+
+    /// Append a multimodal `Role::User` message built from content parts.
+    pub fn push_user_parts(&mut self, parts: Vec<ContentPart>) {
+        self.push(Message::from_parts(Role::User, parts));
+    }
+
+    /// Append an assistant prefix message; the next request makes the model
+    /// continue from it (beta chat prefix completion).
+    ///
+    /// The reply is merged into the prefix by
+    /// [`push_assistant_reply`][Self::push_assistant_reply], so history ends
+    /// up with a single assistant message.
+    pub fn push_assistant_prefix(&mut self, prefix: impl Into<String>) {
+        self.push(Message::assistant_prefix(&prefix.into()));
+    }
+
+    /// Remove a trailing [assistant prefix][Self::push_assistant_prefix]
+    /// whose reply never arrived.  The prefix is never stored, so the store
+    /// is unaffected.
+    pub(crate) fn drop_pending_prefix(&mut self) {
+        if self.history.last().is_some_and(Message::is_prefix) {
+            self.history.pop();
+        }
+    }
+
+    /// Append an assistant reply.  If the last message is a pending prefix,
+    /// the reply is stitched onto it instead.
+    pub fn push_assistant_reply(&mut self, reply: Message) {
+        match self.history.pop() {
+            Some(last) if last.is_prefix() => self.history.push(last.stitch(reply)),
+            last => {
+                self.history.extend(last);
+                self.history.push(reply);
+            }
+        }
     }
 
     // ── Summarization ─────────────────────────────────────────────────────────
@@ -104,7 +307,13 @@ impl Conversation {
         if !self.summarizer.should_summarize(&self.history) {
             return;
         }
-        let _ = self.summarizer.summarize(&mut self.history).await;
+        let summarize = self.summarizer.summarize(&mut self.history);
+        let _ = with_cost_scope(self.cost_tracker.clone(), summarize).await;
+        // The summarizer may have rewritten stored messages.
+        if let Some(binding) = &mut self.store {
+            binding.rewrite = true;
+            self.persist_or_warn().await;
+        }
     }
 
     // ── Single-turn send ──────────────────────────────────────────────────────
@@ -112,13 +321,19 @@ impl Conversation {
     /// Send the current history to the API as a single (non-streaming) request
     /// and return the assistant's text content (if any).
     ///
-    /// The assistant reply is automatically appended to the history.
+    /// The assistant reply is automatically appended to the history (merged
+    /// into a pending [prefix][Self::push_assistant_prefix]; the returned text
+    /// is the continuation only).
     /// Summarization is run both before the request and after the reply is received.
+    /// With a [store][Self::with_store], the history is persisted before the
+    /// request and after the reply.
     pub async fn send_once(&mut self) -> Result<Option<String>> {
         self.maybe_summarize().await;
+        self.persist_or_warn().await;
 
         let req = ApiRequest::builder().messages(self.history.clone());
-        let resp = self.client.send(req).await?;
+        let resp = self.backend.send(req).await?;
+        self.record_usage(resp.model.as_str(), &resp.usage);
 
         let choice = resp
             .choices
@@ -128,9 +343,10 @@ impl Conversation {
 
         let assistant_msg = choice.message;
         let content = assistant_msg.content.clone();
-        self.history.push(assistant_msg);
+        self.push_assistant_reply(assistant_msg);
 
         self.maybe_summarize().await;
+        self.persist_or_warn().await;
 
         Ok(content)
     }
@@ -141,9 +357,8 @@ impl Conversation {
     /// # ⚠ Caller responsibilities
     ///
     /// Unlike [`send_once`][Conversation::send_once], this method is intentionally
-    /// minimal: it does **not** append the assistant reply to history, does **not**
-    /// run summarization, and does **not** set `stream: true` on the request for
-    /// you (the underlying [`ApiClient::stream_text`] handles that).
+    /// minimal: it does **not** append the assistant reply to history and does
+    /// **not** run summarization.  It sets `stream: true` on the request itself.
     ///
     /// If you want the conversation to remember this turn you must collect the
     /// full text and push it yourself:
@@ -179,8 +394,25 @@ impl Conversation {
     ) -> Result<BoxStream<'_, std::result::Result<String, ApiError>>> {
         let req = ApiRequest::builder()
             .messages(self.history.clone())
-            .stream(true);
-        self.client.stream_text(req).await
+            .stream(true)
+            .include_usage(true);
+        let chunks = self.backend.send_stream(req).await?;
+        let tracker = self.cost_tracker.clone();
+        Ok(chunks
+            .map(move |item| {
+                item.map(|chunk| {
+                    if let (Some(tracker), Some(usage)) = (&tracker, &chunk.usage) {
+                        tracker.record(&chunk.model, usage);
+                    }
+                    chunk
+                        .choices
+                        .into_iter()
+                        .next()
+                        .and_then(|c| c.delta.content)
+                        .unwrap_or_default()
+                })
+            })
+            .boxed())
     }
 }
 
@@ -189,6 +421,7 @@ impl Conversation {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::ApiClient;
 
     fn fake() -> Conversation {
         Conversation::new(ApiClient::new("fake-token"))
@@ -222,6 +455,26 @@ mod tests {
         assert!(matches!(conv.history()[0].role, Role::Assistant));
     }
 
+    #[test]
+    fn assistant_reply_is_stitched_onto_prefix() {
+        let mut conv = fake();
+        conv.push_user_input("code please");
+        conv.push_assistant_prefix("```rust\n");
+        let mut reply = Message::new(Role::Assistant, "fn main() {}\n");
+        reply.reasoning_content = Some("short".into());
+        conv.push_assistant_reply(reply);
+
+        assert_eq!(conv.history().len(), 2);
+        let msg = &conv.history()[1];
+        assert_eq!(msg.content.as_deref(), Some("```rust\nfn main() {}\n"));
+        assert_eq!(msg.reasoning_content.as_deref(), Some("short"));
+        assert!(msg.prefix.is_none());
+
+        // Without a pending prefix the reply is simply appended.
+        conv.push_assistant_reply(Message::new(Role::Assistant, "more"));
+        assert_eq!(conv.history().len(), 3);
+    }
+
     #[test]
     fn enable_auto_summary_false() {
         let conv = fake().enable_auto_summary(false);
@@ -1,5 +1,11 @@
 pub mod core;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod store;
 pub mod summarizer;
 
 pub use core::Conversation;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+pub use store::{ConversationStore, JsonlStore};
 pub use summarizer::{LlmSummarizer, SlidingWindowSummarizer, Summarizer};
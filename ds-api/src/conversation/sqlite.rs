@@ -0,0 +1,199 @@
+//! A SQLite-backed [`ConversationStore`] (feature `sqlite`).
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use rusqlite::{Connection, params};
+
+use crate::conversation::ConversationStore;
+use crate::error::{ApiError, Result};
+use crate::raw::request::message::Message;
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS conversation_messages (
+    session TEXT NOT NULL,
+    seq     INTEGER NOT NULL,
+    message TEXT NOT NULL,
+    PRIMARY KEY (session, seq)
+)";
+
+/// A [`ConversationStore`] that keeps every session in one SQLite table,
+/// `conversation_messages`, one row per message stored as JSON.
+///
+/// Appends and replacements each run in a transaction, so a crash leaves a
+/// session as it was before or after the write.  Clones share the
+/// connection; database calls run on Tokio's blocking thread pool.
+///
+/// ```no_run
+/// use ds_api::conversation::SqliteStore;
+/// use ds_api::DeepseekAgent;
+///
+/// # fn main() -> ds_api::error::Result<()> {
+/// let store = SqliteStore::open("sessions.db")?;
+/// let agent = DeepseekAgent::new("sk-...").with_store(store, "user-42");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SqliteStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStore {
+    /// Open (or create) the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_connection(Connection::open(path).map_err(store_error)?)
+    }
+
+    /// A database that lives only as long as the store.
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory().map_err(store_error)?)
+    }
+
+    /// Use an already open connection, creating the table if it is missing.
+    pub fn from_connection(conn: Connection) -> Result<Self> {
+        conn.execute(SCHEMA, []).map_err(store_error)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Run `f` on the connection without blocking the runtime.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            // A panic while holding the lock cannot leave a transaction open.
+            let mut conn = conn.lock().unwrap_or_else(|e| e.into_inner());
+            f(&mut conn)
+        })
+        .await
+        .map_err(|e| ApiError::Store(e.to_string()))?
+        .map_err(store_error)
+    }
+}
+
+fn store_error(e: rusqlite::Error) -> ApiError {
+    ApiError::Store(e.to_string())
+}
+
+/// Insert `rows` into `session` numbered from `first`.
+fn insert(
+    tx: &rusqlite::Transaction<'_>,
+    session: &str,
+    first: i64,
+    rows: &[String],
+) -> rusqlite::Result<()> {
+    let mut stmt = tx
+        .prepare("INSERT INTO conversation_messages (session, seq, message) VALUES (?1, ?2, ?3)")?;
+    for (seq, row) in (first..).zip(rows) {
+        stmt.execute(params![session, seq, row])?;
+    }
+    Ok(())
+}
+
+fn to_rows(messages: &[Message]) -> Result<Vec<String>> {
+    messages
+        .iter()
+        .map(|m| Ok(serde_json::to_string(m)?))
+        .collect()
+}
+
+#[async_trait]
+impl ConversationStore for SqliteStore {
+    async fn load(&self, session: &str) -> Result<Vec<Message>> {
+        let session = session.to_string();
+        let rows = self
+            .with_conn(move |conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT message FROM conversation_messages WHERE session = ?1 ORDER BY seq",
+                )?;
+                stmt.query_map([session], |row| row.get::<_, String>(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()
+            })
+            .await?;
+        rows.iter()
+            .map(|row| Ok(serde_json::from_str(row)?))
+            .collect()
+    }
+
+    async fn append(&self, session: &str, messages: &[Message]) -> Result<()> {
+        let session = session.to_string();
+        let rows = to_rows(messages)?;
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let next: i64 = tx.query_row(
+                "SELECT COALESCE(MAX(seq) + 1, 0) FROM conversation_messages WHERE session = ?1",
+                [&session],
+                |row| row.get(0),
+            )?;
+            insert(&tx, &session, next, &rows)?;
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn replace(&self, session: &str, messages: &[Message]) -> Result<()> {
+        let session = session.to_string();
+        let rows = to_rows(messages)?;
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "DELETE FROM conversation_messages WHERE session = ?1",
+                [&session],
+            )?;
+            insert(&tx, &session, 0, &rows)?;
+            tx.commit()
+        })
+        .await
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<String>> {
+        self.with_conn(|conn| {
+            let mut stmt = conn
+                .prepare("SELECT DISTINCT session FROM conversation_messages ORDER BY session")?;
+            stmt.query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::request::message::Role;
+
+    #[tokio::test]
+    async fn append_replace_and_list() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        assert!(store.load("a").await.unwrap().is_empty());
+
+        store
+            .append("a", &[Message::new(Role::User, "hello")])
+            .await
+            .unwrap();
+        store
+            .append("a", &[Message::new(Role::Assistant, "hi")])
+            .await
+            .unwrap();
+        store
+            .append("b", &[Message::new(Role::User, "hey")])
+            .await
+            .unwrap();
+        let texts = |messages: Vec<Message>| -> Vec<String> {
+            messages.into_iter().filter_map(|m| m.content).collect()
+        };
+        assert_eq!(texts(store.load("a").await.unwrap()), ["hello", "hi"]);
+
+        store
+            .replace("a", &[Message::new(Role::System, "summary")])
+            .await
+            .unwrap();
+        assert_eq!(texts(store.load("a").await.unwrap()), ["summary"]);
+        assert_eq!(store.list_sessions().await.unwrap(), ["a", "b"]);
+    }
+}
@@ -0,0 +1,281 @@
+//! Persistent storage for conversation history.
+//!
+//! A [`ConversationStore`] keeps the messages of named sessions.  Attach one
+//! with [`Conversation::with_store`][crate::conversation::Conversation::with_store]
+//! (or [`DeepseekAgent::with_store`][crate::agent::DeepseekAgent::with_store])
+//! and new messages are written as the conversation goes:
+//!
+//! - [`append`][ConversationStore::append] adds the messages that are new
+//!   since the last write;
+//! - [`replace`][ConversationStore::replace] rewrites the whole session after
+//!   summarization has rewritten the history.
+//!
+//! Two backends ship with the crate: [`JsonlStore`], one JSON Lines file per
+//! session, and `SqliteStore` behind the `sqlite` feature.
+
+use std::io::{ErrorKind, SeekFrom};
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use crate::error::{ApiError, Result};
+use crate::raw::request::message::Message;
+
+/// Storage for the message history of named sessions.
+///
+/// Implementations are shared behind an `Arc` and must be safe to call from
+/// several tasks; calls for one session are never made concurrently by a
+/// single [`Conversation`][crate::conversation::Conversation].
+///
+/// # Implementing a store
+///
+/// ```no_run
+/// use std::collections::HashMap;
+/// use std::sync::Mutex;
+///
+/// use async_trait::async_trait;
+/// use ds_api::conversation::ConversationStore;
+/// use ds_api::error::Result;
+/// use ds_api::raw::request::message::Message;
+///
+/// #[derive(Default)]
+/// struct InMemory(Mutex<HashMap<String, Vec<Message>>>);
+///
+/// #[async_trait]
+/// impl ConversationStore for InMemory {
+///     async fn load(&self, session: &str) -> Result<Vec<Message>> {
+///         Ok(self.0.lock().unwrap().get(session).cloned().unwrap_or_default())
+///     }
+///
+///     async fn append(&self, session: &str, messages: &[Message]) -> Result<()> {
+///         let mut sessions = self.0.lock().unwrap();
+///         sessions.entry(session.to_string()).or_default().extend_from_slice(messages);
+///         Ok(())
+///     }
+///
+///     async fn replace(&self, session: &str, messages: &[Message]) -> Result<()> {
+///         self.0.lock().unwrap().insert(session.to_string(), messages.to_vec());
+///         Ok(())
+///     }
+///
+///     async fn list_sessions(&self) -> Result<Vec<String>> {
+///         Ok(self.0.lock().unwrap().keys().cloned().collect())
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait ConversationStore: Send + Sync {
+    /// The messages of `session`, oldest first.  An unknown session has none.
+    async fn load(&self, session: &str) -> Result<Vec<Message>>;
+
+    /// Add `messages` to the end of `session`, creating it if needed.
+    async fn append(&self, session: &str, messages: &[Message]) -> Result<()>;
+
+    /// Replace every message of `session` with `messages`.
+    ///
+    /// Must be atomic: after a crash, `load` returns either the old messages
+    /// or the new ones, never a mix.
+    async fn replace(&self, session: &str, messages: &[Message]) -> Result<()>;
+
+    /// The names of all stored sessions.
+    async fn list_sessions(&self) -> Result<Vec<String>>;
+}
+
+/// A [`ConversationStore`] that keeps each session in its own JSON Lines
+/// file, `<dir>/<session>.jsonl`, one message per line.
+///
+/// Appends add lines to the end of the file.  Replacing writes a temporary
+/// file and renames it over the old one, so a crash leaves one version or the
+/// other.  A last line cut short by a crash during an append is ignored when
+/// loading and dropped by the next append.  Session names must be usable as
+/// file names: not empty, no path separators, and not starting with a dot.
+///
+/// ```no_run
+/// use ds_api::{ApiClient, conversation::Conversation, conversation::JsonlStore};
+///
+/// let conv = Conversation::new(ApiClient::new("sk-..."))
+///     .with_store(JsonlStore::new("./sessions"), "user-42");
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonlStore {
+    dir: PathBuf,
+}
+
+impl JsonlStore {
+    /// Store sessions under `dir`.  The directory is created on the first
+    /// write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, session: &str, ext: &str) -> Result<PathBuf> {
+        if session.is_empty() || session.starts_with('.') || session.contains(['/', '\\']) {
+            return Err(ApiError::Store(format!(
+                "invalid session name for a file store: {session:?}"
+            )));
+        }
+        Ok(self.dir.join(format!("{session}.{ext}")))
+    }
+}
+
+/// Serialize `messages` as JSON Lines, each line ending in a newline.
+fn to_lines(messages: &[Message]) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    for message in messages {
+        serde_json::to_writer(&mut buf, message)?;
+        buf.push(b'\n');
+    }
+    Ok(buf)
+}
+
+/// Truncate `file` after its last newline if an earlier append was cut short,
+/// so the next line starts on a line of its own.
+async fn drop_torn_line(file: &mut tokio::fs::File) -> Result<()> {
+    let len = file.metadata().await?.len();
+    if len == 0 {
+        return Ok(());
+    }
+    file.seek(SeekFrom::Start(len - 1)).await?;
+    if file.read_u8().await? == b'\n' {
+        return Ok(());
+    }
+    let mut text = Vec::new();
+    file.seek(SeekFrom::Start(0)).await?;
+    file.read_to_end(&mut text).await?;
+    let keep = text.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+    file.set_len(keep as u64).await?;
+    Ok(())
+}
+
+#[async_trait]
+impl ConversationStore for JsonlStore {
+    async fn load(&self, session: &str) -> Result<Vec<Message>> {
+        let text = match tokio::fs::read_to_string(self.path(session, "jsonl")?).await {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        // Every complete line ends in a newline; anything after the last one
+        // is an interrupted append.
+        let complete = &text[..text.rfind('\n').map_or(0, |i| i + 1)];
+        complete
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+
+    async fn append(&self, session: &str, messages: &[Message]) -> Result<()> {
+        let path = self.path(session, "jsonl")?;
+        let lines = to_lines(messages)?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)
+            .await?;
+        drop_torn_line(&mut file).await?;
+        file.write_all(&lines).await?;
+        file.sync_data().await?;
+        Ok(())
+    }
+
+    async fn replace(&self, session: &str, messages: &[Message]) -> Result<()> {
+        let path = self.path(session, "jsonl")?;
+        let tmp = self.path(session, "jsonl.tmp")?;
+        let lines = to_lines(messages)?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let mut file = tokio::fs::File::create(&tmp).await?;
+        file.write_all(&lines).await?;
+        file.sync_all().await?;
+        drop(file);
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+
+    async fn list_sessions(&self) -> Result<Vec<String>> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut sessions = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            if let Some(session) = entry
+                .file_name()
+                .to_str()
+                .and_then(|n| n.strip_suffix(".jsonl"))
+            {
+                sessions.push(session.to_string());
+            }
+        }
+        sessions.sort();
+        Ok(sessions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::request::message::Role;
+
+    fn store(name: &str) -> JsonlStore {
+        let dir = std::env::temp_dir().join(format!("ds-api-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        JsonlStore::new(dir)
+    }
+
+    fn texts(messages: &[Message]) -> Vec<&str> {
+        messages
+            .iter()
+            .map(|m| m.content.as_deref().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn append_replace_and_list() {
+        let store = store("jsonl-store");
+        assert!(store.load("a").await.unwrap().is_empty());
+        assert!(store.list_sessions().await.unwrap().is_empty());
+
+        let hello = Message::new(Role::User, "hello");
+        let hi = Message::new(Role::Assistant, "hi");
+        store
+            .append("a", std::slice::from_ref(&hello))
+            .await
+            .unwrap();
+        store.append("a", &[hi]).await.unwrap();
+        store.append("b", &[hello]).await.unwrap();
+        assert_eq!(texts(&store.load("a").await.unwrap()), ["hello", "hi"]);
+
+        store
+            .replace("a", &[Message::new(Role::System, "summary")])
+            .await
+            .unwrap();
+        assert_eq!(texts(&store.load("a").await.unwrap()), ["summary"]);
+        assert_eq!(store.list_sessions().await.unwrap(), ["a", "b"]);
+        assert!(store.append("../x", &[]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn interrupted_append_is_dropped() {
+        let store = store("jsonl-torn");
+        store
+            .append("a", &[Message::new(Role::User, "kept")])
+            .await
+            .unwrap();
+        let path = store.path("a", "jsonl").unwrap();
+        let mut text = std::fs::read_to_string(&path).unwrap();
+        text.push_str(r#"{"role":"assistant","cont"#);
+        std::fs::write(&path, text).unwrap();
+
+        assert_eq!(texts(&store.load("a").await.unwrap()), ["kept"]);
+        store
+            .append("a", &[Message::new(Role::User, "next")])
+            .await
+            .unwrap();
+        assert_eq!(texts(&store.load("a").await.unwrap()), ["kept", "next"]);
+    }
+}
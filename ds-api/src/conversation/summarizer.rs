@@ -18,10 +18,12 @@
 //! | [`SlidingWindowSummarizer`] | Keeps the last N messages and silently drops the rest; no API call. |
 
 use std::pin::Pin;
+use std::sync::Arc;
 
 use futures::Future;
 
-use crate::api::{ApiClient, ApiRequest};
+use crate::api::cost::record_in_scope;
+use crate::api::{ApiRequest, ChatBackend};
 use crate::error::ApiError;
 use crate::raw::request::message::{Message, Role};
 
@@ -111,16 +113,23 @@ pub(crate) fn estimate_tokens(history: &[Message]) -> usize {
                 true
             }
         })
-        .filter_map(|m| m.content.as_deref())
-        .map(|s| {
-            s.chars()
-                .map(|c| if c.is_ascii() { 1usize } else { 4 })
-                .sum::<usize>()
-        })
+        .map(message_weight)
         .sum::<usize>()
         / 4
 }
 
+/// [`char_weight`] of a message's text plus a fixed allowance per image,
+/// in the same units (divide by 4 to get tokens).
+pub(crate) fn message_weight(m: &Message) -> usize {
+    m.content.as_deref().map_or(0, char_weight) + m.image_tokens() * 4
+}
+
+/// Weighted character count used by the token heuristics: ASCII characters
+/// count 1, everything else counts 4.  Divide the sum by 4 to get tokens.
+pub(crate) fn char_weight(s: &str) -> usize {
+    s.chars().map(|c| if c.is_ascii() { 1 } else { 4 }).sum()
+}
+
 /// Partition `history` into (system_prompts, rest), where system prompts are
 /// permanent user-provided system messages (role=System, name≠"[auto-summary]").
 ///
@@ -176,8 +185,8 @@ fn extract_system_prompts(history: &mut Vec<Message>) -> Vec<Message> {
 /// ```
 #[derive(Clone)]
 pub struct LlmSummarizer {
-    /// Client used exclusively for summary API calls (can share the agent's token).
-    client: ApiClient,
+    /// Backend used for summary API calls (usually shared with the agent).
+    backend: Arc<dyn ChatBackend>,
     /// Model used for the summarization API call.  Defaults to `"deepseek-chat"`.
     pub(crate) model: String,
     /// Estimated token count above which summarization is triggered.
@@ -193,9 +202,15 @@ impl LlmSummarizer {
     /// [`with_model`][LlmSummarizer::with_model] — useful when the agent is
     /// pointed at an OpenAI-compatible provider and you want the summarizer to
     /// use the same model.
-    pub fn new(client: ApiClient) -> Self {
+    pub fn new(backend: impl ChatBackend + 'static) -> Self {
+        Self::from_shared(Arc::new(backend))
+    }
+
+    /// Create around an already shared backend, e.g. the one held by a
+    /// [`Conversation`][crate::conversation::Conversation].
+    pub fn from_shared(backend: Arc<dyn ChatBackend>) -> Self {
         Self {
-            client,
+            backend,
             model: "deepseek-chat".to_string(),
             token_threshold: 60_000,
             retain_last: 10,
@@ -294,12 +309,18 @@ impl Summarizer for LlmSummarizer {
                     Role::Tool => "Tool",
                 };
 
-                let content_text = msg.content.clone().unwrap_or_else(|| {
+                let mut content_text = msg.content.clone().unwrap_or_else(|| {
                     msg.tool_calls
                         .as_ref()
                         .map(|calls| format!("[Calls Tools: {:?}]", calls))
                         .unwrap_or_default()
                 });
+                // Images cannot go into a text transcript; note that they were there.
+                match msg.image_count() {
+                    0 => {}
+                    1 => content_text.push_str(" [1 image]"),
+                    n => content_text.push_str(&format!(" [{n} images]")),
+                }
 
                 if !content_text.is_empty() {
                     transcript.push_str(&format!("{role_label}: {content_text}\n"));
@@ -319,7 +340,9 @@ impl Summarizer for LlmSummarizer {
                 .add_message(Message::new(Role::User, &summarize_prompt))
                 .max_tokens(512);
 
-            let response = self.client.send(req).await?;
+            let response = self.backend.send(req).await?;
+            // Hidden from the caller; counted by the conversation's tracker.
+            record_in_scope(response.model.as_str(), &response.usage);
 
             let summary_text = response
                 .choices
@@ -489,6 +512,22 @@ mod tests {
         assert!(est > 0);
     }
 
+    #[test]
+    fn estimate_tokens_counts_images() {
+        use crate::raw::{ContentPart, ImageDetail};
+
+        let text = msg(Role::User, "abcd");
+        let low = text
+            .clone()
+            .with_part(ContentPart::image_url("https://x/a.png").with_detail(ImageDetail::Low));
+        let high = text
+            .clone()
+            .with_part(ContentPart::image_url("https://x/a.png"));
+        assert_eq!(estimate_tokens(&[text]), 1);
+        assert_eq!(estimate_tokens(&[low]), 86);
+        assert_eq!(estimate_tokens(&[high]), 766);
+    }
+
     // ── SlidingWindowSummarizer ───────────────────────────────────────────────
 
     #[tokio::test]
@@ -5,16 +5,22 @@
 //! `Box<dyn Error>`, `reqwest::Error`, `serde_json::Error`) into a single `ApiError` so that `?`
 //! conversions are simpler and error messages are more consistent.
 
+use reqwest::StatusCode;
+use serde::{Deserialize, Deserializer};
 use thiserror::Error;
 
 /// Unified error type covering common error sources and including a generic string variant for easy conversions.
 #[derive(Error, Debug)]
 pub enum ApiError {
     /// HTTP-level failure (useful when preserving status code and response body text).
+    ///
+    /// `body` holds the JSON error envelope parsed from `text` when the
+    /// server sent one; prefer [`ApiError::kind`] over matching on its fields.
     #[error("HTTP error {status}: {text}")]
     Http {
-        status: reqwest::StatusCode,
+        status: StatusCode,
         text: String,
+        body: Option<ErrorBody>,
     },
 
     /// Network/request error from reqwest.
@@ -30,6 +36,72 @@ pub enum ApiError {
     #[error("EventSource error: {0}")]
     EventSource(String),
 
+    /// The requested model is not in the endpoint's model list.
+    #[error("Unknown model {model:?}; available: {available:?}")]
+    UnknownModel {
+        model: String,
+        available: Vec<String>,
+    },
+
+    /// A request field is outside the range the API accepts; raised by
+    /// [`ApiRequest::validate`][crate::api::ApiRequest::validate] before
+    /// anything is sent.
+    #[error("Invalid value for `{field}`: {message}")]
+    Validation {
+        field: &'static str,
+        message: String,
+    },
+
+    /// A replayed request did not match any interaction in the cassette.
+    /// Carries a diff against the closest recorded request.
+    #[error("Request does not match the cassette:\n{0}")]
+    CassetteMismatch(String),
+
+    /// Every key in the client's [`KeyPool`][crate::api::KeyPool] has been
+    /// evicted.
+    #[error("Every API key in the pool has been evicted")]
+    NoUsableKeys,
+
+    /// A streaming response produced no chunk within the first-byte timeout.
+    #[error("Stream produced no data within {0:?}")]
+    StreamFirstByteTimeout(std::time::Duration),
+
+    /// No chunk arrived within the stream's idle timeout.
+    #[error("Stream was idle for more than {0:?}")]
+    StreamIdleTimeout(std::time::Duration),
+
+    /// The stream did not finish within its total deadline.
+    #[error("Stream exceeded its total deadline of {0:?}")]
+    StreamDeadlineExceeded(std::time::Duration),
+
+    /// The model's reply could not be deserialized into the requested type,
+    /// even after re-asking; see
+    /// [`StructuredOutput`][crate::api::StructuredOutput].
+    #[error("Reply did not match the expected type after {attempts} attempt(s): {message}")]
+    SchemaMismatch {
+        attempts: u32,
+        /// The last deserialization error.
+        message: String,
+        /// The last reply, as received.
+        content: String,
+    },
+
+    /// A typed agent run hit its [`Budget`][crate::agent::Budget] before the
+    /// model gave a final answer.
+    #[error("Agent run stopped before a final answer: {0}")]
+    BudgetExceeded(crate::agent::BudgetLimit),
+
+    /// A typed agent run used up its tool-call steps and the final,
+    /// tool-free reply did not match the expected type; see
+    /// [`with_max_steps`][crate::agent::DeepseekAgent::with_max_steps].
+    #[error("Agent run stopped: tool-call limit of {max_steps} steps reached")]
+    MaxStepsReached { max_steps: u32 },
+
+    /// A [`ConversationStore`][crate::conversation::ConversationStore] could
+    /// not load or save messages.
+    #[error("Conversation store error: {0}")]
+    Store(String),
+
     /// IO error (fallback).
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
@@ -46,14 +118,167 @@ pub enum ApiError {
 /// Common `Result` alias used throughout the crate.
 pub type Result<T> = std::result::Result<T, ApiError>;
 
+/// The JSON error envelope returned by DeepSeek and OpenAI-compatible APIs:
+/// `{"error": {"message": "...", "type": "...", "code": "..."}}`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ErrorBody {
+    pub message: String,
+    #[serde(rename = "type", default)]
+    pub r#type: Option<String>,
+    /// Some providers send numeric codes; they are kept as strings.
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub code: Option<String>,
+    #[serde(default)]
+    pub param: Option<String>,
+}
+
+impl ErrorBody {
+    /// Parse the envelope from a response body, if it is one.
+    pub fn parse(text: &str) -> Option<Self> {
+        #[derive(Deserialize)]
+        struct Envelope {
+            error: ErrorBody,
+        }
+        serde_json::from_str::<Envelope>(text).ok().map(|e| e.error)
+    }
+
+    /// Does any field mention `needle`?
+    fn mentions(&self, needle: &str) -> bool {
+        self.message.to_ascii_lowercase().contains(needle)
+            || self.r#type.as_deref().is_some_and(|t| t.contains(needle))
+            || self.code.as_deref().is_some_and(|c| c.contains(needle))
+    }
+}
+
+fn string_or_number<'de, D: Deserializer<'de>>(
+    d: D,
+) -> std::result::Result<Option<String>, D::Error> {
+    Ok(match Option::<serde_json::Value>::deserialize(d)? {
+        Some(serde_json::Value::String(s)) => Some(s),
+        Some(serde_json::Value::Null) | None => None,
+        Some(other) => Some(other.to_string()),
+    })
+}
+
+/// Broad classification of an [`ApiError`], from [`ApiError::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The request was rejected as malformed or invalid (400, 404, 422, an
+    /// unknown model, a failed client-side validation).
+    InvalidRequest,
+    /// The prompt plus `max_tokens` does not fit the model's context window.
+    ContextLengthExceeded,
+    /// The API key is missing, invalid or not allowed (401, 403), or every
+    /// pooled key has been evicted.
+    Authentication,
+    /// The account is out of balance or quota (402, `insufficient_quota`).
+    InsufficientBalance,
+    /// Too many requests (429).
+    RateLimited,
+    /// The server is overloaded (503).
+    Overloaded,
+    /// Any other 5xx response.
+    Server,
+    /// The request or stream timed out (408, transport or stream timeouts).
+    Timeout,
+    /// The server could not be reached or the connection broke.
+    Network,
+    /// A response could not be decoded.
+    Decode,
+    /// Anything else.
+    Other,
+}
+
 impl ApiError {
     /// Convenience constructor for the `Http` variant.
-    pub fn http_error(status: reqwest::StatusCode, text: impl Into<String>) -> Self {
+    ///
+    /// The JSON error envelope is parsed from `text` if present.
+    pub fn http_error(status: StatusCode, text: impl Into<String>) -> Self {
+        let text = text.into();
         ApiError::Http {
             status,
-            text: text.into(),
+            body: ErrorBody::parse(&text),
+            text,
+        }
+    }
+
+    /// The HTTP status code, for [`ApiError::Http`].
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ApiError::Http { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// The parsed error envelope, for [`ApiError::Http`] responses that sent
+    /// one.
+    pub fn body(&self) -> Option<&ErrorBody> {
+        match self {
+            ApiError::Http { body, .. } => body.as_ref(),
+            _ => None,
         }
     }
+
+    /// Classify the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ApiError::Http { status, body, .. } => {
+                let mentions = |needle| body.as_ref().is_some_and(|b| b.mentions(needle));
+                if mentions("context_length") || mentions("context length") {
+                    ErrorKind::ContextLengthExceeded
+                } else if mentions("insufficient_quota") {
+                    ErrorKind::InsufficientBalance
+                } else {
+                    match status.as_u16() {
+                        400 | 404 | 422 => ErrorKind::InvalidRequest,
+                        401 | 403 => ErrorKind::Authentication,
+                        402 => ErrorKind::InsufficientBalance,
+                        408 => ErrorKind::Timeout,
+                        429 => ErrorKind::RateLimited,
+                        503 => ErrorKind::Overloaded,
+                        500..=599 => ErrorKind::Server,
+                        _ => ErrorKind::Other,
+                    }
+                }
+            }
+            ApiError::Reqwest(e) if e.is_timeout() => ErrorKind::Timeout,
+            ApiError::Reqwest(e) if e.is_decode() => ErrorKind::Decode,
+            ApiError::Reqwest(e) if e.is_connect() || e.is_request() || e.is_body() => {
+                ErrorKind::Network
+            }
+            ApiError::Reqwest(_) => ErrorKind::Other,
+            ApiError::Json(_) | ApiError::SchemaMismatch { .. } => ErrorKind::Decode,
+            ApiError::EventSource(_) => ErrorKind::Network,
+            ApiError::UnknownModel { .. } | ApiError::Validation { .. } => {
+                ErrorKind::InvalidRequest
+            }
+            ApiError::NoUsableKeys => ErrorKind::Authentication,
+            ApiError::StreamFirstByteTimeout(_)
+            | ApiError::StreamIdleTimeout(_)
+            | ApiError::StreamDeadlineExceeded(_) => ErrorKind::Timeout,
+            ApiError::CassetteMismatch(_)
+            | ApiError::BudgetExceeded(_)
+            | ApiError::MaxStepsReached { .. }
+            | ApiError::Store(_)
+            | ApiError::Io(_)
+            | ApiError::Other(_)
+            | ApiError::Unknown => ErrorKind::Other,
+        }
+    }
+
+    /// Returns `true` if sending the same request again may succeed: rate
+    /// limits, server errors, timeouts and network failures.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.kind(),
+            ErrorKind::RateLimited
+                | ErrorKind::Overloaded
+                | ErrorKind::Server
+                | ErrorKind::Timeout
+                | ErrorKind::Network
+        )
+    }
 }
 
 impl From<&str> for ApiError {
@@ -67,3 +292,71 @@ impl From<String> for ApiError {
         ApiError::Other(s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_error_envelope() {
+        let err = ApiError::http_error(
+            StatusCode::PAYMENT_REQUIRED,
+            r#"{"error":{"message":"Insufficient Balance","type":"unknown_error","param":null,"code":"invalid_request_error"}}"#,
+        );
+        let body = err.body().unwrap();
+        assert_eq!(body.message, "Insufficient Balance");
+        assert_eq!(body.r#type.as_deref(), Some("unknown_error"));
+        assert_eq!(body.code.as_deref(), Some("invalid_request_error"));
+        assert_eq!(err.kind(), ErrorKind::InsufficientBalance);
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn kind_reads_the_stored_body() {
+        let err = ApiError::Http {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            text: String::new(),
+            body: ErrorBody::parse(r#"{"error":{"message":"quota","code":"insufficient_quota"}}"#),
+        };
+        assert_eq!(err.body().unwrap().message, "quota");
+        assert_eq!(err.kind(), ErrorKind::InsufficientBalance);
+    }
+
+    #[test]
+    fn numeric_codes_and_plain_text_bodies() {
+        let err = ApiError::http_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            r#"{"error":{"message":"oops","code":500}}"#,
+        );
+        assert_eq!(err.body().unwrap().code.as_deref(), Some("500"));
+
+        let err = ApiError::http_error(StatusCode::SERVICE_UNAVAILABLE, "busy");
+        assert!(err.body().is_none());
+        assert_eq!(err.kind(), ErrorKind::Overloaded);
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn classifies_context_length_and_quota() {
+        let err = ApiError::http_error(
+            StatusCode::BAD_REQUEST,
+            r#"{"error":{"message":"This model's maximum context length is 65536 tokens. However, you requested 70000 tokens.","type":"invalid_request_error"}}"#,
+        );
+        assert_eq!(err.kind(), ErrorKind::ContextLengthExceeded);
+
+        let err = ApiError::http_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            r#"{"error":{"message":"You exceeded your current quota","type":"insufficient_quota"}}"#,
+        );
+        assert_eq!(err.kind(), ErrorKind::InsufficientBalance);
+
+        assert_eq!(
+            ApiError::http_error(StatusCode::UNAUTHORIZED, "").kind(),
+            ErrorKind::Authentication
+        );
+        assert_eq!(
+            ApiError::http_error(StatusCode::UNPROCESSABLE_ENTITY, "").kind(),
+            ErrorKind::InvalidRequest
+        );
+    }
+}
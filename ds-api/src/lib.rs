@@ -62,7 +62,7 @@ async fn main() {
 
 ## Injecting messages mid-run
 
-[`DeepseekAgent::with_interrupt_channel`] returns a sender you can use to push
+[`DeepseekAgent::interrupt_sender`] returns a sender you can use to push
 messages into the agent while it's still running. Messages are picked up after
 the current tool-execution round and added to the conversation before the next
 API turn.
@@ -151,18 +151,39 @@ pub mod mcp_server;
 pub mod raw; // raw types remain accessible via `ds_api::raw` but are not the primary public API
 pub mod tool_trait;
 
-pub use agent::{AgentEvent, DeepseekAgent, ToolCallChunk, ToolCallResult, ToolInjection};
-pub use api::{ApiClient, ApiRequest};
-pub use conversation::{Conversation, LlmSummarizer, SlidingWindowSummarizer};
-pub use error::ApiError;
+pub use agent::{
+    AgentEvent, ApprovalHandle, ApprovalPolicy, Budget, BudgetLimit, CancelHandle, Checkpoint,
+    DeepseekAgent, PendingToolCall, RequestOptions, ToolCallChunk, ToolCallResult, ToolInjection,
+    TurnContext,
+};
+pub use api::{
+    ApiClient, ApiRequest, Cassette, ChatBackend, CostStats, CostTracker, Endpoint, FallbackClient,
+    FimRequest, KeyPool, KeySelection, MockBackend, MockReply, ModelPrice, PriceTable, RateLimit,
+    RateLimitStats, RetryEvent, RetryPolicy, SchemaMode, StreamTimeouts, StructuredOutput,
+};
+pub use conversation::{
+    Conversation, ConversationStore, JsonlStore, LlmSummarizer, SlidingWindowSummarizer,
+};
+pub use error::{ApiError, ErrorBody, ErrorKind};
 
 pub use tool_trait::Tool;
 pub use tool_trait::ToolBundle;
 
 pub use ds_api_macros::tool;
 
+/// Re-exported so typed output (`send_typed`, `chat_typed`) can be used with
+/// the same `schemars` version this crate was built against.
+pub use schemars;
+
+/// Re-exported for tools that stop work when the agent no longer needs the
+/// result; see [`Tool::call_with_cancel`].
+pub use tokio_util::sync::CancellationToken;
+
 #[cfg(feature = "mcp")]
 pub use mcp::McpTool;
 
+#[cfg(feature = "sqlite")]
+pub use conversation::SqliteStore;
+
 #[cfg(feature = "mcp-server")]
 pub use mcp_server::{McpServer, McpServerError};
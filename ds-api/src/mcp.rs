@@ -47,13 +47,17 @@
 use async_trait::async_trait;
 use rmcp::{
     ServiceExt,
-    model::CallToolRequestParams,
-    service::{Peer, RoleClient, RunningService},
+    model::{
+        CallToolRequest, CallToolRequestParams, CancelledNotificationParam, ClientRequest,
+        RequestId, ServerResult,
+    },
+    service::{Peer, PeerRequestOptions, RoleClient, RunningService, ServiceError},
     transport::{StreamableHttpClientTransport, TokioChildProcess},
 };
-use serde_json::Value;
+use serde_json::{Value, json};
 use std::sync::Arc;
 use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
 use tracing::{error, instrument};
 
 use crate::raw::request::message::ToolType;
@@ -385,6 +389,14 @@ impl Tool for McpTool {
     }
 
     async fn call(&self, name: &str, args: Value) -> Value {
+        self.call_with_cancel(name, args, CancellationToken::new())
+            .await
+    }
+
+    /// Forward the call to the server, and tell the server to stop with a
+    /// `notifications/cancelled` if `cancel` fires or the call is dropped
+    /// before the result arrives.
+    async fn call_with_cancel(&self, name: &str, args: Value, cancel: CancellationToken) -> Value {
         let arguments = args.as_object().cloned().map(|m| m.into_iter().collect());
         let owned_name: std::borrow::Cow<'static, str> = name.to_string().into();
 
@@ -393,7 +405,33 @@ impl Tool for McpTool {
             None => CallToolRequestParams::new(owned_name),
         };
 
-        match self.peer.call_tool(params).await {
+        let request = ClientRequest::CallToolRequest(CallToolRequest::new(params));
+        let response = match self
+            .peer
+            .send_cancellable_request(request, PeerRequestOptions::no_options())
+            .await
+        {
+            Ok(handle) => {
+                let notify = NotifyCancelled {
+                    peer: Some((*self.peer).clone()),
+                    request_id: handle.id.clone(),
+                };
+                let response = tokio::select! {
+                    response = handle.await_response() => response,
+                    _ = cancel.cancelled() => return json!({ "error": "cancelled" }),
+                };
+                notify.disarm();
+                response
+            }
+            Err(e) => Err(e),
+        };
+
+        let response = response.and_then(|result| match result {
+            ServerResult::CallToolResult(result) => Ok(result),
+            _ => Err(ServiceError::UnexpectedResponse),
+        });
+
+        match response {
             Ok(result) => {
                 // MCP returns a list of content items; flatten them into a
                 // single JSON value that the model can read.
@@ -404,10 +442,10 @@ impl Tool for McpTool {
                     .collect();
 
                 // Apply max_content_items limit if set
-                if let Some(max_items) = self.max_content_items {
-                    if contents.len() > max_items {
-                        contents.truncate(max_items);
-                    }
+                if let Some(max_items) = self.max_content_items
+                    && contents.len() > max_items
+                {
+                    contents.truncate(max_items);
                 }
 
                 let result_value = match contents.len() {
@@ -442,3 +480,37 @@ impl Tool for McpTool {
         }
     }
 }
+
+/// Sends `notifications/cancelled` for a request when dropped, unless the
+/// response arrived first.
+struct NotifyCancelled {
+    peer: Option<Peer<RoleClient>>,
+    request_id: RequestId,
+}
+
+impl NotifyCancelled {
+    fn disarm(mut self) {
+        self.peer = None;
+    }
+}
+
+impl Drop for NotifyCancelled {
+    fn drop(&mut self) {
+        let Some(peer) = self.peer.take() else {
+            return;
+        };
+        let request_id = self.request_id.clone();
+        // Dropping can happen outside a runtime (e.g. at shutdown); the server
+        // then learns about it when the transport closes instead.
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move {
+                let _ = peer
+                    .notify_cancelled(CancelledNotificationParam {
+                        request_id,
+                        reason: Some("cancelled by client".to_string()),
+                    })
+                    .await;
+            });
+        }
+    }
+}
@@ -13,6 +13,7 @@
 //! ## Request types
 //!
 //! - [`ChatCompletionRequest`]
+//! - [`CompletionRequest`] (FIM, beta)
 //! - [`Message`]
 //! - [`Model`]
 //! - [`Tool`]
@@ -25,6 +26,8 @@
 //!
 //! - [`ChatCompletionResponse`]
 //! - [`ChatCompletionChunk`]
+//! - [`CompletionResponse`] / [`CompletionChunk`] (FIM, beta)
+//! - [`ModelList`] / [`Balance`] (account endpoints)
 //! - [`Choice`]
 //! - [`Usage`]
 //!
@@ -134,6 +137,7 @@
 //!         stream: Some(false),
 //!         response_format: Some(ResponseFormat {
 //!             r#type: ResponseFormatType::JsonObject,
+//!             json_schema: None,
 //!         }),
 //!         ..Default::default()
 //!     };
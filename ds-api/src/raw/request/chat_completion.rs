@@ -6,7 +6,7 @@ use super::{
     stream_options::StreamOptions, thinking::Thinking, tool::Tool, tool_choice::ToolChoice,
 };
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ChatCompletionRequest {
     /// List of messages in the conversation.
@@ -175,6 +175,7 @@ mod tests {
             messages: vec![Message {
                 role: Role::User,
                 content: Some("Hello, world!".to_string()),
+                parts: None,
                 name: None,
                 tool_call_id: None,
                 tool_calls: None,
@@ -238,6 +239,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_extra_body_serialize_merge() {
         use crate::raw::model::Model;
         use serde_json::{Map, Value, json};
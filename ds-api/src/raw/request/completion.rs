@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{model::Model, stop::Stop, stream_options::StreamOptions};
+
+/// Request body for the (beta) FIM completion endpoint, `POST /beta/completions`.
+///
+/// The model fills in the text between `prompt` and `suffix`, which makes this
+/// endpoint suitable for code completion in editors.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompletionRequest {
+    /// The model ID to use. FIM completion is served by `deepseek-chat`.
+    pub model: Model,
+
+    /// The text preceding the completion (e.g. code before the cursor).
+    pub prompt: String,
+
+    /// The text following the completion (e.g. code after the cursor).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
+
+    /// If true, the prompt is echoed back in addition to the completion.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub echo: Option<bool>,
+
+    /// Possible values: >= -2 and <= 2
+    /// Penalises new tokens based on their frequency in the text so far.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f32>,
+
+    /// Possible values: <= 20
+    /// Include the log-probabilities of the `logprobs` most likely tokens at each position.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<u32>,
+
+    /// Maximum number of tokens to generate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+
+    /// Possible values: >= -2 and <= 2
+    /// Penalises new tokens based on whether they already appear in the text so far.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f32>,
+
+    /// A string or up to 16 strings. Generation stops when one of them is produced.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Stop>,
+
+    /// If true, the completion is streamed as SSE, ending with `data: [DONE]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+
+    /// Options related to streaming output. Only valid when `stream` is true.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+
+    /// Possible values: <= 2
+    /// Sampling temperature between 0 and 2.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    /// Possible values: <= 1
+    /// Nucleus sampling: only the tokens comprising the top `top_p` probability mass are considered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+
+    /// Extra arbitrary JSON body fields, merged into the top-level request JSON.
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub extra_body: Option<Map<String, Value>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_only_set_fields() {
+        let req = CompletionRequest {
+            prompt: "def fib(a):".to_string(),
+            suffix: Some("    return fib(a-1) + fib(a-2)".to_string()),
+            max_tokens: Some(128),
+            ..Default::default()
+        };
+
+        let v = serde_json::to_value(&req).unwrap();
+        assert_eq!(v["model"], "deepseek-chat");
+        assert_eq!(v["prompt"], "def fib(a):");
+        assert_eq!(v["suffix"], "    return fib(a-1) + fib(a-2)");
+        assert_eq!(v["max_tokens"], 128);
+        assert!(v.get("echo").is_none());
+        assert!(v.get("stream").is_none());
+    }
+}
@@ -0,0 +1,95 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Serialize};
+
+/// One part of a multimodal message (`content` in array form).
+///
+/// Image parts are understood by vision models on OpenAI-compatible
+/// providers; the DeepSeek API itself accepts text only.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+}
+
+/// The image of a [`ContentPart::ImageUrl`]: an `http(s)` URL or a
+/// `data:` URI with base64-encoded bytes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageUrl {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<ImageDetail>,
+}
+
+/// How closely the model should look at an image; `Low` is cheaper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageDetail {
+    Auto,
+    Low,
+    High,
+}
+
+impl ContentPart {
+    /// A text part.
+    pub fn text(text: impl Into<String>) -> Self {
+        ContentPart::Text { text: text.into() }
+    }
+
+    /// An image referenced by URL.
+    pub fn image_url(url: impl Into<String>) -> Self {
+        ContentPart::ImageUrl {
+            image_url: ImageUrl {
+                url: url.into(),
+                detail: None,
+            },
+        }
+    }
+
+    /// An inline image, sent as a base64 `data:` URI.
+    ///
+    /// `mime` is the image type, e.g. `"image/png"`.
+    pub fn image_data(mime: &str, bytes: &[u8]) -> Self {
+        Self::image_url(format!("data:{mime};base64,{}", STANDARD.encode(bytes)))
+    }
+
+    /// Set the [`ImageDetail`] of an image part; text parts are unchanged.
+    pub fn with_detail(mut self, detail: ImageDetail) -> Self {
+        if let ContentPart::ImageUrl { image_url } = &mut self {
+            image_url.detail = Some(detail);
+        }
+        self
+    }
+
+    /// The text of a text part.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            ContentPart::Text { text } => Some(text),
+            ContentPart::ImageUrl { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn serializes_openai_part_shapes() {
+        let parts = vec![
+            ContentPart::text("what is this?"),
+            ContentPart::image_url("https://example.com/a.png").with_detail(ImageDetail::Low),
+            ContentPart::image_data("image/png", b"hi"),
+        ];
+        assert_eq!(
+            serde_json::to_value(&parts).unwrap(),
+            json!([
+                { "type": "text", "text": "what is this?" },
+                { "type": "image_url", "image_url": { "url": "https://example.com/a.png", "detail": "low" } },
+                { "type": "image_url", "image_url": { "url": "data:image/png;base64,aGk=" } }
+            ])
+        );
+    }
+}
@@ -1,38 +1,139 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
+
+use super::content_part::{ContentPart, ImageDetail};
 
 // Unified message struct
 // This struct is used both for the `messages` array in requests and the `message` field in responses.
 // All fields are optional to cover different roles and scenarios.
-#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(from = "MessageRepr")]
 pub struct Message {
     /// default role is User
     pub role: Role,
 
-    /// The content may be null for assistant messages (only when `tool_calls` are present)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// The content may be null for assistant messages (only when `tool_calls` are present).
+    ///
+    /// For multimodal messages this is the text view: the text parts of
+    /// [`parts`][Self::parts] joined by newlines.  It is not sent while
+    /// `parts` is set, so editing it on a multimodal message changes nothing
+    /// on the wire; edit `parts`, or clear it to send this string.
     pub content: Option<String>,
 
+    /// Multimodal content (text and images).  When set, it takes precedence
+    /// over [`content`][Self::content]: it is sent as the array form of
+    /// `content` and the `content` string is ignored.
+    pub parts: Option<Vec<ContentPart>>,
+
     /// Optional name to identify a user or function
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
     /// Required when role = "tool"; links to the previous tool call ID
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
 
     /// Present when role = "assistant" and the model requested tool calls
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<ToolCall>>,
 
     /// Reasoning content produced by the model (may appear only in responses)
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub reasoning_content: Option<String>,
 
     /// Beta: if true, forces the model to begin its reply with the prefix content provided in this assistant message
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub prefix: Option<bool>,
 }
 
+/// `content` on the wire: a plain string or an array of parts.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ContentRepr<T, P> {
+    Text(T),
+    Parts(P),
+}
+
+/// Wire form of [`Message`], read with `content` in either form.
+#[derive(Deserialize)]
+struct MessageRepr {
+    role: Role,
+    #[serde(default)]
+    content: Option<ContentRepr<String, Vec<ContentPart>>>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    tool_call_id: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<ToolCall>>,
+    #[serde(default)]
+    reasoning_content: Option<String>,
+    #[serde(default)]
+    prefix: Option<bool>,
+}
+
+impl From<MessageRepr> for Message {
+    fn from(repr: MessageRepr) -> Self {
+        let (content, parts) = match repr.content {
+            None => (None, None),
+            Some(ContentRepr::Text(text)) => (Some(text), None),
+            Some(ContentRepr::Parts(parts)) => (text_view(&parts), Some(parts)),
+        };
+        Message {
+            role: repr.role,
+            content,
+            parts,
+            name: repr.name,
+            tool_call_id: repr.tool_call_id,
+            tool_calls: repr.tool_calls,
+            reasoning_content: repr.reasoning_content,
+            prefix: repr.prefix,
+        }
+    }
+}
+
+/// Borrowed wire form of [`Message`] for serialization.
+#[derive(Serialize)]
+struct MessageRef<'a> {
+    role: &'a Role,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<ContentRepr<&'a str, &'a [ContentPart]>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: &'a Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: &'a Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_calls: &'a Option<Vec<ToolCall>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning_content: &'a Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prefix: &'a Option<bool>,
+}
+
+impl Serialize for Message {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let content = match (&self.parts, &self.content) {
+            (Some(parts), _) => Some(ContentRepr::Parts(parts.as_slice())),
+            (None, Some(text)) => Some(ContentRepr::Text(text.as_str())),
+            (None, None) => None,
+        };
+        MessageRef {
+            role: &self.role,
+            content,
+            name: &self.name,
+            tool_call_id: &self.tool_call_id,
+            tool_calls: &self.tool_calls,
+            reasoning_content: &self.reasoning_content,
+            prefix: &self.prefix,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// The text parts of `parts` joined by newlines, or `None` without text.
+fn text_view(parts: &[ContentPart]) -> Option<String> {
+    let texts: Vec<&str> = parts.iter().filter_map(ContentPart::as_text).collect();
+    (!texts.is_empty()).then(|| texts.join("\n"))
+}
+
+/// Rough token cost of an image at the given detail, for estimates.
+const IMAGE_TOKENS_LOW: usize = 85;
+const IMAGE_TOKENS_HIGH: usize = 765;
+
 /// The `name` value written by built-in summarizers to mark a system message
 /// as an auto-generated summary that may be replaced on the next summarization
 /// pass.  Any `Role::System` message **without** this tag is treated as a
@@ -44,6 +145,7 @@ impl Message {
         Self {
             role,
             content: Some(message.to_string()),
+            parts: None,
             name: None,
             tool_call_id: None,
             tool_calls: None,
@@ -64,6 +166,109 @@ impl Message {
         Self::new(Role::System, message)
     }
 
+    /// A multimodal message built from content parts.
+    ///
+    /// ```
+    /// use ds_api::raw::request::message::{Message, Role};
+    /// use ds_api::raw::ContentPart;
+    ///
+    /// let msg = Message::from_parts(
+    ///     Role::User,
+    ///     vec![
+    ///         ContentPart::text("What is in this picture?"),
+    ///         ContentPart::image_url("https://example.com/cat.png"),
+    ///     ],
+    /// );
+    /// assert_eq!(msg.content.as_deref(), Some("What is in this picture?"));
+    /// ```
+    pub fn from_parts(role: Role, parts: Vec<ContentPart>) -> Self {
+        Self {
+            role,
+            content: text_view(&parts),
+            parts: Some(parts),
+            ..Default::default()
+        }
+    }
+
+    /// Append a content part, turning a plain-text message into a multimodal
+    /// one (builder-style).
+    ///
+    /// ```
+    /// use ds_api::raw::request::message::Message;
+    /// use ds_api::raw::ContentPart;
+    ///
+    /// let msg = Message::user("Describe this diagram.")
+    ///     .with_part(ContentPart::image_url("https://example.com/diagram.png"));
+    /// assert_eq!(msg.image_count(), 1);
+    /// ```
+    pub fn with_part(mut self, part: ContentPart) -> Self {
+        let parts = self.parts.get_or_insert_with(|| {
+            self.content
+                .take()
+                .map(ContentPart::text)
+                .into_iter()
+                .collect()
+        });
+        parts.push(part);
+        self.content = text_view(parts);
+        self
+    }
+
+    /// Number of image parts.
+    pub fn image_count(&self) -> usize {
+        self.parts.as_deref().map_or(0, |parts| {
+            parts
+                .iter()
+                .filter(|p| matches!(p, ContentPart::ImageUrl { .. }))
+                .count()
+        })
+    }
+
+    /// Rough token estimate for the images in this message.
+    pub(crate) fn image_tokens(&self) -> usize {
+        self.parts.as_deref().map_or(0, |parts| {
+            parts
+                .iter()
+                .map(|p| match p {
+                    ContentPart::ImageUrl { image_url } => match image_url.detail {
+                        Some(ImageDetail::Low) => IMAGE_TOKENS_LOW,
+                        _ => IMAGE_TOKENS_HIGH,
+                    },
+                    ContentPart::Text { .. } => 0,
+                })
+                .sum()
+        })
+    }
+
+    /// An assistant message the model must continue from (beta chat prefix
+    /// completion).  It has to be the last message of a request.
+    pub fn assistant_prefix(prefix: &str) -> Self {
+        Self {
+            prefix: Some(true),
+            ..Self::assistant(prefix)
+        }
+    }
+
+    /// Returns `true` if this is an assistant prefix message; see
+    /// [`assistant_prefix`][Self::assistant_prefix].
+    pub fn is_prefix(&self) -> bool {
+        matches!(self.role, Role::Assistant) && self.prefix == Some(true)
+    }
+
+    /// Merge the model's `continuation` into this prefix message, giving one
+    /// ordinary assistant message whose content is prefix + continuation.
+    pub fn stitch(self, continuation: Message) -> Message {
+        let content = match (self.content, continuation.content) {
+            (Some(prefix), Some(rest)) => Some(prefix + &rest),
+            (prefix, rest) => prefix.or(rest),
+        };
+        Message {
+            content,
+            prefix: None,
+            ..continuation
+        }
+    }
+
     /// Returns `true` if this is an auto-generated summary message produced by
     /// a built-in [`Summarizer`](crate::conversation::Summarizer) implementation.
     ///
@@ -114,3 +319,47 @@ pub struct FunctionCall {
     pub name: String,
     pub arguments: String, // JSON string
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn plain_text_content_stays_a_string() {
+        let v = serde_json::to_value(Message::user("hi")).unwrap();
+        assert_eq!(v, json!({ "role": "user", "content": "hi" }));
+    }
+
+    #[test]
+    fn parts_round_trip_through_the_array_form() {
+        let msg = Message::user("look")
+            .with_part(ContentPart::image_url("https://example.com/a.png"))
+            .with_part(ContentPart::text("and compare"));
+        assert_eq!(msg.content.as_deref(), Some("look\nand compare"));
+
+        let v = serde_json::to_value(&msg).unwrap();
+        assert_eq!(v["content"][0], json!({ "type": "text", "text": "look" }));
+        assert_eq!(v["content"][1]["type"], "image_url");
+
+        let back: Message = serde_json::from_value(v).unwrap();
+        assert_eq!(back.parts, msg.parts);
+        assert_eq!(back.content, msg.content);
+        assert_eq!(back.image_count(), 1);
+    }
+
+    #[test]
+    fn parts_take_precedence_over_content() {
+        let mut msg = Message::user("look").with_part(ContentPart::image_url("https://a/b.png"));
+        msg.content = Some("edited".into());
+        let v = serde_json::to_value(&msg).unwrap();
+        assert_eq!(v["content"][0], json!({ "type": "text", "text": "look" }));
+    }
+
+    #[test]
+    fn null_content_deserializes_to_none() {
+        let msg: Message =
+            serde_json::from_value(json!({ "role": "assistant", "content": null })).unwrap();
+        assert!(msg.content.is_none() && msg.parts.is_none());
+    }
+}
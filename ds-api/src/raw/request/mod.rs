@@ -1,4 +1,6 @@
 pub mod chat_completion;
+pub mod completion;
+pub mod content_part;
 pub mod message;
 pub mod model;
 pub mod response_format;
@@ -9,9 +11,11 @@ pub mod tool;
 pub mod tool_choice;
 
 pub use chat_completion::ChatCompletionRequest;
+pub use completion::CompletionRequest;
+pub use content_part::{ContentPart, ImageDetail, ImageUrl};
 pub use message::{FunctionCall, Message, Role, ToolCall, ToolType};
 pub use model::Model;
-pub use response_format::{ResponseFormat, ResponseFormatType};
+pub use response_format::{JsonSchemaFormat, ResponseFormat, ResponseFormatType};
 pub use stop::Stop;
 pub use stream_options::StreamOptions;
 pub use thinking::{Thinking, ThinkingType};
@@ -1,13 +1,33 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseFormat {
     pub r#type: ResponseFormatType,
+    /// The schema for [`ResponseFormatType::JsonSchema`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_schema: Option<JsonSchemaFormat>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ResponseFormatType {
     Text,
     JsonObject,
+    /// Structured output constrained by a JSON Schema.  Supported by
+    /// OpenAI-compatible providers, not by the DeepSeek API itself.
+    JsonSchema,
+}
+
+/// A named JSON Schema for [`ResponseFormatType::JsonSchema`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    /// Schema name; `a-z`, `A-Z`, `0-9`, `_` and `-` only.
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub schema: Value,
+    /// Ask the provider to follow the schema exactly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
 }
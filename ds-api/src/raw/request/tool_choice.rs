@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use super::message::ToolType;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ToolChoice {
     String(ToolChoiceType),
@@ -16,7 +16,7 @@ pub enum ToolChoice {
     Object(ToolChoiceObject),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ToolChoiceType {
     None,
@@ -24,13 +24,13 @@ pub enum ToolChoiceType {
     Required,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolChoiceObject {
     pub r#type: ToolType,
     pub function: FunctionName,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionName {
     pub name: String,
 }
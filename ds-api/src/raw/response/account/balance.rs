@@ -0,0 +1,56 @@
+use serde::Deserialize;
+
+/// Response of `GET /user/balance`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Balance {
+    /// Whether the balance is sufficient for API calls.
+    pub is_available: bool,
+    /// One entry per currency.
+    pub balance_infos: Vec<BalanceInfo>,
+}
+
+/// Account balance in a single currency.
+///
+/// Amounts are kept as the decimal strings returned by the API to avoid
+/// rounding; use [`total`][BalanceInfo::total] for a numeric value.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BalanceInfo {
+    /// `"CNY"` or `"USD"`.
+    pub currency: String,
+    /// Total available balance, including granted balance.
+    pub total_balance: String,
+    /// Total granted (not yet expired) balance.
+    pub granted_balance: String,
+    /// Total topped-up balance.
+    pub topped_up_balance: String,
+}
+
+impl BalanceInfo {
+    /// The total balance parsed as a float, or `None` if it is malformed.
+    pub fn total(&self) -> Option<f64> {
+        self.total_balance.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserializes_balance() {
+        let json = r#"{
+            "is_available": true,
+            "balance_infos": [{
+                "currency": "CNY",
+                "total_balance": "110.00",
+                "granted_balance": "10.00",
+                "topped_up_balance": "100.00"
+            }]
+        }"#;
+        let b: Balance = serde_json::from_str(json).unwrap();
+
+        assert!(b.is_available);
+        assert_eq!(b.balance_infos[0].currency, "CNY");
+        assert_eq!(b.balance_infos[0].total(), Some(110.0));
+    }
+}
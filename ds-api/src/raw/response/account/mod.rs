@@ -0,0 +1,5 @@
+pub mod balance;
+pub mod model_list;
+
+pub use balance::{Balance, BalanceInfo};
+pub use model_list::{ModelInfo, ModelList};
@@ -0,0 +1,55 @@
+use serde::Deserialize;
+
+use crate::raw::Model;
+
+/// Response of `GET /models`: the models available to the API key.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelList {
+    /// Always `"list"`.
+    pub object: String,
+    pub data: Vec<ModelInfo>,
+}
+
+/// A single entry of [`ModelList`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModelInfo {
+    /// The model identifier, as accepted in the `model` field of a request.
+    pub id: String,
+    /// Always `"model"`.
+    pub object: String,
+    pub owned_by: String,
+}
+
+impl ModelList {
+    /// Returns `true` if `model` is one of the listed model ids.
+    pub fn contains(&self, model: &Model) -> bool {
+        self.data.iter().any(|m| m.id == model.as_str())
+    }
+
+    /// Iterate over the listed model ids.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.data.iter().map(|m| m.id.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserializes_and_looks_up_models() {
+        let json = r#"{
+            "object": "list",
+            "data": [
+                { "id": "deepseek-chat", "object": "model", "owned_by": "deepseek" },
+                { "id": "deepseek-reasoner", "object": "model", "owned_by": "deepseek" }
+            ]
+        }"#;
+        let list: ModelList = serde_json::from_str(json).unwrap();
+
+        assert!(list.contains(&Model::DeepseekChat));
+        assert!(list.contains(&Model::Custom("deepseek-reasoner".to_string())));
+        assert!(!list.contains(&Model::Custom("gpt-4o".to_string())));
+        assert_eq!(list.ids().count(), 2);
+    }
+}
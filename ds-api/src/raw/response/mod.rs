@@ -1,10 +1,13 @@
+pub mod account;
 pub mod non_streaming;
 pub mod streaming;
 
+pub use account::{Balance, BalanceInfo, ModelInfo, ModelList};
 pub use non_streaming::{
-    ChatCompletionResponse, Choice, CompletionTokensDetails, FinishReason, Logprobs, ObjectType,
-    TokenLogprob, TopLogprob, Usage,
+    ChatCompletionResponse, Choice, CompletionChoice, CompletionLogprobs, CompletionResponse,
+    CompletionTokensDetails, FinishReason, Logprobs, ObjectType, TokenLogprob, TopLogprob, Usage,
 };
 pub use streaming::{
-    ChatCompletionChunk, ChunkChoice, ChunkObjectType, Delta, DeltaFunctionCall, DeltaToolCall,
+    ChatCompletionChunk, ChunkChoice, ChunkObjectType, CompletionChunk, CompletionChunkChoice,
+    Delta, DeltaFunctionCall, DeltaToolCall,
 };
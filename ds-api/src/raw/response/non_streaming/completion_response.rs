@@ -0,0 +1,37 @@
+use serde::Deserialize;
+
+use super::{
+    finish_reason::FinishReason, logprobs::CompletionLogprobs, object_type::ObjectType,
+    usage::Usage,
+};
+use crate::raw::Model;
+
+/// Response of the FIM completion endpoint (`POST /beta/completions`).
+#[derive(Debug, Deserialize)]
+pub struct CompletionResponse {
+    pub id: String,
+    pub choices: Vec<CompletionChoice>,
+    pub created: u64,
+    pub model: Model,
+    #[serde(default)]
+    pub system_fingerprint: Option<String>,
+    #[serde(rename = "object")]
+    pub object: ObjectType,
+    pub usage: Usage,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompletionChoice {
+    pub finish_reason: FinishReason,
+    pub index: u32,
+    pub text: String,
+    #[serde(default)]
+    pub logprobs: Option<CompletionLogprobs>,
+}
+
+impl CompletionResponse {
+    /// The generated text of the first choice.
+    pub fn text(&self) -> Option<&str> {
+        self.choices.first().map(|c| c.text.as_str())
+    }
+}
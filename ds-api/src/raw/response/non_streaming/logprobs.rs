@@ -17,6 +17,19 @@ pub struct TokenLogprob {
     pub top_logprobs: Vec<TopLogprob>,
 }
 
+/// Log-probability information of a FIM completion choice.
+#[derive(Debug, Deserialize)]
+pub struct CompletionLogprobs {
+    #[serde(default)]
+    pub text_offset: Vec<u32>,
+    #[serde(default)]
+    pub token_logprobs: Vec<f32>,
+    #[serde(default)]
+    pub tokens: Vec<String>,
+    #[serde(default)]
+    pub top_logprobs: Vec<std::collections::HashMap<String, f32>>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct TopLogprob {
     pub token: String,
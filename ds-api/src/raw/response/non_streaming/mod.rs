@@ -1,5 +1,6 @@
 pub mod chat_completion_response;
 pub mod choice;
+pub mod completion_response;
 pub mod finish_reason;
 pub mod logprobs;
 pub mod object_type;
@@ -7,8 +8,9 @@ pub mod usage;
 
 pub use chat_completion_response::ChatCompletionResponse;
 pub use choice::Choice;
+pub use completion_response::{CompletionChoice, CompletionResponse};
 pub use finish_reason::FinishReason;
-pub use logprobs::{Logprobs, TokenLogprob, TopLogprob};
+pub use logprobs::{CompletionLogprobs, Logprobs, TokenLogprob, TopLogprob};
 pub use object_type::ObjectType;
 pub use usage::{CompletionTokensDetails, Usage};
 
@@ -4,4 +4,6 @@ use serde::Deserialize;
 pub enum ObjectType {
     #[serde(rename = "chat.completion")]
     ChatCompletion,
+    #[serde(rename = "text_completion")]
+    TextCompletion,
 }
@@ -1,6 +1,6 @@
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Usage {
     pub completion_tokens: u32,
     pub prompt_tokens: u32,
@@ -16,7 +16,16 @@ pub struct Usage {
     // pub prompt_tokens_details: Option<PromptTokensDetails>,
 }
 
-#[derive(Debug, Deserialize)]
+impl Usage {
+    /// Tokens spent on reasoning (thinking), or 0 when not reported.
+    pub fn reasoning_tokens(&self) -> u32 {
+        self.completion_tokens_details
+            .as_ref()
+            .map_or(0, |d| d.reasoning_tokens)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct CompletionTokensDetails {
     pub reasoning_tokens: u32,
 }
@@ -0,0 +1,28 @@
+use serde::Deserialize;
+
+use crate::raw::response::non_streaming::{CompletionLogprobs, FinishReason, ObjectType, Usage};
+
+/// One SSE chunk of a streaming FIM completion.
+#[derive(Debug, Deserialize)]
+pub struct CompletionChunk {
+    pub id: String,
+    pub choices: Vec<CompletionChunkChoice>,
+    pub created: u64,
+    pub model: String,
+    #[serde(default)]
+    pub system_fingerprint: Option<String>,
+    #[serde(rename = "object")]
+    pub object: ObjectType,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompletionChunkChoice {
+    pub index: u32,
+    pub text: String,
+    #[serde(default)]
+    pub finish_reason: Option<FinishReason>,
+    #[serde(default)]
+    pub logprobs: Option<CompletionLogprobs>,
+}
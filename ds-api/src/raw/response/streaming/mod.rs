@@ -1,9 +1,11 @@
 pub mod chat_completion_chunk;
 pub mod chunk_choice;
 pub mod chunk_object_type;
+pub mod completion_chunk;
 pub mod delta;
 
 pub use chat_completion_chunk::ChatCompletionChunk;
 pub use chunk_choice::ChunkChoice;
 pub use chunk_object_type::ChunkObjectType;
+pub use completion_chunk::{CompletionChunk, CompletionChunkChoice};
 pub use delta::{Delta, DeltaFunctionCall, DeltaToolCall};
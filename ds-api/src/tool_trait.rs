@@ -3,6 +3,7 @@ use async_trait::async_trait;
 use serde_json::Value;
 use serde_json::json;
 use std::collections::HashMap;
+use tokio_util::sync::CancellationToken;
 
 /// The core trait that all agent tools must implement.
 ///
@@ -56,6 +57,35 @@ pub trait Tool: Send + Sync {
     /// structs with `#[derive(Serialize)]`, primitives, etc.) and converts the
     /// value to `serde_json::Value` automatically.
     async fn call(&self, name: &str, args: Value) -> Value;
+
+    /// Like [`call`][Self::call], with a token that is cancelled once the agent
+    /// no longer needs the result: the call timed out, an interrupt aborted the
+    /// batch, or the stream was dropped.
+    ///
+    /// The agent always calls this method.  The default forwards to `call`
+    /// and ignores the token.  With `#[tool]`, give a method a
+    /// [`CancellationToken`] parameter to receive it; that parameter is not
+    /// part of the tool's JSON schema:
+    ///
+    /// ```no_run
+    /// use ds_api::{CancellationToken, tool};
+    ///
+    /// struct Crawler;
+    ///
+    /// #[tool]
+    /// impl ds_api::Tool for Crawler {
+    ///     /// Fetch a page, giving up when the agent stops waiting.
+    ///     async fn fetch(&self, url: String, cancel: CancellationToken) -> String {
+    ///         tokio::select! {
+    ///             _ = cancel.cancelled() => "cancelled".to_string(),
+    ///             body = async { format!("contents of {url}") } => body,
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    async fn call_with_cancel(&self, name: &str, args: Value, _cancel: CancellationToken) -> Value {
+        self.call(name, args).await
+    }
 }
 
 /// 将多个 Tool 合并为一个，方便批量注册进 agent。
@@ -78,6 +108,7 @@ impl ToolBundle {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn add<T: Tool + 'static>(mut self, tool: T) -> Self {
         let idx = self.tools.len();
         for raw in tool.raw_tools() {
@@ -95,8 +126,13 @@ impl Tool for ToolBundle {
     }
 
     async fn call(&self, name: &str, args: Value) -> Value {
+        self.call_with_cancel(name, args, CancellationToken::new())
+            .await
+    }
+
+    async fn call_with_cancel(&self, name: &str, args: Value, cancel: CancellationToken) -> Value {
         match self.index.get(name) {
-            Some(&idx) => self.tools[idx].call(name, args).await,
+            Some(&idx) => self.tools[idx].call_with_cancel(name, args, cancel).await,
             None => json!({ "error": format!("未知工具: {name}") }),
         }
     }
@@ -0,0 +1,90 @@
+//! Integration tests for the model-listing and balance endpoints.
+
+use ds_api::raw::Model;
+use ds_api::{ApiClient, ApiError};
+use serde_json::json;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn models_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .and(header("authorization", "Bearer k"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "object": "list",
+            "data": [
+                { "id": "deepseek-chat", "object": "model", "owned_by": "deepseek" },
+                { "id": "deepseek-reasoner", "object": "model", "owned_by": "deepseek" }
+            ]
+        })))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn list_models_returns_typed_list() {
+    let server = models_server().await;
+    let client = ApiClient::new("k").with_base_url(server.uri());
+
+    let models = client.list_models().await.unwrap();
+    assert_eq!(
+        models.ids().collect::<Vec<_>>(),
+        ["deepseek-chat", "deepseek-reasoner"]
+    );
+}
+
+#[tokio::test]
+async fn validate_model_rejects_unknown_models() {
+    let server = models_server().await;
+    let client = ApiClient::new("k").with_base_url(server.uri());
+
+    client.validate_model(&Model::DeepseekChat).await.unwrap();
+
+    let err = client
+        .validate_model(&Model::Custom("deepseek-coder".to_string()))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ApiError::UnknownModel { model, available } if model == "deepseek-coder" && available.len() == 2
+    ));
+}
+
+#[tokio::test]
+async fn balance_returns_typed_balance() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/user/balance"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "is_available": true,
+            "balance_infos": [{
+                "currency": "USD",
+                "total_balance": "4.20",
+                "granted_balance": "0.00",
+                "topped_up_balance": "4.20"
+            }]
+        })))
+        .mount(&server)
+        .await;
+    let client = ApiClient::new("k").with_base_url(server.uri());
+
+    let balance = client.balance().await.unwrap();
+    assert!(balance.is_available);
+    assert_eq!(balance.balance_infos[0].total(), Some(4.2));
+}
+
+#[tokio::test]
+async fn invalid_token_surfaces_http_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/models"))
+        .respond_with(ResponseTemplate::new(401).set_body_string("invalid api key"))
+        .mount(&server)
+        .await;
+    let client = ApiClient::new("bad").with_base_url(server.uri());
+
+    let err = client.list_models().await.unwrap_err();
+    assert!(matches!(err, ApiError::Http { status, .. } if status.as_u16() == 401));
+}
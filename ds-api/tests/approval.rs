@@ -0,0 +1,158 @@
+//! Integration tests for human-in-the-loop tool approval.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use ds_api::raw::request::message::Role;
+use ds_api::{AgentEvent, ApprovalPolicy, DeepseekAgent, MockBackend, MockReply, tool};
+use futures::StreamExt;
+use serde_json::json;
+
+#[derive(Default)]
+struct Deploy {
+    runs: Arc<AtomicUsize>,
+}
+
+#[tool]
+impl ds_api::Tool for Deploy {
+    /// Deploy a service.
+    async fn deploy(&self, target: String) -> serde_json::Value {
+        self.runs.fetch_add(1, Ordering::SeqCst);
+        json!({ "deployed": target })
+    }
+}
+
+fn deploys(targets: &[&str]) -> MockReply {
+    targets
+        .iter()
+        .enumerate()
+        .fold(MockReply::default(), |reply, (i, target)| {
+            reply.with_tool_call(format!("call_{i}"), "deploy", json!({ "target": target }))
+        })
+}
+
+#[tokio::test]
+async fn approve_reject_and_edit_before_any_tool_runs() {
+    let mock = MockBackend::new()
+        .reply(deploys(&["staging", "prod", "prod"]))
+        .reply(MockReply::text("done"));
+    let tool = Deploy::default();
+    let runs = tool.runs.clone();
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .add_tool(tool)
+        .with_approval(ApprovalPolicy::new().require("deploy"));
+    let approvals = agent.approval_handle();
+
+    let mut stream = agent.chat("ship it");
+    let mut results = Vec::new();
+    let mut requested = Vec::new();
+    while let Some(ev) = stream.next().await {
+        match ev.unwrap() {
+            AgentEvent::ApprovalRequired { call } => {
+                assert_eq!(runs.load(Ordering::SeqCst), 0);
+                match call.id.as_str() {
+                    "call_0" => approvals.approve(&call.id),
+                    "call_1" => approvals.reject(&call.id, "no prod deploys on Friday"),
+                    _ => approvals.edit(&call.id, json!({ "target": "canary" })),
+                }
+                requested.push(call.id);
+            }
+            AgentEvent::ToolResult(r) => results.push((r.id, r.args, r.result)),
+            _ => {}
+        }
+    }
+    let agent = stream.into_agent().unwrap();
+
+    assert_eq!(requested, ["call_0", "call_1", "call_2"]);
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+    // The rejection is settled before the batch runs, so it is reported first.
+    assert_eq!(
+        results,
+        [
+            (
+                "call_1".to_string(),
+                r#"{"target":"prod"}"#.to_string(),
+                json!({ "error": "rejected by user", "reason": "no prod deploys on Friday" }),
+            ),
+            (
+                "call_0".to_string(),
+                r#"{"target":"staging"}"#.to_string(),
+                json!({ "deployed": "staging" }),
+            ),
+            (
+                "call_2".to_string(),
+                r#"{"target":"canary"}"#.to_string(),
+                json!({ "deployed": "canary" }),
+            ),
+        ]
+    );
+
+    // History shows the edited call and answers every call in order.
+    let history = agent.history();
+    let assistant = history
+        .iter()
+        .find(|m| m.tool_calls.is_some())
+        .and_then(|m| m.tool_calls.as_ref())
+        .unwrap();
+    assert_eq!(assistant[2].function.arguments, r#"{"target":"canary"}"#);
+    let tool_ids: Vec<_> = history
+        .iter()
+        .filter(|m| matches!(m.role, Role::Tool))
+        .map(|m| m.tool_call_id.clone().unwrap())
+        .collect();
+    assert_eq!(tool_ids, ["call_0", "call_1", "call_2"]);
+    let sent = &mock.requests()[1].messages;
+    assert!(
+        sent.iter()
+            .any(|m| m.content.as_deref().is_some_and(|c| c.contains("Friday")))
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn predicate_gates_some_calls_and_the_run_waits_for_a_decision() {
+    let mock = MockBackend::new()
+        .reply(deploys(&["staging", "prod"]))
+        .reply(MockReply::text("done"));
+    let tool = Deploy::default();
+    let runs = tool.runs.clone();
+    let agent = DeepseekAgent::from_client(mock, "deepseek-chat")
+        .add_tool(tool)
+        .with_approval(ApprovalPolicy::new().require_if(|call| call.args.contains("prod")));
+    let approvals = agent.approval_handle();
+
+    let mut stream = agent.chat("ship it");
+    let mut gated = Vec::new();
+    let mut decider = None;
+    while let Some(ev) = stream.next().await {
+        if let AgentEvent::ApprovalRequired { call } = ev.unwrap() {
+            gated.push(call.id.clone());
+            // Decide later, from elsewhere; a decision for an unknown call
+            // is ignored.
+            let approvals = approvals.clone();
+            let runs = runs.clone();
+            decider = Some(tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                approvals.reject("call_9", "unknown");
+                let runs_before = runs.load(Ordering::SeqCst);
+                approvals.approve(call.id);
+                runs_before
+            }));
+        }
+    }
+    let agent = stream.into_agent().unwrap();
+
+    assert_eq!(gated, ["call_1"]);
+    // Not even the ungated call ran before the decision.
+    assert_eq!(decider.unwrap().await.unwrap(), 0);
+    let results: Vec<_> = agent
+        .history()
+        .iter()
+        .filter(|m| matches!(m.role, Role::Tool))
+        .map(|m| m.content.clone().unwrap())
+        .collect();
+    assert_eq!(
+        results,
+        [r#"{"deployed":"staging"}"#, r#"{"deployed":"prod"}"#]
+    );
+}
@@ -0,0 +1,99 @@
+//! Integration tests for `DeepseekAgent::with_budget`.
+
+mod common;
+
+use std::time::Duration;
+
+use common::{Echo, assert_history_complete, echo_call, run};
+use ds_api::raw::request::message::Role;
+use ds_api::{AgentEvent, Budget, BudgetLimit, DeepseekAgent, MockBackend, MockReply};
+use serde_json::json;
+
+#[tokio::test]
+async fn turn_limit_skips_pending_tools_and_stops() {
+    let mock = MockBackend::new()
+        .reply(echo_call("call_1"))
+        .reply(MockReply::text("never sent"));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .add_tool(Echo)
+        .with_budget(Budget::new().max_turns(1));
+    let (events, agent) = run(agent, "go").await;
+
+    assert!(matches!(
+        events.last(),
+        Some(AgentEvent::BudgetExceeded(BudgetLimit::Turns { limit: 1 }))
+    ));
+    let AgentEvent::ToolResult(skipped) = &events[events.len() - 2] else {
+        panic!("expected the skipped tool result, got {events:?}");
+    };
+    assert!(
+        skipped.result["error"]
+            .as_str()
+            .unwrap()
+            .contains("not executed")
+    );
+    assert_eq!(mock.remaining(), 1);
+    assert_history_complete(&agent);
+    assert!(matches!(agent.history().last().unwrap().role, Role::Tool));
+}
+
+#[tokio::test]
+async fn token_limit_stops_a_streamed_tool_loop() {
+    let mock = MockBackend::new()
+        .reply(echo_call("call_1"))
+        .reply(echo_call("call_2"))
+        .reply(echo_call("call_3"));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .with_streaming()
+        .add_tool(Echo)
+        .with_budget(Budget::new().max_tokens(20));
+    let (events, agent) = run(agent, "go").await;
+
+    // The first call runs; the second turn pushes the total to 30 tokens.
+    let results: Vec<_> = events
+        .iter()
+        .filter_map(|e| match e {
+            AgentEvent::ToolResult(r) => Some(r.result.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(results[0], json!({ "text": "call_1" }));
+    assert!(results[1]["error"].is_string());
+    assert!(matches!(
+        events.last(),
+        Some(AgentEvent::BudgetExceeded(BudgetLimit::Tokens {
+            limit: 20,
+            used: 30
+        }))
+    ));
+    assert_eq!(mock.remaining(), 1);
+    assert_history_complete(&agent);
+}
+
+#[tokio::test]
+async fn exhausted_time_budget_stops_before_the_first_turn() {
+    let mock = MockBackend::new().reply(MockReply::text("hi"));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .with_budget(Budget::new().max_duration(Duration::ZERO));
+    let (events, _) = run(agent, "go").await;
+    assert!(matches!(
+        events[..],
+        [AgentEvent::BudgetExceeded(BudgetLimit::Duration { .. })]
+    ));
+    assert_eq!(mock.remaining(), 1);
+}
+
+#[tokio::test]
+async fn spend_is_reset_for_every_run() {
+    let mock = MockBackend::new()
+        .reply(MockReply::text("one").with_usage(10, 5))
+        .reply(MockReply::text("two").with_usage(10, 5));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .with_budget(Budget::new().max_tokens(10));
+
+    // A final answer ends the run before the next check, even over budget.
+    let (_, agent) = run(agent, "go").await;
+    let (events, _) = run(agent, "go").await;
+    assert!(matches!(&events[0], AgentEvent::Token(t) if t == "two"));
+    assert_eq!(mock.remaining(), 0);
+}
@@ -0,0 +1,167 @@
+//! Integration tests for `AgentStream::cancel_handle`.
+
+mod common;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use common::assert_history_complete;
+use ds_api::raw::request::message::Role;
+use ds_api::{
+    AgentEvent, ApiClient, ApprovalPolicy, CancellationToken, DeepseekAgent, MockBackend,
+    MockReply, tool,
+};
+use futures::StreamExt;
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[derive(Default)]
+struct Stuck {
+    cancelled: Arc<AtomicBool>,
+}
+
+#[tool]
+impl ds_api::Tool for Stuck {
+    /// Never finishes.
+    async fn hang(&self, cancel: CancellationToken) -> String {
+        cancel.cancelled().await;
+        self.cancelled.store(true, Ordering::SeqCst);
+        std::future::pending::<()>().await;
+        String::new()
+    }
+
+    /// Finishes at once.
+    async fn quick(&self) -> String {
+        "ok".to_string()
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancelling_running_tools_answers_every_call() {
+    let tool = Stuck::default();
+    let cancelled = tool.cancelled.clone();
+    let mock = MockBackend::new()
+        .reply(
+            MockReply::tool_call("call_1", "quick", json!({})).with_tool_call(
+                "call_2",
+                "hang",
+                json!({}),
+            ),
+        )
+        .reply(MockReply::text("unused"));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .add_tool(tool)
+        .with_parallel_tools(2);
+
+    let mut stream = agent.chat("go");
+    let stop = stream.cancel_handle();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        stop.cancel();
+    });
+    let mut results = Vec::new();
+    while let Some(ev) = stream.next().await {
+        if let AgentEvent::ToolResult(r) = ev.unwrap() {
+            results.push((r.id, r.result));
+        }
+    }
+    let agent = stream.into_agent().expect("agent is returned after cancel");
+
+    assert_eq!(
+        results,
+        [
+            ("call_1".to_string(), json!("ok")),
+            ("call_2".to_string(), json!({ "error": "cancelled" })),
+        ]
+    );
+    assert!(cancelled.load(Ordering::SeqCst));
+    assert_history_complete(&agent);
+    assert_eq!(mock.remaining(), 1);
+}
+
+#[tokio::test]
+async fn cancelling_mid_stream_drops_the_partial_reply() {
+    let mock = MockBackend::new()
+        .reply(MockReply::deltas(["Once", " upon", " a time"]))
+        .reply(MockReply::text("fresh start"));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat").with_streaming();
+
+    let mut stream = agent.chat("tell me a story");
+    let stop = stream.cancel_handle();
+    let mut tokens = Vec::new();
+    while let Some(ev) = stream.next().await {
+        if let AgentEvent::Token(t) = ev.unwrap() {
+            tokens.push(t);
+            stop.cancel();
+        }
+    }
+    assert_eq!(tokens, ["Once"]);
+    assert!(stop.is_cancelled());
+
+    let agent = stream.into_agent().unwrap();
+    let roles: Vec<_> = agent.history().iter().map(|m| m.role.clone()).collect();
+    assert!(matches!(roles.as_slice(), [Role::User]));
+
+    // The agent carries on as if the cancelled reply never happened.
+    let mut stream = agent.chat("never mind");
+    while let Some(ev) = stream.next().await {
+        ev.unwrap();
+    }
+    let agent = stream.into_agent().unwrap();
+    assert_eq!(
+        agent.history().last().unwrap().content.as_deref(),
+        Some("fresh start")
+    );
+}
+
+#[tokio::test]
+async fn cancelling_while_awaiting_approval_rejects_the_pending_calls() {
+    let mock = MockBackend::new().reply(MockReply::tool_call("call_1", "quick", json!({})));
+    let agent = DeepseekAgent::from_client(mock, "deepseek-chat")
+        .add_tool(Stuck::default())
+        .with_approval(ApprovalPolicy::new().require("quick"));
+
+    let mut stream = agent.chat("go");
+    let stop = stream.cancel_handle();
+    let mut results = Vec::new();
+    while let Some(ev) = stream.next().await {
+        match ev.unwrap() {
+            AgentEvent::ApprovalRequired { .. } => stop.cancel(),
+            AgentEvent::ToolResult(r) => results.push(r.result),
+            _ => {}
+        }
+    }
+    let agent = stream.into_agent().unwrap();
+
+    assert_eq!(results, [json!({ "error": "cancelled" })]);
+    assert_history_complete(&agent);
+}
+
+#[tokio::test]
+async fn cancelling_an_in_flight_request_returns_promptly() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(30)))
+        .mount(&server)
+        .await;
+    let client = ApiClient::new("k").with_base_url(server.uri());
+    let agent = DeepseekAgent::from_client(client, "deepseek-chat");
+
+    let mut stream = agent.chat("hi");
+    let stop = stream.cancel_handle();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        stop.cancel();
+    });
+    let started = std::time::Instant::now();
+    while let Some(ev) = stream.next().await {
+        ev.unwrap();
+    }
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    let agent = stream.into_agent().unwrap();
+    assert_eq!(agent.history().len(), 1);
+}
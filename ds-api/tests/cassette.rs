@@ -0,0 +1,118 @@
+//! Record against a wiremock server, then replay the cassette offline.
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::completion;
+use ds_api::raw::request::message::Message;
+use ds_api::{AgentEvent, ApiClient, ApiError, ApiRequest, Cassette, DeepseekAgent};
+use futures::StreamExt;
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SSE_BODY: &str = concat!(
+    "data: {\"id\":\"1\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"deepseek-chat\",",
+    "\"choices\":[{\"index\":0,\"delta\":{\"content\":\"str\"},\"finish_reason\":null}]}\n\n",
+    "data: {\"id\":\"1\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"deepseek-chat\",",
+    "\"choices\":[{\"index\":0,\"delta\":{\"content\":\"eamed\"},\"finish_reason\":\"stop\"}]}\n\n",
+    "data: [DONE]\n\n",
+);
+
+fn cassette_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("ds-api-{}-{name}.jsonl", std::process::id()))
+}
+
+async fn server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_partial_json(json!({ "stream": true })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(SSE_BODY),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(completion("recorded")))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn request(text: &str) -> ApiRequest {
+    ApiRequest::deepseek_chat(vec![Message::user(text)])
+}
+
+async fn collect_text(client: &ApiClient, text: &str) -> String {
+    let mut stream = client.stream_text(request(text)).await.unwrap();
+    let mut out = String::new();
+    while let Some(fragment) = stream.next().await {
+        out.push_str(&fragment.unwrap());
+    }
+    out
+}
+
+#[tokio::test]
+async fn records_then_replays_offline() {
+    let path = cassette_path("roundtrip");
+    {
+        let server = server().await;
+        let client = ApiClient::new("k")
+            .with_base_url(server.uri())
+            .with_cassette(Cassette::record(&path).unwrap());
+
+        assert_eq!(
+            client.send(request("hi")).await.unwrap().content(),
+            Some("recorded")
+        );
+        assert_eq!(collect_text(&client, "stream please").await, "streamed");
+        assert_eq!(client.cassette().unwrap().len(), 2);
+    }
+
+    // The server is gone; everything is served from the file.
+    let client = ApiClient::new("k")
+        .with_base_url("http://127.0.0.1:9")
+        .with_cassette(Cassette::replay(&path).unwrap());
+
+    assert_eq!(collect_text(&client, "stream please").await, "streamed");
+    assert_eq!(
+        client.send(request("hi")).await.unwrap().content(),
+        Some("recorded")
+    );
+    assert_eq!(client.cassette().unwrap().unplayed(), 0);
+
+    // Agents work unchanged on top of the replaying client.
+    let mut stream = DeepseekAgent::from_client(client, "deepseek-chat").chat("hi");
+    let first = stream.next().await.unwrap().unwrap();
+    assert!(matches!(first, AgentEvent::Token(t) if t == "recorded"));
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[tokio::test]
+async fn unmatched_request_reports_a_diff() {
+    let path = cassette_path("mismatch");
+    {
+        let server = server().await;
+        let client = ApiClient::new("k")
+            .with_base_url(server.uri())
+            .with_cassette(Cassette::record(&path).unwrap());
+        client.send(request("hi")).await.unwrap();
+    }
+
+    let client = ApiClient::new("k").with_cassette(Cassette::replay(&path).unwrap());
+    let err = client.send(request("hello")).await.unwrap_err();
+
+    let ApiError::CassetteMismatch(diff) = err else {
+        panic!("expected a cassette mismatch, got {err:?}");
+    };
+    assert!(diff.contains("-       \"content\": \"hi\","), "{diff}");
+    assert!(diff.contains("+       \"content\": \"hello\","), "{diff}");
+
+    std::fs::remove_file(&path).ok();
+}
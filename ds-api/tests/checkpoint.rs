@@ -0,0 +1,265 @@
+//! Integration tests for checkpointing and resuming agent runs.
+
+use std::sync::{Arc, Mutex};
+
+mod common;
+
+use common::completion_from;
+use ds_api::raw::request::message::Role;
+use ds_api::{
+    AgentEvent, Budget, Checkpoint, DeepseekAgent, MockBackend, MockReply, ToolBundle, tool,
+};
+use futures::StreamExt;
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+struct Counter;
+
+#[tool]
+impl ds_api::Tool for Counter {
+    /// Add one to `n`.
+    async fn incr(&self, n: u64) -> serde_json::Value {
+        json!({ "n": n + 1 })
+    }
+}
+
+/// An agent whose turn hook records the turn number of every request.
+fn counting_agent(mock: MockBackend, turns: Arc<Mutex<Vec<u32>>>) -> DeepseekAgent {
+    DeepseekAgent::from_client(mock, "deepseek-chat")
+        .add_tool(Counter)
+        .with_turn_hook(move |ctx, opts| {
+            turns.lock().unwrap().push(ctx.turn);
+            opts
+        })
+}
+
+#[tokio::test]
+async fn checkpoints_follow_each_turn_and_tool_batch() {
+    let mock = MockBackend::new()
+        .reply(MockReply::tool_call("call_1", "incr", json!({ "n": 1 })))
+        .reply(MockReply::text("n is 2"));
+    let agent = counting_agent(mock, Default::default()).with_checkpoints();
+
+    let mut stream = agent.chat("count");
+    let mut events = Vec::new();
+    while let Some(ev) = stream.next().await {
+        events.push(match ev.unwrap() {
+            AgentEvent::Checkpoint(c) => format!(
+                "checkpoint(turn {}, {} pending, finished: {})",
+                c.turn(),
+                c.pending_tool_calls().len(),
+                c.is_finished()
+            ),
+            AgentEvent::ToolCall(_) => "tool call".into(),
+            AgentEvent::ToolResult(_) => "tool result".into(),
+            AgentEvent::Token(_) => "token".into(),
+            AgentEvent::TurnFinished { .. } => "turn finished".into(),
+            _ => continue,
+        });
+    }
+
+    assert_eq!(
+        events,
+        [
+            "tool call",
+            "turn finished",
+            "checkpoint(turn 1, 1 pending, finished: false)",
+            "tool result",
+            "checkpoint(turn 1, 0 pending, finished: false)",
+            "token",
+            "turn finished",
+            "checkpoint(turn 2, 0 pending, finished: true)",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn resume_runs_pending_tools_and_keeps_counting_steps() {
+    // First process: stopped right after the model asked for a tool.
+    let mock = MockBackend::new().reply(MockReply::tool_call("call_1", "incr", json!({ "n": 1 })));
+    let agent = counting_agent(mock, Default::default())
+        .with_checkpoints()
+        .with_max_steps(2);
+    let mut stream = agent.chat("count");
+    let saved = loop {
+        if let AgentEvent::Checkpoint(c) = stream.next().await.unwrap().unwrap() {
+            break serde_json::to_string(&c).unwrap();
+        }
+    };
+    drop(stream);
+
+    // Second process: a fresh agent with the same tools.
+    let mock = MockBackend::new()
+        .reply(MockReply::tool_call("call_2", "incr", json!({ "n": 2 })))
+        .reply(MockReply::text("n is 3"));
+    let turns = Arc::new(Mutex::new(Vec::new()));
+    let checkpoint: Checkpoint = serde_json::from_str(&saved).unwrap();
+    let mut stream = counting_agent(mock.clone(), turns.clone()).resume_from(checkpoint);
+    let mut results = Vec::new();
+    let mut max_steps = None;
+    let mut checkpoints = 0;
+    while let Some(ev) = stream.next().await {
+        match ev.unwrap() {
+            AgentEvent::ToolResult(r) => results.push((r.id, r.result)),
+            AgentEvent::MaxStepsReached { max_steps: n } => max_steps = Some(n),
+            AgentEvent::Checkpoint(_) => checkpoints += 1,
+            _ => {}
+        }
+    }
+    let agent = stream.into_agent().unwrap();
+
+    assert_eq!(
+        results,
+        [
+            ("call_1".to_string(), json!({ "n": 2 })),
+            ("call_2".to_string(), json!({ "n": 3 })),
+        ]
+    );
+    // Turn 1 ran before the checkpoint; max_steps came from it.
+    assert_eq!(*turns.lock().unwrap(), [2, 3]);
+    assert_eq!(max_steps, Some(2));
+    // The resuming agent did not ask for checkpoints.
+    assert_eq!(checkpoints, 0);
+    let first = &mock.requests()[0].messages;
+    assert_eq!(first[0].content.as_deref(), Some("count"));
+    assert_eq!(
+        first.last().unwrap().tool_call_id.as_deref(),
+        Some("call_1")
+    );
+    let roles: Vec<_> = agent.history().iter().map(|m| m.role.clone()).collect();
+    assert!(matches!(
+        roles.as_slice(),
+        [
+            Role::User,
+            Role::Assistant,
+            Role::Tool,
+            Role::Assistant,
+            Role::Tool,
+            Role::Assistant
+        ]
+    ));
+}
+
+#[tokio::test]
+async fn resuming_a_finished_run_yields_nothing() {
+    let mock = MockBackend::new().reply(MockReply::text("hello"));
+    let mut stream = counting_agent(mock, Default::default())
+        .with_checkpoints()
+        .chat("hi");
+    let mut last = None;
+    while let Some(ev) = stream.next().await {
+        if let AgentEvent::Checkpoint(c) = ev.unwrap() {
+            last = Some(*c);
+        }
+    }
+    let checkpoint = last.unwrap();
+    assert!(checkpoint.is_finished());
+
+    let mock = MockBackend::new();
+    let mut stream = counting_agent(mock.clone(), Default::default()).resume_from(checkpoint);
+    assert!(stream.next().await.is_none());
+    assert!(mock.requests().is_empty());
+    assert_eq!(stream.into_agent().unwrap().history().len(), 2);
+}
+
+#[tokio::test]
+async fn a_budget_stop_leaves_no_pending_calls_to_resume() {
+    let mock = MockBackend::new().reply(MockReply::tool_call("call_1", "incr", json!({ "n": 1 })));
+    let mut stream = counting_agent(mock, Default::default())
+        .with_checkpoints()
+        .with_budget(Budget::new().max_turns(1))
+        .chat("count");
+    let mut last = None;
+    while let Some(ev) = stream.next().await {
+        if let AgentEvent::Checkpoint(c) = ev.unwrap() {
+            last = Some(*c);
+        }
+    }
+    let checkpoint = last.unwrap();
+    assert!(checkpoint.pending_tool_calls().is_empty());
+    assert!(checkpoint.is_finished());
+    // The skipped call is answered in the checkpoint's history.
+    assert!(matches!(
+        checkpoint.history().last().unwrap().role,
+        Role::Tool
+    ));
+
+    let mock = MockBackend::new();
+    let mut stream = counting_agent(mock.clone(), Default::default()).resume_from(checkpoint);
+    assert!(stream.next().await.is_none());
+    assert!(mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn resume_continues_on_the_resuming_agents_endpoint() {
+    // Checkpointed on an OpenRouter-style model name.
+    let mock = MockBackend::new().reply(MockReply::tool_call("call_1", "incr", json!({ "n": 1 })));
+    let mut stream = DeepseekAgent::from_client(mock, "openai/gpt-4o-mini")
+        .add_tool(Counter)
+        .with_checkpoints()
+        .chat("count");
+    let checkpoint = loop {
+        if let AgentEvent::Checkpoint(c) = stream.next().await.unwrap().unwrap() {
+            break *c;
+        }
+    };
+    drop(stream);
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(header("authorization", "Bearer or-key"))
+        .and(body_partial_json(json!({ "model": "openai/gpt-4o-mini" })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(completion_from("openai/gpt-4o-mini", "n is 2")),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut stream = DeepseekAgent::custom("or-key", server.uri(), "openai/gpt-4o-mini")
+        .add_tool(Counter)
+        .resume_from(checkpoint);
+    let mut text = String::new();
+    while let Some(ev) = stream.next().await {
+        if let AgentEvent::Token(t) = ev.unwrap() {
+            text.push_str(&t);
+        }
+    }
+    assert_eq!(text, "n is 2");
+}
+
+#[tokio::test]
+async fn resume_constructor_runs_on_the_given_backend() {
+    let mock = MockBackend::new().reply(MockReply::tool_call("call_1", "incr", json!({ "n": 1 })));
+    let mut stream = DeepseekAgent::from_client(mock, "openai/gpt-4o-mini")
+        .add_tool(Counter)
+        .with_checkpoints()
+        .chat("count");
+    let checkpoint = loop {
+        if let AgentEvent::Checkpoint(c) = stream.next().await.unwrap().unwrap() {
+            break *c;
+        }
+    };
+    drop(stream);
+    assert_eq!(checkpoint.model(), "openai/gpt-4o-mini");
+
+    let mock = MockBackend::new().reply(MockReply::text("n is 2"));
+    let mut stream =
+        DeepseekAgent::resume(mock.clone(), checkpoint, ToolBundle::new().add(Counter));
+    let mut results = Vec::new();
+    let mut checkpoints = 0;
+    while let Some(ev) = stream.next().await {
+        match ev.unwrap() {
+            AgentEvent::ToolResult(r) => results.push(r.result),
+            AgentEvent::Checkpoint(_) => checkpoints += 1,
+            _ => {}
+        }
+    }
+    assert_eq!(results, [json!({ "n": 2 })]);
+    assert!(checkpoints > 0);
+    let request = serde_json::to_value(&mock.requests()[0]).unwrap();
+    assert_eq!(request["model"], "openai/gpt-4o-mini");
+}
@@ -0,0 +1,83 @@
+//! Fixtures shared by the integration tests.
+//!
+//! Each test binary compiles this module on its own and uses only part of
+//! it, hence the `dead_code` allowance.
+#![allow(dead_code)]
+
+use std::collections::HashSet;
+
+use ds_api::raw::request::message::Role;
+use ds_api::{AgentEvent, DeepseekAgent, MockReply, tool};
+use futures::StreamExt;
+use serde_json::{Value, json};
+
+/// A non-streaming `chat.completion` body from `deepseek-chat` with a
+/// one-token prompt and reply.
+pub fn completion(content: &str) -> Value {
+    completion_from("deepseek-chat", content)
+}
+
+/// Like [`completion`], reported as coming from `model`.
+pub fn completion_from(model: &str, content: &str) -> Value {
+    json!({
+        "id": "c1",
+        "object": "chat.completion",
+        "created": 0,
+        "model": model,
+        "choices": [{
+            "index": 0,
+            "finish_reason": "stop",
+            "message": { "role": "assistant", "content": content }
+        }],
+        "usage": { "prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2 }
+    })
+}
+
+pub struct Echo;
+
+#[tool]
+impl ds_api::Tool for Echo {
+    /// Echo the input.
+    async fn echo(&self, text: String) -> serde_json::Value {
+        json!({ "text": text })
+    }
+}
+
+/// A reply calling [`Echo`] with the call id as its text, reporting 10
+/// prompt and 5 completion tokens.
+pub fn echo_call(id: &str) -> MockReply {
+    MockReply::tool_call(id, "echo", json!({ "text": id })).with_usage(10, 5)
+}
+
+/// Run `agent` on `input` to the end, failing on any error event.
+pub async fn run(agent: DeepseekAgent, input: &str) -> (Vec<AgentEvent>, DeepseekAgent) {
+    let mut stream = agent.chat(input);
+    let mut events = Vec::new();
+    while let Some(ev) = stream.next().await {
+        events.push(ev.unwrap());
+    }
+    (events, stream.into_agent().unwrap())
+}
+
+/// Every assistant tool call is answered by a later tool message, and every
+/// tool message answers a call.
+pub fn assert_history_complete(agent: &DeepseekAgent) {
+    let history = agent.history();
+    let mut calls = HashSet::new();
+    for (i, msg) in history.iter().enumerate() {
+        for call in msg.tool_calls.iter().flatten() {
+            calls.insert(call.id.as_str());
+            assert!(
+                history[i + 1..]
+                    .iter()
+                    .any(|m| m.tool_call_id.as_deref() == Some(call.id.as_str())),
+                "tool call {} has no result",
+                call.id
+            );
+        }
+        if matches!(msg.role, Role::Tool) {
+            let id = msg.tool_call_id.as_deref().unwrap_or_default();
+            assert!(calls.contains(id), "tool message {id:?} answers no call");
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Integration tests for `CostTracker` at the client, conversation and agent
+//! levels.
+
+mod common;
+
+use common::completion;
+use ds_api::conversation::Conversation;
+use ds_api::raw::request::message::Message;
+use ds_api::{
+    ApiClient, ApiRequest, CostTracker, DeepseekAgent, LlmSummarizer, MockBackend, MockReply,
+    ModelPrice, PriceTable,
+};
+use futures::StreamExt;
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn client_tracker_is_shared_by_clones() {
+    let mut body = completion("hi");
+    body["usage"] = json!({
+        "prompt_tokens": 1000,
+        "completion_tokens": 100,
+        "total_tokens": 1100,
+        "prompt_cache_hit_tokens": 600,
+        "prompt_cache_miss_tokens": 400
+    });
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .mount(&server)
+        .await;
+
+    let tracker = CostTracker::new();
+    let client = ApiClient::new("k")
+        .with_base_url(server.uri())
+        .with_cost_tracker(tracker.clone());
+    let req = || ApiRequest::builder().add_message(Message::user("hi"));
+    client.send(req()).await.unwrap();
+    client.clone().send(req()).await.unwrap();
+
+    let total = tracker.total();
+    assert_eq!(total.requests, 2);
+    assert_eq!(total.prompt_cache_hit_tokens, 1200);
+    assert_eq!(total.prompt_cache_miss_tokens, 800);
+    // Default DeepSeek prices: 0.028 hit / 0.28 miss / 0.42 output per 1M.
+    let expected = 2.0 * (600.0 * 0.028 + 400.0 * 0.28 + 100.0 * 0.42) / 1e6;
+    assert!((total.cost - expected).abs() < 1e-12);
+}
+
+#[tokio::test]
+async fn conversation_tracker_includes_summarizer_calls() {
+    let mock = MockBackend::new()
+        .reply(MockReply::text("summary one").with_usage(50, 10))
+        .reply(MockReply::text("answer").with_usage(30, 5))
+        .reply(MockReply::text("summary two").with_usage(40, 10));
+    let tracker = CostTracker::with_prices(
+        PriceTable::new().with_price("deepseek-chat", ModelPrice::new(0.0, 1.0, 2.0)),
+    );
+    let mut conv = Conversation::new(mock.clone())
+        .with_summarizer(
+            LlmSummarizer::new(mock.clone())
+                .with_model("summarizer")
+                .token_threshold(1)
+                .retain_last(1),
+        )
+        .with_history(vec![
+            Message::user("one"),
+            Message::assistant("1"),
+            Message::user("two"),
+            Message::assistant("2"),
+            Message::user("three"),
+        ])
+        .with_cost_tracker(tracker.clone());
+
+    assert_eq!(conv.send_once().await.unwrap().as_deref(), Some("answer"));
+
+    let by_model = tracker.by_model();
+    assert_eq!(by_model["deepseek-chat"].requests, 1);
+    assert!((by_model["deepseek-chat"].cost - 40e-6).abs() < 1e-12);
+    // The summarizer's model has no price: counted, but not priced.
+    assert_eq!(by_model["summarizer"].requests, 2);
+    assert_eq!(by_model["summarizer"].unpriced_requests, 2);
+    assert_eq!(tracker.total().completion_tokens, 25);
+}
+
+#[tokio::test]
+async fn agent_tracker_counts_streamed_turns() {
+    let mock = MockBackend::new()
+        .reply(MockReply::tool_call("call_1", "missing", json!({})).with_usage(10, 2))
+        .reply(MockReply::deltas(["do", "ne"]).with_usage(20, 4));
+    let tracker = CostTracker::new();
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .with_streaming()
+        .with_cost_tracker(tracker.clone());
+
+    let mut stream = agent.chat("hi");
+    while let Some(event) = stream.next().await {
+        event.unwrap();
+    }
+
+    let total = tracker.total();
+    assert_eq!(total.requests, 2);
+    assert_eq!(total.prompt_cache_miss_tokens, 30);
+    assert_eq!(total.completion_tokens, 6);
+    assert!(total.cost > 0.0);
+    assert_eq!(
+        mock.requests()[0]
+            .stream_options
+            .as_ref()
+            .map(|o| o.include_usage),
+        Some(true)
+    );
+}
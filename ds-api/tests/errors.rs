@@ -0,0 +1,81 @@
+//! Integration tests for structured HTTP error parsing.
+
+use ds_api::raw::request::message::Message;
+use ds_api::{ApiClient, ApiRequest, ErrorKind, RetryPolicy};
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn failing_server(status: u16, body: serde_json::Value) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(status).set_body_json(body))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn req() -> ApiRequest {
+    ApiRequest::builder().add_message(Message::user("hi"))
+}
+
+#[tokio::test]
+async fn context_length_error_is_typed() {
+    let server = failing_server(
+        400,
+        json!({ "error": {
+            "message": "This model's maximum context length is 65536 tokens. However, you requested 70000 tokens.",
+            "type": "invalid_request_error",
+            "param": null,
+            "code": "invalid_request_error"
+        }}),
+    )
+    .await;
+    let client = ApiClient::new("k").with_base_url(server.uri());
+
+    let err = client.send(req()).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ContextLengthExceeded);
+    assert_eq!(
+        err.body().unwrap().r#type.as_deref(),
+        Some("invalid_request_error")
+    );
+    assert!(!err.is_retryable());
+}
+
+#[tokio::test]
+async fn insufficient_quota_is_not_retried() {
+    let server = failing_server(
+        429,
+        json!({ "error": {
+            "message": "You exceeded your current quota.",
+            "type": "insufficient_quota",
+            "code": "insufficient_quota"
+        }}),
+    )
+    .await;
+    let client = ApiClient::new("k")
+        .with_base_url(server.uri())
+        .with_retry(RetryPolicy::new().max_attempts(3));
+
+    let err = client.send(req()).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InsufficientBalance);
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn invalid_requests_fail_before_sending() {
+    let server = failing_server(500, json!({})).await;
+    let client = ApiClient::new("k").with_base_url(server.uri());
+
+    let err = client.send(req().temperature(2.5)).await.unwrap_err();
+    assert!(matches!(
+        err,
+        ds_api::ApiError::Validation {
+            field: "temperature",
+            ..
+        }
+    ));
+    assert_eq!(err.kind(), ErrorKind::InvalidRequest);
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
@@ -0,0 +1,131 @@
+//! Integration tests for `FallbackClient` across real HTTP endpoints.
+
+mod common;
+
+use std::time::Duration;
+
+use common::completion_from;
+use ds_api::raw::request::message::Message;
+use ds_api::{
+    ApiClient, ApiError, ApiRequest, ChatBackend, DeepseekAgent, Endpoint, FallbackClient,
+    api::CircuitBreaker,
+};
+use futures::StreamExt;
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn unavailable_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(503).set_body_string("overloaded"))
+        .mount(&server)
+        .await;
+    server
+}
+
+async fn backup_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(header("authorization", "Bearer backup-key"))
+        .and(body_partial_json(json!({ "model": "backup-model" })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(completion_from("backup-model", "from backup")),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+fn req() -> ApiRequest {
+    ApiRequest::builder().add_message(Message::user("hi"))
+}
+
+#[tokio::test]
+async fn fails_over_from_503_to_next_endpoint() {
+    let primary = unavailable_server().await;
+    let backup = backup_server().await;
+    let client = FallbackClient::new([
+        Endpoint::new(primary.uri(), "primary-key", "deepseek-chat"),
+        Endpoint::new(backup.uri(), "backup-key", "backup-model"),
+    ]);
+
+    let resp = client.send(req()).await.unwrap();
+    assert_eq!(resp.content(), Some("from backup"));
+    assert_eq!(client.status()[0].consecutive_failures, 1);
+    assert_eq!(client.status()[1].consecutive_failures, 0);
+}
+
+#[tokio::test]
+async fn connection_errors_fail_over() {
+    let backup = backup_server().await;
+    let client = FallbackClient::new([
+        Endpoint::from_backend(
+            "dead",
+            ApiClient::new("k")
+                .with_base_url("http://127.0.0.1:9")
+                .with_timeout(Duration::from_secs(2)),
+        ),
+        Endpoint::new(backup.uri(), "backup-key", "backup-model"),
+    ]);
+
+    let resp = client.send(req()).await.unwrap();
+    assert_eq!(resp.content(), Some("from backup"));
+}
+
+#[tokio::test]
+async fn open_circuit_skips_unhealthy_endpoint() {
+    let primary = unavailable_server().await;
+    let backup = backup_server().await;
+    let client = FallbackClient::new([
+        Endpoint::new(primary.uri(), "primary-key", "deepseek-chat"),
+        Endpoint::new(backup.uri(), "backup-key", "backup-model"),
+    ])
+    .with_circuit_breaker(
+        CircuitBreaker::new()
+            .failure_threshold(2)
+            .cooldown(Duration::from_secs(60)),
+    );
+
+    for _ in 0..4 {
+        client.send(req()).await.unwrap();
+    }
+    assert!(client.status()[0].open);
+    // Two failures opened the circuit; later requests went straight to the backup.
+    assert_eq!(primary.received_requests().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn all_endpoints_failing_returns_last_error() {
+    let a = unavailable_server().await;
+    let b = unavailable_server().await;
+    let client = FallbackClient::new([
+        Endpoint::new(a.uri(), "k", "m"),
+        Endpoint::new(b.uri(), "k", "m"),
+    ]);
+
+    let err = client.send(req()).await.unwrap_err();
+    assert!(matches!(err, ApiError::Http { status, .. } if status.as_u16() == 503));
+}
+
+#[tokio::test]
+async fn agent_runs_against_fallback_client() {
+    let primary = unavailable_server().await;
+    let backup = backup_server().await;
+    let client = FallbackClient::new([
+        Endpoint::new(primary.uri(), "primary-key", "deepseek-chat"),
+        Endpoint::new(backup.uri(), "backup-key", "backup-model"),
+    ]);
+
+    let mut stream = DeepseekAgent::from_client(client, "deepseek-chat").chat("hi");
+    let mut text = String::new();
+    while let Some(ev) = stream.next().await {
+        if let ds_api::AgentEvent::Token(t) = ev.unwrap() {
+            text.push_str(&t);
+        }
+    }
+    assert_eq!(text, "from backup");
+}
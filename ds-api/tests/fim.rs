@@ -0,0 +1,81 @@
+//! Integration tests for FIM completion against a local wiremock server.
+
+use ds_api::{ApiClient, FimRequest};
+use futures::StreamExt;
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SSE_BODY: &str = concat!(
+    "data: {\"id\":\"1\",\"object\":\"text_completion\",\"created\":0,\"model\":\"deepseek-chat\",",
+    "\"choices\":[{\"index\":0,\"text\":\"a + \",\"finish_reason\":null}]}\n\n",
+    "data: {\"id\":\"1\",\"object\":\"text_completion\",\"created\":0,\"model\":\"deepseek-chat\",",
+    "\"choices\":[{\"index\":0,\"text\":\"b\",\"finish_reason\":\"stop\"}],",
+    "\"usage\":{\"prompt_tokens\":9,\"completion_tokens\":3,\"total_tokens\":12}}\n\n",
+    "data: [DONE]\n\n",
+);
+
+#[tokio::test]
+async fn complete_posts_prompt_and_suffix_to_beta_endpoint() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/beta/completions"))
+        .and(body_partial_json(json!({
+            "model": "deepseek-chat",
+            "prompt": "fn add(a: i32, b: i32) -> i32 {\n    ",
+            "suffix": "\n}",
+            "max_tokens": 16
+        })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "1",
+            "object": "text_completion",
+            "created": 0,
+            "model": "deepseek-chat",
+            "choices": [{ "index": 0, "text": "a + b", "finish_reason": "stop" }],
+            "usage": { "prompt_tokens": 9, "completion_tokens": 3, "total_tokens": 12 }
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = ApiClient::new("k").with_base_url(server.uri());
+    let req = FimRequest::new("fn add(a: i32, b: i32) -> i32 {\n    ")
+        .suffix("\n}")
+        .max_tokens(16);
+    let resp = client.complete(req).await.unwrap();
+
+    assert_eq!(resp.text(), Some("a + b"));
+    assert_eq!(resp.usage.total_tokens, 12);
+}
+
+#[tokio::test]
+async fn complete_stream_yields_text_chunks() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/fim/completions"))
+        .and(body_partial_json(json!({
+            "stream": true,
+            "stream_options": { "include_usage": true }
+        })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(SSE_BODY),
+        )
+        .mount(&server)
+        .await;
+
+    let client = ApiClient::new("k").with_beta_base_url(format!("{}/fim", server.uri()));
+    let mut stream = client.complete_stream(FimRequest::new("x")).await.unwrap();
+
+    let mut text = String::new();
+    let mut usage = None;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.unwrap();
+        text.push_str(&chunk.choices[0].text);
+        usage = usage.or(chunk.usage);
+    }
+
+    assert_eq!(text, "a + b");
+    assert_eq!(usage.unwrap().total_tokens, 12);
+}
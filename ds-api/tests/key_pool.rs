@@ -0,0 +1,158 @@
+//! Integration tests for `ApiClient` key pools.
+
+mod common;
+
+use ds_api::raw::request::message::Message;
+use ds_api::{ApiClient, ApiError, ApiRequest, KeyPool, KeySelection};
+use futures::StreamExt;
+use serde_json::json;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const REVOKED: &str = "sk-revoked-000000";
+const BROKE: &str = "sk-broke-11111111";
+const GOOD: &str = "sk-good-22222222";
+const OTHER: &str = "sk-other-33333333";
+
+/// A completion reporting `total` tokens in all.
+fn completion(total: u32) -> serde_json::Value {
+    let mut body = common::completion("ok");
+    body["usage"] =
+        json!({ "prompt_tokens": total - 1, "completion_tokens": 1, "total_tokens": total });
+    body
+}
+
+async fn server() -> MockServer {
+    let server = MockServer::start().await;
+    let reply = |key: &str, template: ResponseTemplate| {
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("authorization", format!("Bearer {key}").as_str()))
+            .respond_with(template)
+    };
+    reply(
+        REVOKED,
+        ResponseTemplate::new(401).set_body_string("invalid key"),
+    )
+    .mount(&server)
+    .await;
+    reply(
+        BROKE,
+        ResponseTemplate::new(402).set_body_string("insufficient balance"),
+    )
+    .mount(&server)
+    .await;
+    reply(
+        GOOD,
+        ResponseTemplate::new(200).set_body_json(completion(10)),
+    )
+    .mount(&server)
+    .await;
+    reply(
+        OTHER,
+        ResponseTemplate::new(200).set_body_json(completion(3)),
+    )
+    .mount(&server)
+    .await;
+    server
+}
+
+fn req() -> ApiRequest {
+    ApiRequest::builder().add_message(Message::user("hi"))
+}
+
+#[tokio::test]
+async fn rejected_keys_are_evicted_and_request_moves_on() {
+    let server = server().await;
+    let client = ApiClient::new("unused")
+        .with_base_url(server.uri())
+        .with_key_pool(KeyPool::new([REVOKED, BROKE, GOOD]));
+
+    client.send(req()).await.unwrap();
+    client.send(req()).await.unwrap();
+
+    let stats = client.key_stats().unwrap();
+    assert_eq!(stats[0].evicted.map(|s| s.as_u16()), Some(401));
+    assert_eq!(stats[1].evicted.map(|s| s.as_u16()), Some(402));
+    assert_eq!(stats[2].evicted, None);
+    assert_eq!(stats[2].requests, 2);
+    assert_eq!(stats[2].total_tokens, 20);
+    assert_eq!(stats[2].prompt_tokens, 18);
+    // Keys never appear unmasked.
+    assert_eq!(stats[2].masked_key, "***2222");
+}
+
+#[tokio::test]
+async fn round_robin_spreads_requests() {
+    let server = server().await;
+    let client = ApiClient::new("unused")
+        .with_base_url(server.uri())
+        .with_key_pool(KeyPool::new([GOOD, OTHER]));
+
+    for _ in 0..4 {
+        client.send(req()).await.unwrap();
+    }
+    let requests: Vec<u64> = client
+        .key_stats()
+        .unwrap()
+        .iter()
+        .map(|s| s.requests)
+        .collect();
+    assert_eq!(requests, [2, 2]);
+}
+
+#[tokio::test]
+async fn least_used_follows_token_counts() {
+    let server = server().await;
+    let client = ApiClient::new("unused")
+        .with_base_url(server.uri())
+        .with_key_pool(KeyPool::new([GOOD, OTHER]).selection(KeySelection::LeastUsed));
+
+    // GOOD costs 10 tokens per request, OTHER 3: after the first two
+    // requests OTHER stays below GOOD for the next three.
+    for _ in 0..5 {
+        client.send(req()).await.unwrap();
+    }
+    let stats = client.key_stats().unwrap();
+    assert_eq!(stats[0].requests, 1);
+    assert_eq!(stats[1].requests, 4);
+}
+
+#[tokio::test]
+async fn exhausted_pool_returns_error() {
+    let server = server().await;
+    let client = ApiClient::new("unused")
+        .with_base_url(server.uri())
+        .with_key_pool(KeyPool::new([REVOKED]));
+
+    let err = client.send(req()).await.unwrap_err();
+    assert!(matches!(err, ApiError::Http { status, .. } if status.as_u16() == 401));
+
+    let err = client.send(req()).await.unwrap_err();
+    assert!(matches!(err, ApiError::NoUsableKeys));
+}
+
+#[tokio::test]
+async fn streamed_usage_is_counted() {
+    let server = MockServer::start().await;
+    let body = concat!(
+        "data: {\"id\":\"c\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"deepseek-chat\",",
+        "\"choices\":[{\"index\":0,\"delta\":{\"content\":\"hi\"},\"finish_reason\":null}]}\n\n",
+        "data: {\"id\":\"c\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"deepseek-chat\",",
+        "\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"stop\"}],",
+        "\"usage\":{\"prompt_tokens\":4,\"completion_tokens\":1,\"total_tokens\":5}}\n\n",
+        "data: [DONE]\n\n",
+    );
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&server)
+        .await;
+    let client = ApiClient::new("unused")
+        .with_base_url(server.uri())
+        .with_key_pool(KeyPool::new([GOOD]));
+
+    let chunks: Vec<_> = client.send_stream(req()).await.unwrap().collect().await;
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(client.key_stats().unwrap()[0].total_tokens, 5);
+}
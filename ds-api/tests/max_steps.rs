@@ -0,0 +1,84 @@
+//! Integration tests for `DeepseekAgent::with_max_steps`.
+
+mod common;
+
+use common::{Echo, echo_call, run};
+use ds_api::raw::request::message::Role;
+use ds_api::{AgentEvent, DeepseekAgent, MockBackend, MockReply};
+use serde_json::json;
+
+#[tokio::test]
+async fn final_turn_disables_tools_after_max_steps() {
+    let mock = MockBackend::new()
+        .reply(echo_call("call_1"))
+        .reply(echo_call("call_2"))
+        .reply(MockReply::text("final answer"));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .add_tool(Echo)
+        .with_max_steps(2);
+    let (events, _) = run(agent, "go").await;
+
+    let choices: Vec<_> = mock
+        .requests()
+        .iter()
+        .map(|r| serde_json::to_value(r).unwrap()["tool_choice"].clone())
+        .collect();
+    assert_eq!(choices, [json!("auto"), json!("auto"), json!("none")]);
+
+    let results = events
+        .iter()
+        .filter(|e| matches!(e, AgentEvent::ToolResult(r) if r.result["text"].is_string()))
+        .count();
+    assert_eq!(results, 2);
+    let n = events.len();
+    assert!(matches!(&events[n - 4], AgentEvent::Token(t) if t == "final answer"));
+    assert!(matches!(
+        events[n - 1],
+        AgentEvent::MaxStepsReached { max_steps: 2 }
+    ));
+}
+
+#[tokio::test]
+async fn tool_calls_on_the_final_turn_are_not_executed() {
+    // A provider that ignores `tool_choice: none`.
+    let mock = MockBackend::new()
+        .reply(echo_call("call_1"))
+        .reply(echo_call("call_2"));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .with_streaming()
+        .add_tool(Echo)
+        .with_max_steps(1);
+    let (events, agent) = run(agent, "go").await;
+
+    let n = events.len();
+    let AgentEvent::ToolResult(skipped) = &events[n - 2] else {
+        panic!("expected the skipped tool result, got {events:?}");
+    };
+    assert_eq!(skipped.id, "call_2");
+    assert!(skipped.result["error"].as_str().unwrap().contains("limit"));
+    assert!(matches!(
+        events[n - 1],
+        AgentEvent::MaxStepsReached { max_steps: 1 }
+    ));
+    let last = agent.history().last().unwrap();
+    assert!(matches!(last.role, Role::Tool));
+    assert_eq!(last.tool_call_id.as_deref(), Some("call_2"));
+}
+
+#[tokio::test]
+async fn runs_within_the_limit_end_normally() {
+    let mock = MockBackend::new()
+        .reply(echo_call("call_1"))
+        .reply(MockReply::text("done"));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .add_tool(Echo)
+        .with_max_steps(2);
+    let (events, _) = run(agent, "go").await;
+
+    assert!(matches!(
+        events.last(),
+        Some(AgentEvent::TurnFinished { .. })
+    ));
+    let last = serde_json::to_value(&mock.requests()[1]).unwrap();
+    assert_eq!(last["tool_choice"], "auto");
+}
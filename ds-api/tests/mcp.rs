@@ -0,0 +1,96 @@
+//! Integration tests for `McpTool` against an in-process MCP server.
+#![cfg(all(feature = "mcp", feature = "mcp-server"))]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use ds_api::{CancellationToken, McpTool, Tool};
+use rmcp::model::{
+    CallToolRequestParams, CallToolResult, ListToolsResult, PaginatedRequestParams,
+    ServerCapabilities, ServerInfo, Tool as RmcpTool, ToolsCapability,
+};
+use rmcp::service::{RequestContext, RoleServer};
+use rmcp::{ErrorData, ServerHandler, ServiceExt};
+use serde_json::json;
+use tokio::sync::Notify;
+
+/// A server whose only tool, `wait`, runs until the client cancels it.
+#[derive(Clone, Default)]
+struct Waiter {
+    cancelled: Arc<Notify>,
+}
+
+impl ServerHandler for Waiter {
+    fn get_info(&self) -> ServerInfo {
+        let mut capabilities = ServerCapabilities::default();
+        capabilities.tools = Some(ToolsCapability::default());
+        ServerInfo::new(capabilities)
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        let wait = RmcpTool::new_with_raw("wait", None, Arc::new(serde_json::Map::new()));
+        Ok(ListToolsResult::with_all_items(vec![wait]))
+    }
+
+    async fn call_tool(
+        &self,
+        _request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        context.ct.cancelled().await;
+        self.cancelled.notify_one();
+        Ok(CallToolResult::structured(json!({})))
+    }
+}
+
+async fn connect(server: Waiter) -> McpTool {
+    let (server_io, client_io) = tokio::io::duplex(4096);
+    tokio::spawn(async move {
+        let running = server.serve(server_io).await.unwrap();
+        let _ = running.waiting().await;
+    });
+    McpTool::from_transport(client_io).await.unwrap()
+}
+
+async fn assert_server_cancelled(server: &Waiter) {
+    tokio::time::timeout(Duration::from_secs(5), server.cancelled.notified())
+        .await
+        .expect("the server never saw the call cancelled");
+}
+
+#[tokio::test]
+async fn cancelling_the_token_ends_the_call_and_notifies_the_server() {
+    let server = Waiter::default();
+    let tool = connect(server.clone()).await;
+
+    let cancel = CancellationToken::new();
+    let (result, ()) = tokio::join!(
+        tool.call_with_cancel("wait", json!({}), cancel.clone()),
+        async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            cancel.cancel();
+        },
+    );
+
+    assert_eq!(result, json!({ "error": "cancelled" }));
+    assert_server_cancelled(&server).await;
+}
+
+#[tokio::test]
+async fn dropping_the_call_notifies_the_server() {
+    let server = Waiter::default();
+    let tool = connect(server.clone()).await;
+
+    let call = tool.call_with_cancel("wait", json!({}), CancellationToken::new());
+    assert!(
+        tokio::time::timeout(Duration::from_millis(50), call)
+            .await
+            .is_err()
+    );
+
+    assert_server_cancelled(&server).await;
+}
@@ -0,0 +1,137 @@
+//! Agent-level tests driven by the scripted `MockBackend` — no HTTP involved.
+
+mod common;
+
+use common::run;
+use ds_api::raw::request::message::Role;
+use ds_api::{AgentEvent, ApiError, DeepseekAgent, MockBackend, MockReply, RequestOptions, tool};
+use futures::StreamExt;
+use serde_json::json;
+
+struct Adder;
+
+#[tool]
+impl ds_api::Tool for Adder {
+    /// Add two numbers.
+    async fn add(&self, a: i64, b: i64) -> serde_json::Value {
+        json!({ "sum": a + b })
+    }
+}
+
+fn script() -> MockBackend {
+    MockBackend::new()
+        .reply(MockReply::tool_call(
+            "call_1",
+            "add",
+            json!({ "a": 2, "b": 3 }),
+        ))
+        .reply(MockReply::deltas(["The sum ", "is 5."]))
+}
+
+#[tokio::test]
+async fn non_streaming_agent_runs_tool_round_trip() {
+    let mock = script();
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat").add_tool(Adder);
+    let (events, agent) = run(agent, "what is 2 + 3?").await;
+
+    // Each turn closes with Usage and TurnFinished before its tools run.
+    assert!(matches!(&events[0], AgentEvent::ToolCall(c) if c.name == "add"));
+    assert!(matches!(&events[1], AgentEvent::Usage(_)));
+    assert!(matches!(&events[2], AgentEvent::TurnFinished { .. }));
+    assert!(matches!(&events[3], AgentEvent::ToolResult(r) if r.result["sum"] == 5));
+    assert!(matches!(&events[4], AgentEvent::Token(t) if t == "The sum is 5."));
+
+    // The second request carries the tool result back to the model.
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    let last = requests[1].messages.last().unwrap();
+    assert!(matches!(last.role, Role::Tool));
+    assert_eq!(last.tool_call_id.as_deref(), Some("call_1"));
+
+    assert_eq!(agent.history().len(), 4);
+}
+
+#[tokio::test]
+async fn streaming_agent_receives_scripted_deltas() {
+    let mock = script();
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .with_streaming()
+        .add_tool(Adder);
+    let (events, _) = run(agent, "what is 2 + 3?").await;
+
+    let tokens: Vec<_> = events
+        .iter()
+        .filter_map(|e| match e {
+            AgentEvent::Token(t) => Some(t.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(tokens, ["The sum ", "is 5."]);
+    assert!(
+        events
+            .iter()
+            .any(|e| matches!(e, AgentEvent::ToolResult(r) if r.result["sum"] == 5))
+    );
+    assert_eq!(mock.remaining(), 0);
+}
+
+#[tokio::test]
+async fn scripted_errors_surface_from_the_agent() {
+    let mock = MockBackend::new().error(ApiError::Other("backend down".into()));
+    let mut stream = DeepseekAgent::from_client(mock, "deepseek-chat").chat("hi");
+
+    let err = stream.next().await.unwrap().unwrap_err();
+    assert!(matches!(err, ApiError::Other(m) if m == "backend down"));
+}
+
+#[tokio::test]
+async fn request_options_and_turn_hook_shape_each_turn() {
+    let mock = script();
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .add_tool(Adder)
+        .with_options(RequestOptions::new().temperature(0.1).max_tokens(256))
+        .with_turn_hook(|ctx, opts| {
+            if ctx.turn == 1 {
+                opts.tool_choice_function("add")
+            } else {
+                opts.tool_choice_none().temperature(0.0)
+            }
+        });
+    let (_, agent) = run(agent, "what is 2 + 3?").await;
+
+    let requests: Vec<_> = mock
+        .requests()
+        .iter()
+        .map(|r| serde_json::to_value(r).unwrap())
+        .collect();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0]["max_tokens"], 256);
+    assert!((requests[0]["temperature"].as_f64().unwrap() - 0.1).abs() < 1e-6);
+    assert_eq!(requests[0]["tool_choice"]["function"]["name"], "add");
+    assert_eq!(requests[1]["max_tokens"], 256);
+    assert_eq!(requests[1]["temperature"], 0.0);
+    assert_eq!(requests[1]["tool_choice"], "none");
+
+    // The turn counter restarts with every `chat()`.
+    mock.push_reply(MockReply::text("again"));
+    run(agent, "once more").await;
+    let third = serde_json::to_value(&mock.requests()[2]).unwrap();
+    assert_eq!(third["tool_choice"]["function"]["name"], "add");
+}
+
+#[tokio::test]
+async fn tool_choice_is_left_out_without_tools() {
+    let mock = MockBackend::new()
+        .reply(MockReply::text("one"))
+        .reply(MockReply::text("two"));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .with_options(RequestOptions::new().tool_choice_function("add"));
+    let (_, agent) = run(agent, "hi").await;
+    let agent = agent.with_options(RequestOptions::new().tool_choice_required());
+    run(agent, "again").await;
+
+    for r in mock.requests() {
+        assert!(r.tools.is_none());
+        assert!(r.tool_choice.is_none());
+    }
+}
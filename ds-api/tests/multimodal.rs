@@ -0,0 +1,79 @@
+//! Integration tests for multimodal (text + image) message content.
+
+mod common;
+
+use common::completion_from;
+use ds_api::raw::request::message::{Message, Role};
+use ds_api::raw::{ContentPart, ImageDetail};
+use ds_api::{ApiClient, ApiError, ApiRequest, DeepseekAgent};
+use futures::StreamExt;
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(completion_from("vision-model", "A cat.")),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn agent_sends_content_parts_as_an_array() {
+    let server = server().await;
+    let agent = DeepseekAgent::custom("k", server.uri(), "vision-model");
+
+    let mut stream = agent.chat_parts(vec![
+        ContentPart::text("What is this?"),
+        ContentPart::image_data("image/png", &[0x89, b'P', b'N', b'G'])
+            .with_detail(ImageDetail::Low),
+    ]);
+    while let Some(ev) = stream.next().await {
+        ev.unwrap();
+    }
+    let agent = stream.into_agent().unwrap();
+
+    let body: serde_json::Value = server.received_requests().await.unwrap()[0]
+        .body_json()
+        .unwrap();
+    assert_eq!(
+        body["messages"][0]["content"],
+        json!([
+            { "type": "text", "text": "What is this?" },
+            { "type": "image_url", "image_url": { "url": "data:image/png;base64,iVBORw==", "detail": "low" } }
+        ])
+    );
+
+    let history = agent.history();
+    assert_eq!(history[0].content.as_deref(), Some("What is this?"));
+    assert_eq!(history[0].image_count(), 1);
+    assert_eq!(history[1].content.as_deref(), Some("A cat."));
+}
+
+#[tokio::test]
+async fn images_outside_user_messages_are_rejected() {
+    let server = server().await;
+    let client = ApiClient::new("k").with_base_url(server.uri());
+
+    let bad = Message::from_parts(
+        Role::Assistant,
+        vec![ContentPart::image_url("https://example.com/a.png")],
+    );
+    let err = client
+        .send(ApiRequest::builder().add_message(bad))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ApiError::Validation {
+            field: "messages",
+            ..
+        }
+    ));
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
@@ -0,0 +1,139 @@
+//! Integration tests for `DeepseekAgent::with_parallel_tools`.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use ds_api::raw::request::message::Role;
+use ds_api::{AgentEvent, DeepseekAgent, MockBackend, MockReply, tool};
+use futures::StreamExt;
+use serde_json::json;
+
+#[derive(Default)]
+struct Sleeper {
+    running: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+#[tool]
+impl ds_api::Tool for Sleeper {
+    /// Sleep for `ms` milliseconds.
+    async fn sleep(&self, ms: u64) -> serde_json::Value {
+        let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        json!({ "slept": ms })
+    }
+}
+
+fn sleeps(ms: &[u64]) -> MockReply {
+    ms.iter()
+        .enumerate()
+        .fold(MockReply::default(), |reply, (i, ms)| {
+            reply.with_tool_call(format!("call_{i}"), "sleep", json!({ "ms": ms }))
+        })
+}
+
+fn result_ids(events: &[AgentEvent]) -> Vec<String> {
+    events
+        .iter()
+        .filter_map(|e| match e {
+            AgentEvent::ToolResult(r) => Some(r.id.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn tool_message_ids(agent: &DeepseekAgent) -> Vec<String> {
+    agent
+        .history()
+        .iter()
+        .filter(|m| matches!(m.role, Role::Tool))
+        .map(|m| m.tool_call_id.clone().unwrap())
+        .collect()
+}
+
+#[tokio::test(start_paused = true)]
+async fn results_arrive_as_tools_finish_but_history_keeps_call_order() {
+    let mock = MockBackend::new()
+        .reply(sleeps(&[300, 100, 200]))
+        .reply(MockReply::text("done"));
+    let agent = DeepseekAgent::from_client(mock, "deepseek-chat")
+        .add_tool(Sleeper::default())
+        .with_parallel_tools(3);
+
+    let started = tokio::time::Instant::now();
+    let mut stream = agent.chat("go");
+    let mut events = Vec::new();
+    while let Some(ev) = stream.next().await {
+        events.push(ev.unwrap());
+    }
+    let agent = stream.into_agent().unwrap();
+
+    assert_eq!(result_ids(&events), ["call_1", "call_2", "call_0"]);
+    assert_eq!(tool_message_ids(&agent), ["call_0", "call_1", "call_2"]);
+    // Run side by side, the batch takes as long as its slowest tool.
+    assert!(started.elapsed() < Duration::from_millis(400));
+}
+
+#[tokio::test(start_paused = true)]
+async fn concurrency_limit_is_respected() {
+    let tool = Sleeper::default();
+    let peak = tool.peak.clone();
+    let mock = MockBackend::new()
+        .reply(sleeps(&[10, 10, 10, 10, 10]))
+        .reply(MockReply::text("done"));
+    let agent = DeepseekAgent::from_client(mock, "deepseek-chat")
+        .add_tool(tool)
+        .with_parallel_tools(2);
+
+    let mut stream = agent.chat("go");
+    let mut events = Vec::new();
+    while let Some(ev) = stream.next().await {
+        events.push(ev.unwrap());
+    }
+
+    assert_eq!(result_ids(&events).len(), 5);
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn interrupt_aborts_the_unfinished_rest_of_the_batch() {
+    let mock = MockBackend::new()
+        .reply(sleeps(&[60_000, 10, 60_000]))
+        .reply(MockReply::text("ok, stopping"));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .add_tool(Sleeper::default())
+        .with_parallel_tools(3);
+    let interrupt = agent.interrupt_sender();
+
+    let mut stream = agent.chat("go");
+    let mut results = Vec::new();
+    while let Some(ev) = stream.next().await {
+        if let AgentEvent::ToolResult(r) = ev.unwrap() {
+            // Interrupt as soon as the quick tool is done.
+            if results.is_empty() {
+                interrupt.send("stop".into()).unwrap();
+            }
+            results.push((r.id, r.result));
+        }
+    }
+    let agent = stream.into_agent().unwrap();
+
+    let aborted = json!({ "error": "aborted by interrupt" });
+    assert_eq!(
+        results,
+        [
+            ("call_1".to_string(), json!({ "slept": 10 })),
+            ("call_0".to_string(), aborted.clone()),
+            ("call_2".to_string(), aborted),
+        ]
+    );
+    assert_eq!(tool_message_ids(&agent), ["call_0", "call_1", "call_2"]);
+    // The interrupt follows the tool messages in the next request.
+    let second = &mock.requests()[1].messages;
+    let last = second.last().unwrap();
+    assert!(matches!(last.role, Role::User));
+    assert_eq!(last.content.as_deref(), Some("stop"));
+}
@@ -0,0 +1,119 @@
+//! Integration tests for beta chat prefix completion.
+
+mod common;
+
+use common::completion;
+use ds_api::raw::request::message::Message;
+use ds_api::{AgentEvent, ApiClient, ApiRequest, DeepseekAgent, MockBackend, MockReply};
+use futures::StreamExt;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn server(route: &str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(route))
+        .respond_with(ResponseTemplate::new(200).set_body_json(completion("fn main() {}\n")))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn prefix_requests_go_to_the_beta_endpoint() {
+    let server = server("/beta/chat/completions").await;
+    let client = ApiClient::new("k").with_base_url(server.uri());
+
+    let req = ApiRequest::builder()
+        .add_message(Message::user("hello world in rust"))
+        .assistant_prefix("```rust\n");
+    let resp = client.send(req).await.unwrap();
+    assert_eq!(
+        resp.choices[0].message.content.as_deref(),
+        Some("fn main() {}\n")
+    );
+
+    let body: serde_json::Value = server.received_requests().await.unwrap()[0]
+        .body_json()
+        .unwrap();
+    assert_eq!(body["messages"][1]["prefix"], true);
+
+    // Requests without a prefix keep using the regular endpoint.
+    let err = client
+        .send(ApiRequest::builder().add_message(Message::user("hi")))
+        .await
+        .unwrap_err();
+    assert_eq!(err.status().map(|s| s.as_u16()), Some(404));
+}
+
+#[tokio::test]
+async fn custom_beta_base_url_is_used() {
+    let server = server("/v1beta/chat/completions").await;
+    let client = ApiClient::new("k")
+        .with_base_url("http://127.0.0.1:9")
+        .with_beta_base_url(format!("{}/v1beta", server.uri()));
+
+    let req = ApiRequest::builder()
+        .add_message(Message::user("hi"))
+        .assistant_prefix("```");
+    client.send(req).await.unwrap();
+}
+
+#[tokio::test]
+async fn agent_stitches_prefix_and_continuation() {
+    let mock = MockBackend::new().reply(MockReply::deltas(["fn main() ", "{}\n"]));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat").with_streaming();
+
+    let mut stream = agent.chat_with_prefix("hello world in rust", "```rust\n");
+    let mut tokens = String::new();
+    while let Some(ev) = stream.next().await {
+        if let AgentEvent::Token(t) = ev.unwrap() {
+            tokens.push_str(&t);
+        }
+    }
+    let agent = stream.into_agent().unwrap();
+
+    assert_eq!(tokens, "fn main() {}\n");
+    let last = mock.requests()[0].messages.last().cloned().unwrap();
+    assert!(last.is_prefix());
+
+    let history = agent.history();
+    assert_eq!(history.len(), 2);
+    assert_eq!(
+        history[1].content.as_deref(),
+        Some("```rust\nfn main() {}\n")
+    );
+    assert!(!history[1].is_prefix());
+}
+
+#[tokio::test]
+async fn a_failed_prefix_turn_leaves_the_agent_usable() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/beta/chat/completions"))
+        .respond_with(ResponseTemplate::new(400).set_body_string("bad"))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(completion("hello")))
+        .mount(&server)
+        .await;
+    let client = ApiClient::new("k").with_base_url(server.uri());
+    let agent = DeepseekAgent::from_client(client, "deepseek-chat");
+
+    let mut stream = agent.chat_with_prefix("Write quicksort in Rust.", "```rust\n");
+    assert!(stream.next().await.unwrap().is_err());
+    while stream.next().await.is_some() {}
+    let agent = stream.into_agent().unwrap();
+    assert_eq!(agent.history().len(), 1);
+
+    let mut stream = agent.chat("Never mind, say hello.");
+    let mut text = String::new();
+    while let Some(ev) = stream.next().await {
+        if let AgentEvent::Token(t) = ev.unwrap() {
+            text.push_str(&t);
+        }
+    }
+    assert_eq!(text, "hello");
+}
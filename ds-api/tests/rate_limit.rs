@@ -0,0 +1,102 @@
+//! Integration tests for the client-side rate limiter.
+
+use ds_api::raw::request::message::Message;
+use ds_api::{ApiClient, ApiRequest, RateLimit};
+use futures::StreamExt;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const SSE_BODY: &str = concat!(
+    "data: {\"id\":\"1\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"deepseek-chat\",",
+    "\"choices\":[{\"index\":0,\"delta\":{\"content\":\"hi\"},\"finish_reason\":null}]}\n\n",
+    "data: [DONE]\n\n",
+);
+
+async fn sse_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(SSE_BODY),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn clones_share_in_flight_slots() {
+    let server = sse_server().await;
+    let client = ApiClient::new("k")
+        .with_base_url(server.uri())
+        .with_rate_limit(RateLimit::new().max_in_flight(2));
+    let clone = client.clone();
+
+    let req = ApiRequest::deepseek_chat(vec![Message::user("hi")]);
+    let mut stream = client.send_stream(req).await.unwrap();
+
+    // The open stream holds a slot, and the clone sees it.
+    assert_eq!(clone.rate_limit_stats().unwrap().in_flight, 1);
+
+    while stream.next().await.is_some() {}
+    drop(stream);
+    assert_eq!(clone.rate_limit_stats().unwrap().in_flight, 0);
+    assert_eq!(clone.rate_limit_stats().unwrap().queued, 0);
+}
+
+#[tokio::test]
+async fn request_budget_is_shared_between_clones() {
+    let server = sse_server().await;
+    let client = ApiClient::new("k")
+        .with_base_url(server.uri())
+        .with_rate_limit(RateLimit::new().requests_per_minute(5));
+    let clone = client.clone();
+
+    let req = ApiRequest::deepseek_chat(vec![Message::user("hi")]);
+    drop(client.send_stream(req).await.unwrap());
+
+    let left = clone
+        .rate_limit_stats()
+        .unwrap()
+        .available_requests
+        .unwrap();
+    assert!((3.9..4.1).contains(&left), "unexpected budget {left}");
+}
+
+#[tokio::test]
+async fn streamed_usage_reconciles_the_token_budget() {
+    let body = concat!(
+        "data: {\"id\":\"1\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"deepseek-chat\",",
+        "\"choices\":[{\"index\":0,\"delta\":{\"content\":\"hi\"},\"finish_reason\":\"stop\"}],",
+        "\"usage\":{\"prompt_tokens\":4,\"completion_tokens\":1,\"total_tokens\":5}}\n\n",
+        "data: [DONE]\n\n",
+    );
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(body),
+        )
+        .mount(&server)
+        .await;
+    let client = ApiClient::new("k")
+        .with_base_url(server.uri())
+        .with_rate_limit(RateLimit::new().tokens_per_minute(100_000));
+
+    // The `max_tokens` reservation is returned once the usage chunk arrives.
+    let req = ApiRequest::deepseek_chat(vec![Message::user("hi")]).max_tokens(50_000);
+    let mut stream = client.send_stream(req).await.unwrap();
+    while stream.next().await.is_some() {}
+
+    let left = client.rate_limit_stats().unwrap().available_tokens.unwrap();
+    assert!(left > 99_000.0, "unexpected budget {left}");
+}
+
+#[test]
+fn no_limit_means_no_stats() {
+    assert!(ApiClient::new("k").rate_limit_stats().is_none());
+}
@@ -0,0 +1,119 @@
+//! Integration tests for `RetryPolicy` against a local wiremock server.
+
+mod common;
+
+use std::time::Duration;
+
+use common::completion;
+use ds_api::raw::request::message::Message;
+use ds_api::{AgentEvent, ApiClient, ApiError, ApiRequest, DeepseekAgent, RetryPolicy};
+use futures::StreamExt;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+fn fast_policy() -> RetryPolicy {
+    RetryPolicy::new()
+        .max_attempts(3)
+        .initial_backoff(Duration::from_millis(1))
+        .jitter(false)
+}
+
+async fn mount_failures(server: &MockServer, status: u16, times: u64) {
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(status).insert_header("retry-after", "0"))
+        .up_to_n_times(times)
+        .mount(server)
+        .await;
+}
+
+async fn mount_success(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(completion("ok")))
+        .mount(server)
+        .await;
+}
+
+fn request() -> ApiRequest {
+    ApiRequest::deepseek_chat(vec![Message::user("hi")])
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn retries_transient_status_until_success() {
+    let server = MockServer::start().await;
+    mount_failures(&server, 503, 2).await;
+    mount_success(&server).await;
+
+    let client = ApiClient::new("k")
+        .with_base_url(server.uri())
+        .with_retry(fast_policy());
+    let resp = client.send(request()).await.unwrap();
+
+    assert_eq!(resp.content(), Some("ok"));
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let server = MockServer::start().await;
+    mount_failures(&server, 429, 5).await;
+
+    let client = ApiClient::new("k")
+        .with_base_url(server.uri())
+        .with_retry(fast_policy());
+    let err = client.send(request()).await.unwrap_err();
+
+    assert!(matches!(err, ApiError::Http { status, .. } if status.as_u16() == 429));
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn does_not_retry_client_errors() {
+    let server = MockServer::start().await;
+    mount_failures(&server, 400, 5).await;
+
+    let client = ApiClient::new("k")
+        .with_base_url(server.uri())
+        .with_retry(fast_policy());
+    assert!(client.send(request()).await.is_err());
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn no_policy_means_single_attempt() {
+    let server = MockServer::start().await;
+    mount_failures(&server, 503, 1).await;
+    mount_success(&server).await;
+
+    let client = ApiClient::new("k").with_base_url(server.uri());
+    assert!(client.send(request()).await.is_err());
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn agent_emits_retry_events() {
+    let server = MockServer::start().await;
+    mount_failures(&server, 503, 1).await;
+    mount_success(&server).await;
+
+    let client = ApiClient::new("k")
+        .with_base_url(server.uri())
+        .with_retry(fast_policy());
+    let mut stream = DeepseekAgent::from_client(client, "deepseek-chat").chat("hi");
+
+    let mut events = Vec::new();
+    while let Some(ev) = stream.next().await {
+        events.push(ev.unwrap());
+    }
+
+    assert!(matches!(
+        &events[0],
+        AgentEvent::Retry(r) if r.attempt == 1 && r.status.map(|s| s.as_u16()) == Some(503)
+    ));
+    assert!(matches!(&events[1], AgentEvent::Token(t) if t == "ok"));
+}
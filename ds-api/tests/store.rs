@@ -0,0 +1,201 @@
+//! Integration tests for persisting conversations to a `ConversationStore`.
+
+mod common;
+
+use std::path::PathBuf;
+
+use common::run;
+use ds_api::conversation::{Conversation, SlidingWindowSummarizer};
+use ds_api::raw::request::message::{Message, Role};
+use ds_api::{
+    AgentEvent, ConversationStore, DeepseekAgent, JsonlStore, MockBackend, MockReply, tool,
+};
+use futures::StreamExt;
+use serde_json::json;
+
+struct Counter;
+
+#[tool]
+impl ds_api::Tool for Counter {
+    /// Add one to `n`.
+    async fn incr(&self, n: u64) -> serde_json::Value {
+        json!({ "n": n + 1 })
+    }
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ds-api-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+async fn roles(store: &impl ConversationStore, session: &str) -> Vec<Role> {
+    store
+        .load(session)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|m| m.role)
+        .collect()
+}
+
+#[tokio::test]
+async fn agent_runs_are_persisted_and_reloaded() {
+    let store = JsonlStore::new(temp_dir("store-agent"));
+    let mock = MockBackend::new()
+        .reply(MockReply::tool_call("call_1", "incr", json!({ "n": 1 })))
+        .reply(MockReply::text("n is 2"));
+    let agent = DeepseekAgent::from_client(mock, "deepseek-chat")
+        .add_tool(Counter)
+        .with_store(store.clone(), "s1");
+    run(agent, "count").await;
+
+    assert!(matches!(
+        roles(&store, "s1").await.as_slice(),
+        [Role::User, Role::Assistant, Role::Tool, Role::Assistant]
+    ));
+
+    // A new process picks up where the last one stopped.
+    let mock = MockBackend::new().reply(MockReply::text("still 2"));
+    let mut agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .with_streaming()
+        .with_store(store.clone(), "s1");
+    agent.load_history().await.unwrap();
+    run(agent, "and now?").await;
+
+    assert_eq!(mock.requests()[0].messages.len(), 5);
+    let stored = store.load("s1").await.unwrap();
+    assert_eq!(stored.len(), 6);
+    assert_eq!(stored[5].content.as_deref(), Some("still 2"));
+    assert_eq!(store.list_sessions().await.unwrap(), ["s1"]);
+}
+
+#[tokio::test]
+async fn resuming_a_checkpoint_replaces_the_stored_session() {
+    let store = JsonlStore::new(temp_dir("store-resume"));
+    let mock = MockBackend::new().reply(MockReply::tool_call("call_1", "incr", json!({ "n": 1 })));
+    let agent = DeepseekAgent::from_client(mock, "deepseek-chat")
+        .add_tool(Counter)
+        .with_checkpoints()
+        .with_store(store.clone(), "s1");
+    let mut stream = agent.chat("count");
+    let checkpoint = loop {
+        if let AgentEvent::Checkpoint(c) = stream.next().await.unwrap().unwrap() {
+            break *c;
+        }
+    };
+    drop(stream);
+    // The first process saved the user message before its first turn.
+    assert_eq!(store.load("s1").await.unwrap().len(), 1);
+
+    // A new process resumes into the same session.
+    let mock = MockBackend::new().reply(MockReply::text("n is 2"));
+    let agent = DeepseekAgent::from_client(mock, "deepseek-chat")
+        .add_tool(Counter)
+        .with_store(store.clone(), "s1");
+    let mut stream = agent.resume_from(checkpoint);
+    while let Some(ev) = stream.next().await {
+        ev.unwrap();
+    }
+
+    assert!(matches!(
+        roles(&store, "s1").await.as_slice(),
+        [Role::User, Role::Assistant, Role::Tool, Role::Assistant]
+    ));
+}
+
+#[tokio::test]
+async fn summarization_replaces_the_stored_session() {
+    let store = JsonlStore::new(temp_dir("store-summary"));
+    let mock = MockBackend::new()
+        .reply(MockReply::text("one"))
+        .reply(MockReply::text("two"));
+    let mut conv = Conversation::new(mock)
+        .with_summarizer(SlidingWindowSummarizer::new(2))
+        .with_store(store.clone(), "s1");
+
+    conv.push_user_input("first");
+    conv.send_once().await.unwrap();
+    conv.push_user_input("second");
+    conv.send_once().await.unwrap();
+
+    let stored = store.load("s1").await.unwrap();
+    assert_eq!(stored.len(), conv.history().len());
+    let texts: Vec<_> = stored.iter().map(|m| m.content.as_deref()).collect();
+    assert_eq!(texts, [Some("second"), Some("two")]);
+}
+
+#[tokio::test]
+async fn edits_after_load_history_replace_the_stored_session() {
+    let store = JsonlStore::new(temp_dir("store-edit"));
+    let mock = MockBackend::new().reply(MockReply::text("one"));
+    let mut conv = Conversation::new(mock).with_store(store.clone(), "s1");
+    conv.push_user_input("first");
+    conv.send_once().await.unwrap();
+
+    let mut conv = Conversation::new(MockBackend::new()).with_store(store.clone(), "s1");
+    conv.load_history().await.unwrap();
+    conv.history_mut()[1].content = Some("edited".into());
+    conv.history_mut()
+        .insert(0, Message::new(Role::System, "be brief"));
+    conv.push_user_input("second");
+    conv.persist().await.unwrap();
+
+    let texts: Vec<_> = store
+        .load("s1")
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|m| m.content)
+        .collect();
+    assert_eq!(
+        texts,
+        [
+            Some("be brief".to_string()),
+            Some("first".to_string()),
+            Some("edited".to_string()),
+            Some("second".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn attaching_a_store_without_loading_replaces_the_session() {
+    let store = JsonlStore::new(temp_dir("store-attach"));
+    let mock = MockBackend::new().reply(MockReply::text("hello"));
+    run(
+        DeepseekAgent::from_client(mock, "deepseek-chat").with_store(store.clone(), "s1"),
+        "hi",
+    )
+    .await;
+
+    let mock = MockBackend::new().reply(MockReply::text("hello again"));
+    let agent = DeepseekAgent::from_client(mock, "deepseek-chat")
+        .with_store(store.clone(), "s1")
+        .with_system_prompt("be brief");
+    run(agent, "hi again").await;
+
+    assert!(matches!(
+        roles(&store, "s1").await.as_slice(),
+        [Role::System, Role::User, Role::Assistant]
+    ));
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_store_persists_agent_runs() {
+    use ds_api::SqliteStore;
+
+    let path = temp_dir("store-sqlite").with_extension("db");
+    let _ = std::fs::remove_file(&path);
+    let store = SqliteStore::open(&path).unwrap();
+    let mock = MockBackend::new().reply(MockReply::text("hello"));
+    let agent = DeepseekAgent::from_client(mock, "deepseek-chat").with_store(store, "s1");
+    run(agent, "hi").await;
+
+    let reopened = SqliteStore::open(&path).unwrap();
+    assert!(matches!(
+        roles(&reopened, "s1").await.as_slice(),
+        [Role::User, Role::Assistant]
+    ));
+}
@@ -0,0 +1,113 @@
+//! Integration tests for streaming first-byte / idle / total timeouts.
+
+use std::time::Duration;
+
+use ds_api::raw::request::message::Message;
+use ds_api::{AgentEvent, ApiClient, ApiError, ApiRequest, DeepseekAgent, StreamTimeouts};
+use futures::StreamExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const CHUNK: &str = "data: {\"id\":\"c\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"deepseek-chat\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"hel\"},\"finish_reason\":null}]}\n\n";
+
+/// Serve one SSE response that sends a single chunk and then stalls with the
+/// connection open.
+async fn stalling_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = vec![0u8; 64 * 1024];
+        let _ = socket.read(&mut buf).await;
+        let head = "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n";
+        socket.write_all(head.as_bytes()).await.unwrap();
+        let body = format!("{:x}\r\n{CHUNK}\r\n", CHUNK.len());
+        socket.write_all(body.as_bytes()).await.unwrap();
+        socket.flush().await.unwrap();
+        tokio::time::sleep(Duration::from_secs(60)).await;
+    });
+    format!("http://{addr}")
+}
+
+fn req() -> ApiRequest {
+    ApiRequest::builder().add_message(Message::user("hi"))
+}
+
+#[tokio::test]
+async fn idle_stream_fails_with_idle_timeout() {
+    let client = ApiClient::new("k")
+        .with_base_url(stalling_server().await)
+        .with_stream_timeouts(StreamTimeouts::new().idle(Duration::from_millis(200)));
+
+    let items: Vec<_> = client.send_stream(req()).await.unwrap().collect().await;
+    assert_eq!(items.len(), 2);
+    assert!(items[0].is_ok());
+    assert!(matches!(items[1], Err(ApiError::StreamIdleTimeout(_))));
+}
+
+#[tokio::test]
+async fn total_deadline_ends_stream() {
+    let client = ApiClient::new("k")
+        .with_base_url(stalling_server().await)
+        .with_stream_timeouts(StreamTimeouts::new().total(Duration::from_millis(300)));
+
+    let items: Vec<_> = client.send_stream(req()).await.unwrap().collect().await;
+    assert!(matches!(
+        items.last(),
+        Some(Err(ApiError::StreamDeadlineExceeded(_)))
+    ));
+}
+
+#[tokio::test]
+async fn slow_headers_hit_first_byte_timeout() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+        .mount(&server)
+        .await;
+    let client = ApiClient::new("k")
+        .with_base_url(server.uri())
+        .with_stream_timeouts(StreamTimeouts::new().first_byte(Duration::from_millis(200)));
+
+    let err = client.send_stream(req()).await.err().unwrap();
+    assert!(matches!(err, ApiError::StreamFirstByteTimeout(_)));
+}
+
+#[tokio::test]
+async fn slow_headers_count_against_total_deadline() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+        .mount(&server)
+        .await;
+    let client = ApiClient::new("k")
+        .with_base_url(server.uri())
+        .with_stream_timeouts(StreamTimeouts::new().total(Duration::from_millis(200)));
+
+    let err = client.send_stream(req()).await.err().unwrap();
+    assert!(matches!(err, ApiError::StreamDeadlineExceeded(_)));
+}
+
+#[tokio::test]
+async fn agent_surfaces_timeout_and_returns_agent() {
+    let client = ApiClient::new("k")
+        .with_base_url(stalling_server().await)
+        .with_stream_timeouts(StreamTimeouts::new().idle(Duration::from_millis(200)));
+    let agent = DeepseekAgent::from_client(client, "deepseek-chat").with_streaming();
+
+    let mut stream = agent.chat("hi");
+    let mut events = Vec::new();
+    while let Some(ev) = stream.next().await {
+        events.push(ev);
+    }
+    assert!(matches!(&events[0], Ok(AgentEvent::Token(t)) if t == "hel"));
+    assert!(matches!(
+        events.last(),
+        Some(Err(ApiError::StreamIdleTimeout(_)))
+    ));
+    assert!(stream.into_agent().is_some());
+}
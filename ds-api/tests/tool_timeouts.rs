@@ -0,0 +1,133 @@
+//! Integration tests for tool timeouts and cancellation tokens.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use ds_api::{AgentEvent, CancellationToken, DeepseekAgent, MockBackend, MockReply, Tool, tool};
+use futures::StreamExt;
+use serde_json::json;
+
+struct Work;
+
+#[tool]
+impl ds_api::Tool for Work {
+    /// Fetch something slowly.
+    async fn fetch(&self, ms: u64) -> serde_json::Value {
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+        json!({ "fetched": ms })
+    }
+
+    /// Deploy something slowly.
+    async fn deploy(&self, ms: u64) -> serde_json::Value {
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+        json!({ "deployed": ms })
+    }
+}
+
+/// Blocks until the agent gives up, recording that it saw the cancellation.
+#[derive(Default)]
+struct Stuck {
+    cancelled: Arc<AtomicBool>,
+}
+
+#[tool]
+impl ds_api::Tool for Stuck {
+    /// Never finishes.
+    async fn hang(&self, label: String, cancel: CancellationToken) -> String {
+        let cancelled = self.cancelled.clone();
+        tokio::spawn(async move {
+            cancel.cancelled().await;
+            cancelled.store(true, Ordering::SeqCst);
+        });
+        std::future::pending::<()>().await;
+        label
+    }
+}
+
+async fn results(agent: DeepseekAgent) -> Vec<serde_json::Value> {
+    let mut stream = agent.chat("go");
+    let mut results = Vec::new();
+    while let Some(ev) = stream.next().await {
+        if let AgentEvent::ToolResult(r) = ev.unwrap() {
+            results.push(r.result);
+        }
+    }
+    results
+}
+
+#[tokio::test(start_paused = true)]
+async fn default_and_per_tool_timeouts() {
+    let mock = MockBackend::new()
+        .reply(
+            MockReply::tool_call("call_1", "fetch", json!({ "ms": 5000 }))
+                .with_tool_call("call_2", "deploy", json!({ "ms": 5000 }))
+                .with_tool_call("call_3", "fetch", json!({ "ms": 10 })),
+        )
+        .reply(MockReply::text("done"));
+    let agent = DeepseekAgent::from_client(mock, "deepseek-chat")
+        .add_tool(Work)
+        .with_tool_timeout(Duration::from_secs(1))
+        .with_tool_timeout_for("deploy", Duration::from_secs(10));
+
+    assert_eq!(
+        results(agent).await,
+        [
+            json!({ "error": "timed out", "timeout_ms": 1000 }),
+            json!({ "deployed": 5000 }),
+            json!({ "fetched": 10 }),
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn timed_out_call_sees_its_token_cancelled() {
+    let tool = Stuck::default();
+    let cancelled = tool.cancelled.clone();
+    // The token parameter is not part of the schema.
+    let schema = &tool.raw_tools()[0].function.parameters;
+    assert_eq!(schema["required"], json!(["label"]));
+    assert!(schema["properties"].get("cancel").is_none());
+
+    let mock = MockBackend::new()
+        .reply(MockReply::tool_call(
+            "call_1",
+            "hang",
+            json!({ "label": "x" }),
+        ))
+        .reply(MockReply::text("done"));
+    let agent = DeepseekAgent::from_client(mock, "deepseek-chat")
+        .add_tool(tool)
+        .with_tool_timeout(Duration::from_millis(200));
+
+    assert_eq!(
+        results(agent).await,
+        [json!({ "error": "timed out", "timeout_ms": 200 })]
+    );
+    tokio::task::yield_now().await;
+    assert!(cancelled.load(Ordering::SeqCst));
+}
+
+#[tokio::test(start_paused = true)]
+async fn dropping_the_stream_cancels_running_tools() {
+    let tool = Stuck::default();
+    let cancelled = tool.cancelled.clone();
+    let mock = MockBackend::new().reply(MockReply::tool_call(
+        "call_1",
+        "hang",
+        json!({ "label": "x" }),
+    ));
+    let agent = DeepseekAgent::from_client(mock, "deepseek-chat").add_tool(tool);
+
+    let mut stream = agent.chat("go");
+    // Poll until the tool is running and the stream stalls on it.
+    while tokio::time::timeout(Duration::from_secs(1), stream.next())
+        .await
+        .is_ok()
+    {}
+    assert!(!cancelled.load(Ordering::SeqCst));
+
+    drop(stream);
+    tokio::task::yield_now().await;
+    assert!(cancelled.load(Ordering::SeqCst));
+}
@@ -0,0 +1,287 @@
+//! Integration tests for typed structured output.
+
+mod common;
+
+use common::completion;
+use ds_api::raw::ResponseFormatType;
+use ds_api::raw::request::message::{Message, Role};
+use ds_api::{
+    ApiClient, ApiError, ApiRequest, ApprovalPolicy, Budget, BudgetLimit, DeepseekAgent, ErrorKind,
+    MockBackend, MockReply, RequestOptions, SchemaMode, StructuredOutput, tool,
+};
+use serde::Deserialize;
+use serde_json::json;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[derive(Debug, PartialEq, Deserialize, schemars::JsonSchema)]
+struct City {
+    name: String,
+    population: u64,
+}
+
+async fn server(replies: &[&str]) -> MockServer {
+    let server = MockServer::start().await;
+    for reply in replies {
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion(reply)))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+    }
+    server
+}
+
+fn req() -> ApiRequest {
+    ApiRequest::builder().add_message(Message::user("The largest city in Japan?"))
+}
+
+#[tokio::test]
+async fn send_typed_reasks_with_the_error() {
+    let server = server(&[
+        r#"{"name": "Tokyo"}"#,
+        r#"{"name": "Tokyo", "population": 14000000}"#,
+    ])
+    .await;
+    let client = ApiClient::new("k").with_base_url(server.uri());
+
+    let city: City = client.send_typed(req()).await.unwrap();
+    assert_eq!(city.population, 14_000_000);
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    let first: serde_json::Value = requests[0].body_json().unwrap();
+    assert_eq!(first["response_format"]["type"], "json_object");
+    assert!(
+        first["messages"][0]["content"]
+            .as_str()
+            .unwrap()
+            .contains("\"population\"")
+    );
+    let second: serde_json::Value = requests[1].body_json().unwrap();
+    let messages = second["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 4);
+    assert_eq!(messages[2]["role"], "assistant");
+    assert!(
+        messages[3]["content"]
+            .as_str()
+            .unwrap()
+            .contains("missing field `population`")
+    );
+}
+
+#[tokio::test]
+async fn send_typed_gives_up_after_max_attempts() {
+    let server = server(&["not json", "still not json"]).await;
+    let client = ApiClient::new("k").with_base_url(server.uri());
+
+    let err = client
+        .send_typed_with::<City>(req(), StructuredOutput::new().max_attempts(2))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        &err,
+        ApiError::SchemaMismatch { attempts: 2, content, .. } if content == "still not json"
+    ));
+    assert_eq!(err.kind(), ErrorKind::Decode);
+}
+
+struct Census;
+
+#[tool]
+impl ds_api::Tool for Census {
+    /// Look up a city's population.
+    async fn population(&self, city: String) -> serde_json::Value {
+        json!({ "city": city, "population": 14000000 })
+    }
+}
+
+#[tokio::test]
+async fn agent_runs_tools_then_returns_typed_answer() {
+    let mock = MockBackend::new()
+        .reply(MockReply::tool_call(
+            "call_1",
+            "population",
+            json!({ "city": "Tokyo" }),
+        ))
+        .reply(MockReply::text(
+            "```json\n{\"name\": \"Tokyo\", \"population\": 14000000}\n```",
+        ));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat").add_tool(Census);
+
+    let (city, agent) = agent.chat_typed::<City>("The largest city in Japan?").await;
+    assert_eq!(
+        city.unwrap(),
+        City {
+            name: "Tokyo".into(),
+            population: 14_000_000
+        }
+    );
+    assert_eq!(mock.requests().len(), 2);
+    for r in mock.requests() {
+        assert!(matches!(
+            r.response_format.unwrap().r#type,
+            ResponseFormatType::JsonObject
+        ));
+        assert!(
+            r.messages[0]
+                .content
+                .as_deref()
+                .unwrap()
+                .contains("JSON Schema")
+        );
+    }
+    assert_eq!(agent.history().len(), 4);
+}
+
+#[tokio::test]
+async fn agent_prompt_mode_keeps_the_schema_out_of_history() {
+    let mock = MockBackend::new()
+        .reply(MockReply::text(r#"{"name": "Tokyo", "population": 1}"#))
+        .reply(MockReply::text("you're welcome"));
+    let agent =
+        DeepseekAgent::from_client(mock.clone(), "deepseek-chat").with_system_prompt("be brief");
+
+    let (city, agent) = agent.chat_typed::<City>("The largest city in Japan?").await;
+    city.unwrap();
+    let history: Vec<_> = agent
+        .history()
+        .iter()
+        .map(|m| (m.role.clone(), m.content.clone().unwrap()))
+        .collect();
+    assert!(matches!(
+        history.as_slice(),
+        [(Role::System, system), (Role::User, user), (Role::Assistant, _)]
+            if system == "be brief" && user == "The largest city in Japan?"
+    ));
+    // The schema went out after the agent's own system prompt.
+    let sent = &mock.requests()[0].messages;
+    assert_eq!(sent.len(), 3);
+    assert!(sent[1].content.as_deref().unwrap().contains("JSON Schema"));
+
+    // Later turns carry neither the schema nor JSON mode.
+    let mut stream = agent.chat("thanks");
+    while futures::StreamExt::next(&mut stream).await.is_some() {}
+    let next = &mock.requests()[1];
+    assert_eq!(next.messages.len(), 4);
+    assert!(
+        next.messages
+            .iter()
+            .all(|m| !m.content.as_deref().unwrap_or("").contains("JSON Schema"))
+    );
+    assert!(next.response_format.is_none());
+}
+
+#[tokio::test]
+async fn agent_response_format_mode_reasks_and_resets() {
+    let mock = MockBackend::new()
+        .reply(MockReply::text(
+            r#"{"name": "Tokyo", "population": "many"}"#,
+        ))
+        .reply(MockReply::text(
+            r#"{"name": "Tokyo", "population": 14000000}"#,
+        ))
+        .reply(MockReply::text("plain text"))
+        .reply(MockReply::text("{}"))
+        .reply(MockReply::text("you're welcome"));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat");
+
+    let config = StructuredOutput::new().mode(SchemaMode::ResponseFormat);
+    let (city, agent) = agent
+        .chat_typed_with::<City>("The largest city in Japan?", config)
+        .await;
+    assert_eq!(city.unwrap().name, "Tokyo");
+
+    let requests = mock.requests();
+    for r in &requests {
+        let format = r.response_format.as_ref().unwrap();
+        assert_eq!(format.json_schema.as_ref().unwrap().name, "City");
+    }
+    let correction = requests[1].messages.last().unwrap();
+    assert!(matches!(correction.role, Role::User));
+
+    // Later turns go back to free-form output.
+    let mut stream = agent.chat("thanks");
+    while futures::StreamExt::next(&mut stream).await.is_some() {}
+    let agent = stream.into_agent().unwrap();
+    assert!(mock.requests()[2].response_format.is_none());
+
+    // A format set by the caller survives a typed run.
+    let agent = agent.with_options(RequestOptions::new().json());
+    let (_, agent) = agent
+        .chat_typed_with::<City>("Again?", config.max_attempts(1))
+        .await;
+    assert!(mock.requests()[3].response_format.is_some());
+    let mut stream = agent.chat("thanks");
+    while futures::StreamExt::next(&mut stream).await.is_some() {}
+    let format = mock.requests()[4].response_format.clone().unwrap();
+    assert!(matches!(format.r#type, ResponseFormatType::JsonObject));
+}
+
+#[tokio::test]
+async fn agent_rejects_gated_tool_calls_instead_of_waiting() {
+    let mock = MockBackend::new()
+        .reply(MockReply::tool_call(
+            "call_1",
+            "population",
+            json!({ "city": "Tokyo" }),
+        ))
+        .reply(MockReply::text(r#"{"name": "Tokyo", "population": 0}"#));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .add_tool(Census)
+        .with_approval(ApprovalPolicy::new().require("population"));
+
+    let (city, agent) = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        agent.chat_typed::<City>("The largest city in Japan?"),
+    )
+    .await
+    .expect("chat_typed waited for an approval");
+    assert_eq!(city.unwrap().population, 0);
+    let result = agent.history()[2].content.as_deref().unwrap();
+    assert!(result.contains("rejected by user") && result.contains("typed run"));
+}
+
+#[tokio::test]
+async fn agent_does_not_reask_after_a_budget_stop() {
+    let mock = MockBackend::new()
+        .reply(MockReply::tool_call(
+            "call_1",
+            "population",
+            json!({ "city": "Tokyo" }),
+        ))
+        .reply(MockReply::text("never sent"));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .add_tool(Census)
+        .with_budget(Budget::new().max_turns(1));
+
+    let (city, _) = agent.chat_typed::<City>("The largest city in Japan?").await;
+    assert!(matches!(
+        city,
+        Err(ApiError::BudgetExceeded(BudgetLimit::Turns { limit: 1 }))
+    ));
+    assert_eq!(mock.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn agent_does_not_reask_after_the_step_limit() {
+    let mock = MockBackend::new()
+        .reply(MockReply::tool_call(
+            "call_1",
+            "population",
+            json!({ "city": "Tokyo" }),
+        ))
+        .reply(MockReply::text("Tokyo, I think"))
+        .reply(MockReply::text("never sent"));
+    let agent = DeepseekAgent::from_client(mock.clone(), "deepseek-chat")
+        .add_tool(Census)
+        .with_max_steps(1);
+
+    let (city, _) = agent.chat_typed::<City>("The largest city in Japan?").await;
+    assert!(matches!(
+        city,
+        Err(ApiError::MaxStepsReached { max_steps: 1 })
+    ));
+    assert_eq!(mock.requests().len(), 2);
+}
@@ -0,0 +1,116 @@
+//! Integration tests for the per-turn `Usage` and `TurnFinished` agent events.
+
+use ds_api::raw::FinishReason;
+use ds_api::{AgentEvent, ApiClient, DeepseekAgent, MockBackend, MockReply};
+use futures::StreamExt;
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn run(agent: DeepseekAgent) -> Vec<AgentEvent> {
+    let mut stream = agent.chat("hi");
+    let mut events = Vec::new();
+    while let Some(ev) = stream.next().await {
+        events.push(ev.unwrap());
+    }
+    events
+}
+
+#[tokio::test]
+async fn streaming_turn_reports_usage_and_truncation() {
+    let chunk = |choices: serde_json::Value, usage: serde_json::Value| {
+        let chunk = json!({
+            "id": "c",
+            "object": "chat.completion.chunk",
+            "created": 0,
+            "model": "deepseek-reasoner",
+            "choices": choices,
+            "usage": usage,
+        });
+        format!("data: {chunk}\n\n")
+    };
+    let body = [
+        chunk(
+            json!([{ "index": 0, "delta": { "content": "Once upon" }, "finish_reason": null }]),
+            json!(null),
+        ),
+        chunk(
+            json!([{ "index": 0, "delta": { "content": "" }, "finish_reason": "length" }]),
+            json!(null),
+        ),
+        // DeepSeek sends usage on a trailing chunk with no choices.
+        chunk(
+            json!([]),
+            json!({
+                "prompt_tokens": 20,
+                "completion_tokens": 8,
+                "total_tokens": 28,
+                "prompt_cache_hit_tokens": 16,
+                "prompt_cache_miss_tokens": 4,
+                "completion_tokens_details": { "reasoning_tokens": 6 }
+            }),
+        ),
+        "data: [DONE]\n\n".to_string(),
+    ]
+    .concat();
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/chat/completions"))
+        .and(body_partial_json(
+            json!({ "stream": true, "stream_options": { "include_usage": true } }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&server)
+        .await;
+
+    let client = ApiClient::new("k").with_base_url(server.uri());
+    let agent = DeepseekAgent::from_client(client, "deepseek-reasoner").with_streaming();
+    let events = run(agent).await;
+
+    let n = events.len();
+    let AgentEvent::Usage(usage) = &events[n - 2] else {
+        panic!("expected Usage, got {:?}", events[n - 2]);
+    };
+    assert_eq!(usage.total_tokens, 28);
+    assert_eq!(usage.prompt_cache_hit_tokens, Some(16));
+    assert_eq!(usage.reasoning_tokens(), 6);
+    assert!(matches!(
+        events[n - 1],
+        AgentEvent::TurnFinished {
+            finish_reason: Some(FinishReason::Length)
+        }
+    ));
+}
+
+#[tokio::test]
+async fn every_turn_ends_with_usage_then_finish_reason() {
+    let mock = MockBackend::new()
+        .reply(MockReply::tool_call("call_1", "missing", json!({})).with_usage(10, 2))
+        .reply(MockReply::text("done").with_usage(15, 3));
+    let agent = DeepseekAgent::from_client(mock, "deepseek-chat");
+    let events = run(agent).await;
+
+    let ends: Vec<_> = events
+        .iter()
+        .filter_map(|e| match e {
+            AgentEvent::Usage(u) => Some(format!("usage {}", u.total_tokens)),
+            AgentEvent::TurnFinished { finish_reason } => Some(format!("{finish_reason:?}")),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        ends,
+        ["usage 12", "Some(ToolCalls)", "usage 18", "Some(Stop)"]
+    );
+    // The first turn's events come before its tool result.
+    let finished = events
+        .iter()
+        .position(|e| matches!(e, AgentEvent::TurnFinished { .. }))
+        .unwrap();
+    let result = events
+        .iter()
+        .position(|e| matches!(e, AgentEvent::ToolResult(_)))
+        .unwrap();
+    assert!(finished < result);
+}